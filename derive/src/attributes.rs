@@ -0,0 +1,126 @@
+use syn::{
+    parenthesized, punctuated::Punctuated, spanned::Spanned, Attribute, Data, DeriveInput, Expr,
+    Field, Fields, Ident, LitStr, Token,
+};
+
+/// Options from the container-level `#[dynamo(...)]` attribute.
+#[derive(Default)]
+pub(crate) struct ContainerOptions {
+    pub(crate) table: Option<LitStr>,
+    pub(crate) pk: Option<Ident>,
+    pub(crate) sk: Option<Ident>,
+    pub(crate) key: Option<Ident>,
+}
+
+impl ContainerOptions {
+    /// Reads the container attribute and the `#[dynamo(pk)]`/`#[dynamo(sk)]` field markers.
+    pub(crate) fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        for attribute in input
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("dynamo"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                let value = || -> syn::Result<LitStr> { meta.value()?.parse() };
+
+                if meta.path.is_ident("table") {
+                    options.table = Some(value()?);
+                } else if meta.path.is_ident("pk") {
+                    options.pk = Some(value()?.parse()?);
+                } else if meta.path.is_ident("sk") {
+                    options.sk = Some(value()?.parse()?);
+                } else if meta.path.is_ident("key") {
+                    options.key = Some(value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `table`, `pk`, `sk` or `key`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        if let Data::Struct(data) = &input.data {
+            for field in &data.fields {
+                parse_field_markers(field, &mut options)?;
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_field_markers(field: &Field, options: &mut ContainerOptions) -> syn::Result<()> {
+    for attribute in field
+        .attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("dynamo"))
+    {
+        let ident = field.ident.clone();
+        attribute.parse_nested_meta(|meta| {
+            let slot = if meta.path.is_ident("pk") {
+                &mut options.pk
+            } else if meta.path.is_ident("sk") {
+                &mut options.sk
+            } else {
+                return Err(meta.error("expected `pk` or `sk`"));
+            };
+
+            if slot.is_some() {
+                return Err(meta.error("key attribute is declared more than once"));
+            }
+            *slot = ident.clone();
+            Ok(())
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn named_fields(input: &DeriveInput) -> syn::Result<&Punctuated<Field, Token![,]>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new(
+                input.span(),
+                "only structs with named fields are supported",
+            )),
+        },
+        _ => Err(syn::Error::new(input.span(), "only structs are supported")),
+    }
+}
+
+pub(crate) fn find_field<'a>(
+    fields: &'a Punctuated<Field, Token![,]>,
+    name: &Ident,
+) -> syn::Result<&'a Field> {
+    fields
+        .iter()
+        .find(|field| field.ident.as_ref() == Some(name))
+        .ok_or_else(|| syn::Error::new(name.span(), format!("no field named `{name}`")))
+}
+
+/// The `rename_all` value of the container's `#[serde(...)]` attributes, which the
+/// generated key struct has to share to serialize to the same attribute names.
+pub(crate) fn serde_rename_all(attributes: &[Attribute]) -> syn::Result<Option<LitStr>> {
+    let mut rename_all = None;
+
+    for attribute in attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("serde"))
+    {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") && meta.input.peek(Token![=]) {
+                rename_all = Some(meta.value()?.parse()?);
+            } else if meta.input.peek(Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let _content;
+                parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(rename_all)
+}
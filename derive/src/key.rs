@@ -0,0 +1,82 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, DeriveInput, Ident};
+
+use crate::attributes::{find_field, named_fields, serde_rename_all, ContainerOptions};
+
+pub(crate) struct KeyStruct {
+    pub(crate) ident: Ident,
+    /// The struct definition.
+    pub(crate) definition: TokenStream,
+    /// An expression building the key from `self`.
+    pub(crate) constructor: TokenStream,
+}
+
+/// Builds a key struct holding clones of the `pk` (and `sk`) fields, with the same serde
+/// attributes so it serializes to the item's key attribute names.
+pub(crate) fn key_struct(
+    input: &DeriveInput,
+    options: &ContainerOptions,
+) -> syn::Result<KeyStruct> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "generic structs are not supported",
+        ));
+    }
+
+    let fields = named_fields(input)?;
+    let pk = options
+        .pk
+        .as_ref()
+        .ok_or_else(|| syn::Error::new(input.ident.span(), "missing `#[dynamo(pk = \"...\")]`"))?;
+    let mut key_fields = vec![find_field(fields, pk)?];
+    if let Some(sk) = &options.sk {
+        key_fields.push(find_field(fields, sk)?);
+    }
+
+    let ident = options
+        .key
+        .clone()
+        .unwrap_or_else(|| format_ident!("{}Key", input.ident));
+    let vis = &input.vis;
+    let rename_all = serde_rename_all(&input.attrs)?
+        .map(|rename_all| quote!(#[serde(rename_all = #rename_all)]));
+
+    let definitions = key_fields.iter().map(|field| {
+        let name = &field.ident;
+        let ty = &field.ty;
+        let serde_attributes = field
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("serde"));
+        quote!(#(#serde_attributes)* #vis #name: #ty)
+    });
+    let names: Vec<_> = key_fields.iter().map(|field| &field.ident).collect();
+
+    let definition = quote! {
+        #[derive(
+            Debug,
+            Clone,
+            PartialEq,
+            ::clean_dynamodb_store::__private::serde::Serialize,
+            ::clean_dynamodb_store::__private::serde::Deserialize,
+        )]
+        #[serde(crate = "::clean_dynamodb_store::__private::serde")]
+        #rename_all
+        #vis struct #ident {
+            #(#definitions,)*
+        }
+    };
+    let constructor = quote! {
+        #ident {
+            #(#names: ::core::clone::Clone::clone(&self.#names),)*
+        }
+    };
+
+    Ok(KeyStruct {
+        ident,
+        definition,
+        constructor,
+    })
+}
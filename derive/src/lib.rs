@@ -0,0 +1,99 @@
+mod attributes;
+mod key;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
+
+use crate::{attributes::ContainerOptions, key::key_struct};
+
+/// Implements `DynamoEntity` and generates the entity's key struct.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, DynamoEntity)]
+/// #[dynamo(table = "users", pk = "id", sk = "sk")]
+/// struct User { id: String, sk: String, name: String }
+/// ```
+///
+/// The key struct is named `UserKey` unless `key = "..."` names it otherwise. Key
+/// fields can also be marked with `#[dynamo(pk)]` and `#[dynamo(sk)]`.
+#[proc_macro_derive(DynamoEntity, attributes(dynamo))]
+pub fn derive_dynamo_entity(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    dynamo_entity(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generates the key struct of an item type and an inherent `key()` accessor, without
+/// binding the type to a table. Don't combine it with `DynamoEntity`, which already
+/// generates the key struct.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, DynamoKey)]
+/// struct Order {
+///     #[dynamo(pk)]
+///     customer_id: String,
+///     #[dynamo(sk)]
+///     order_id: String,
+///     total: u64,
+/// }
+///
+/// let key: OrderKey = order.key();
+/// ```
+#[proc_macro_derive(DynamoKey, attributes(dynamo))]
+pub fn derive_dynamo_key(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    dynamo_key(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn dynamo_entity(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let options = ContainerOptions::parse(input)?;
+    let table = options.table.as_ref().ok_or_else(|| {
+        syn::Error::new(input.ident.span(), "missing `#[dynamo(table = \"...\")]`")
+    })?;
+    let key = key_struct(input, &options)?;
+
+    let ident = &input.ident;
+    let key_ident = &key.ident;
+    let definition = &key.definition;
+    let constructor = &key.constructor;
+
+    Ok(quote! {
+        #definition
+
+        impl ::clean_dynamodb_store::DynamoEntity for #ident {
+            const TABLE: &'static str = #table;
+            type Key = #key_ident;
+
+            fn key(&self) -> #key_ident {
+                #constructor
+            }
+        }
+    })
+}
+
+fn dynamo_key(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let options = ContainerOptions::parse(input)?;
+    let key = key_struct(input, &options)?;
+
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let key_ident = &key.ident;
+    let definition = &key.definition;
+    let constructor = &key.constructor;
+
+    Ok(quote! {
+        #definition
+
+        impl #ident {
+            #vis fn key(&self) -> #key_ident {
+                #constructor
+            }
+        }
+    })
+}
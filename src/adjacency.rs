@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{keys::raw_prefixed, DynamoDbStore, Error};
+
+const DEFAULT_EDGE_PREFIX: &str = "EDGE";
+
+/// Graph edges stored as adjacency lists: one item per edge with `PK = source` and
+/// `SK = EDGE#target`.
+///
+/// Incoming edges are read from an inverted index (partition key `SK`, sort key `PK`)
+/// configured with [`AdjacencyList::with_reverse_index`].
+#[derive(Debug, Clone)]
+pub struct AdjacencyList {
+    store: DynamoDbStore,
+    table_name: String,
+    partition_key: String,
+    sort_key: String,
+    edge_prefix: String,
+    reverse_index: Option<String>,
+}
+
+/// An edge read back from the table, with the item's remaining attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub source: String,
+    pub target: String,
+    pub attributes: HashMap<String, AttributeValue>,
+}
+
+impl Edge {
+    /// Deserializes the attributes stored on the edge.
+    pub fn data<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        Ok(serde_dynamo::from_item(self.attributes.clone())?)
+    }
+
+    /// Like [`data`](Self::data), but consumes the edge instead of copying its
+    /// attributes, for reading many edges.
+    pub fn into_data<T: DeserializeOwned>(self) -> Result<T, Error> {
+        Ok(serde_dynamo::from_item(self.attributes)?)
+    }
+}
+
+impl DynamoDbStore {
+    pub fn adjacency_list(&self, table_name: impl Into<String>) -> AdjacencyList {
+        AdjacencyList {
+            store: self.clone(),
+            table_name: table_name.into(),
+            partition_key: "PK".to_string(),
+            sort_key: "SK".to_string(),
+            edge_prefix: DEFAULT_EDGE_PREFIX.to_string(),
+            reverse_index: None,
+        }
+    }
+}
+
+impl AdjacencyList {
+    pub fn with_key_attributes(
+        mut self,
+        partition_key: impl Into<String>,
+        sort_key: impl Into<String>,
+    ) -> Self {
+        self.partition_key = partition_key.into();
+        self.sort_key = sort_key.into();
+        self
+    }
+
+    pub fn with_edge_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.edge_prefix = prefix.into();
+        self
+    }
+
+    pub fn with_reverse_index(mut self, index_name: impl Into<String>) -> Self {
+        self.reverse_index = Some(index_name.into());
+        self
+    }
+
+    pub async fn add_edge(&self, source: &str, target: &str) -> Result<(), Error> {
+        self.put_edge(source, target, HashMap::new()).await
+    }
+
+    /// Adds an edge carrying the fields of `data`, e.g. the role of a membership.
+    pub async fn add_edge_with<T: Serialize>(
+        &self,
+        source: &str,
+        target: &str,
+        data: &T,
+    ) -> Result<(), Error> {
+        self.put_edge(source, target, serde_dynamo::to_item(data)?)
+            .await
+    }
+
+    pub async fn remove_edge(&self, source: &str, target: &str) -> Result<(), Error> {
+        self.store
+            .delete_item(&self.table_name, self.key(source, target))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn edge(&self, source: &str, target: &str) -> Result<Option<Edge>, Error> {
+        match self
+            .store
+            .get_item(&self.table_name, self.key(source, target))
+            .await?
+        {
+            Some(item) => self.edge_from_item("GetItem", item).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the outgoing edges of `source`.
+    pub async fn edges_of(&self, source: &str) -> Result<Vec<Edge>, Error> {
+        let items = self
+            .store
+            .query(&self.table_name)
+            .partition_key(&self.partition_key, AttributeValue::S(source.to_string()))
+            .sort_key_begins_with(&self.sort_key, raw_prefixed(&self.edge_prefix, ""))
+            .all()
+            .await?;
+
+        items
+            .into_iter()
+            .map(|item| self.edge_from_item("Query", item))
+            .collect()
+    }
+
+    /// Returns the edges pointing at `target`, read from the reverse index.
+    pub async fn edges_to(&self, target: &str) -> Result<Vec<Edge>, Error> {
+        let Some(index_name) = &self.reverse_index else {
+            return Err(Error::Validation(
+                "reverse lookups need an index configured with `with_reverse_index`".to_string(),
+            ));
+        };
+
+        let items = self
+            .store
+            .query(&self.table_name)
+            .index(index_name)
+            .partition_key(
+                &self.sort_key,
+                AttributeValue::S(raw_prefixed(&self.edge_prefix, target)),
+            )
+            .all()
+            .await?;
+
+        items
+            .into_iter()
+            .map(|item| self.edge_from_item("Query", item))
+            .collect()
+    }
+
+    async fn put_edge(
+        &self,
+        source: &str,
+        target: &str,
+        mut item: HashMap<String, AttributeValue>,
+    ) -> Result<(), Error> {
+        item.extend(self.key(source, target));
+        self.store.put_item(&self.table_name, item).await?;
+        Ok(())
+    }
+
+    fn key(&self, source: &str, target: &str) -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            (
+                self.partition_key.clone(),
+                AttributeValue::S(source.to_string()),
+            ),
+            (
+                self.sort_key.clone(),
+                AttributeValue::S(raw_prefixed(&self.edge_prefix, target)),
+            ),
+        ])
+    }
+
+    fn edge_from_item(
+        &self,
+        operation: &'static str,
+        mut item: HashMap<String, AttributeValue>,
+    ) -> Result<Edge, Error> {
+        let source = match item.remove(&self.partition_key) {
+            Some(AttributeValue::S(source)) => source,
+            _ => return Err(self.malformed_edge(operation)),
+        };
+        let target = match item.remove(&self.sort_key) {
+            Some(AttributeValue::S(sort_key)) => sort_key
+                .strip_prefix(&raw_prefixed(&self.edge_prefix, ""))
+                .map(str::to_string)
+                .ok_or_else(|| self.malformed_edge(operation))?,
+            _ => return Err(self.malformed_edge(operation)),
+        };
+
+        Ok(Edge {
+            source,
+            target,
+            attributes: item,
+        })
+    }
+
+    fn malformed_edge(&self, operation: &'static str) -> Error {
+        Error::UnexpectedResponse {
+            operation,
+            reason: format!(
+                "edge item lacks a string `{}` or an `{}`-prefixed `{}`",
+                self.partition_key, self.edge_prefix, self.sort_key
+            ),
+        }
+    }
+}
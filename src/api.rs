@@ -0,0 +1,610 @@
+use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    batch::{BatchGetResult, BatchWriteResult},
+    entity::DynamoEntity,
+    filter::Filter,
+    naming::NamingConvention,
+    page::Page,
+    query::SortKeyCondition,
+    table::TableBoundStore,
+    DynamoDbStore, Error,
+};
+
+pub type Item = HashMap<String, AttributeValue>;
+
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>;
+
+/// The core item operations as an object-safe trait, so application code can depend on
+/// `Arc<dyn DynamoStoreApi>` and swap in another implementation in tests.
+pub trait DynamoStoreApi: Send + Sync {
+    /// Writes `item`, failing with a conditional check error if `condition` does not
+    /// hold for the stored item.
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()>;
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>>;
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()>;
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page>;
+
+    /// How the typed helpers of [`DynamoStoreApiExt`] spell attribute names, so that they
+    /// write the same items as the store's own `save`; names are kept as they are by
+    /// default.
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        Box::pin(async { Ok(NamingConvention::AsIs) })
+    }
+
+    /// Writes `items`; the default implementation puts them one at a time.
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            let processed = items.len();
+            for item in items {
+                self.put_item(table_name, item, None).await?;
+            }
+            Ok(BatchWriteResult {
+                processed,
+                ..BatchWriteResult::default()
+            })
+        })
+    }
+
+    /// Deletes `keys`; the default implementation deletes them one at a time.
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            let processed = keys.len();
+            for key in keys {
+                self.delete_item(table_name, key, None).await?;
+            }
+            Ok(BatchWriteResult {
+                processed,
+                ..BatchWriteResult::default()
+            })
+        })
+    }
+
+    /// Reads `keys`; the default implementation gets them one at a time.
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        Box::pin(async move {
+            let mut result = BatchGetResult::default();
+            for key in keys {
+                match self.get_item(table_name, key.clone()).await? {
+                    Some(item) => result.items.push(item),
+                    None => result.not_found.push(key),
+                }
+            }
+            Ok(result)
+        })
+    }
+}
+
+/// A single-page query through [`DynamoStoreApi::query_page`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryRequest {
+    pub table_name: String,
+    pub index_name: Option<String>,
+    pub partition_key: (String, AttributeValue),
+    pub sort_key: Option<(String, SortKeyCondition)>,
+    pub filter: Option<Filter>,
+    pub limit: Option<i32>,
+    pub scan_forward: bool,
+    pub exclusive_start_key: Option<Item>,
+    pub consistent_read: Option<bool>,
+}
+
+impl QueryRequest {
+    pub fn new(
+        table_name: impl Into<String>,
+        partition_key: impl Into<String>,
+        value: AttributeValue,
+    ) -> Self {
+        Self {
+            table_name: table_name.into(),
+            index_name: None,
+            partition_key: (partition_key.into(), value),
+            sort_key: None,
+            filter: None,
+            limit: None,
+            scan_forward: true,
+            exclusive_start_key: None,
+            consistent_read: None,
+        }
+    }
+
+    pub fn index(mut self, index_name: impl Into<String>) -> Self {
+        self.index_name = Some(index_name.into());
+        self
+    }
+
+    pub fn sort_key(mut self, name: impl Into<String>, condition: SortKeyCondition) -> Self {
+        self.sort_key = Some((name.into(), condition));
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn reverse(mut self) -> Self {
+        self.scan_forward = false;
+        self
+    }
+
+    pub fn start_after(mut self, key: Item) -> Self {
+        self.exclusive_start_key = Some(key);
+        self
+    }
+
+    pub fn consistent_read(mut self, consistent_read: bool) -> Self {
+        self.consistent_read = Some(consistent_read);
+        self
+    }
+}
+
+/// Typed helpers available on every [`DynamoStoreApi`], including `dyn DynamoStoreApi`.
+pub trait DynamoStoreApiExt: DynamoStoreApi {
+    fn save<'a, T: DynamoEntity + Sync>(&'a self, entity: &'a T) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let naming = self.naming().await?;
+            let item = naming.rename_item(serde_dynamo::to_item(entity)?);
+            self.put_item(T::TABLE, item, None).await
+        })
+    }
+
+    fn load<'a, T: DynamoEntity>(&'a self, key: &'a T::Key) -> StoreFuture<'a, Option<T>>
+    where
+        T::Key: Sync,
+    {
+        Box::pin(async move {
+            let naming = self.naming().await?;
+            let key = naming.rename_item(serde_dynamo::to_item(key)?);
+            match self.get_item(T::TABLE, key).await? {
+                Some(item) => Ok(Some(serde_dynamo::from_item(naming.restore_item(item))?)),
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn remove<'a, T: DynamoEntity + Sync>(&'a self, entity: &'a T) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let naming = self.naming().await?;
+            let key = naming.rename_item(serde_dynamo::to_item(entity.key())?);
+            self.delete_item(T::TABLE, key, None).await
+        })
+    }
+
+    /// Follows `last_evaluated_key` until every page of `request` has been read.
+    fn query_all<'a>(&'a self, mut request: QueryRequest) -> StoreFuture<'a, Vec<Item>> {
+        Box::pin(async move {
+            let mut items = Vec::new();
+            loop {
+                let page = self.query_page(&request).await?;
+                items.extend(page.items);
+
+                match page.last_evaluated_key {
+                    Some(key) => request.exclusive_start_key = Some(key),
+                    None => return Ok(items),
+                }
+            }
+        })
+    }
+}
+
+impl<S: DynamoStoreApi + ?Sized> DynamoStoreApiExt for S {}
+
+impl DynamoStoreApi for DynamoDbStore {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.put_item_if(table_name, item, condition, self.defaults())
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        Box::pin(DynamoDbStore::get_item(self, table_name, key))
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.delete_item_if(table_name, key, condition, self.defaults())
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        Box::pin(async move {
+            let (partition_key, value) = request.partition_key.clone();
+            let mut query = self
+                .query(&request.table_name)
+                .partition_key(partition_key, value);
+            if let Some(index_name) = &request.index_name {
+                query = query.index(index_name);
+            }
+            if let Some((name, condition)) = &request.sort_key {
+                query = query.sort_key(name, condition.clone());
+            }
+            if let Some(filter) = &request.filter {
+                query = query.filter(filter.clone());
+            }
+            if let Some(limit) = request.limit {
+                query = query.limit(limit);
+            }
+            if !request.scan_forward {
+                query = query.reverse();
+            }
+            if let Some(key) = &request.exclusive_start_key {
+                query = query.start_after(key.clone());
+            }
+            if let Some(consistent_read) = request.consistent_read {
+                query = query.consistent_read(consistent_read);
+            }
+
+            query.page().await
+        })
+    }
+
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        Box::pin(async { Ok(DynamoDbStore::naming(self)) })
+    }
+
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(DynamoDbStore::batch_put_items(self, table_name, items))
+    }
+
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(DynamoDbStore::batch_delete_items(self, table_name, keys))
+    }
+
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        Box::pin(DynamoDbStore::batch_get_items(self, table_name, keys))
+    }
+}
+
+/// Sends calls like the methods of the table: items and keys are renamed to the table's
+/// naming convention, items follow its empty value policy, and the table's request
+/// defaults and retry settings apply. The table name of each call is used as given.
+/// Query key names are renamed too, while filter attribute names are used as given.
+impl DynamoStoreApi for TableBoundStore {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let item = self.prepare_item(item)?;
+            self.run(|| {
+                self.store().put_item_if(
+                    table_name,
+                    item.clone(),
+                    condition.clone(),
+                    self.defaults(),
+                )
+            })
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        Box::pin(async move {
+            let key = self.naming().rename_item(key);
+            let output = self
+                .run(|| {
+                    self.store()
+                        .get_item_with_options(table_name, key.clone(), self.defaults())
+                })
+                .await?;
+            Ok(output.item.map(|item| self.naming().restore_item(item)))
+        })
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let key = self.naming().rename_item(key);
+            self.run(|| {
+                self.store().delete_item_if(
+                    table_name,
+                    key.clone(),
+                    condition.clone(),
+                    self.defaults(),
+                )
+            })
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        Box::pin(async move {
+            let naming = self.naming();
+            let (partition_key, value) = request.partition_key.clone();
+            let mut query = self
+                .store()
+                .query(&request.table_name)
+                .defaults(self.defaults())
+                .partition_key(naming.rename(&partition_key), value);
+            if let Some(index_name) = &request.index_name {
+                query = query.index(index_name);
+            }
+            if let Some((name, condition)) = &request.sort_key {
+                query = query.sort_key(naming.rename(name), condition.clone());
+            }
+            if let Some(filter) = &request.filter {
+                query = query.filter(filter.clone());
+            }
+            if let Some(limit) = request.limit {
+                query = query.limit(limit);
+            }
+            if !request.scan_forward {
+                query = query.reverse();
+            }
+            if let Some(key) = &request.exclusive_start_key {
+                query = query.start_after(key.clone());
+            }
+            if let Some(consistent_read) = request.consistent_read {
+                query = query.consistent_read(consistent_read);
+            }
+
+            let mut page = self.run(|| query.page()).await?;
+            page.items = page
+                .items
+                .into_iter()
+                .map(|item| naming.restore_item(item))
+                .collect();
+            Ok(page)
+        })
+    }
+
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        Box::pin(async { Ok(TableBoundStore::naming(self)) })
+    }
+
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            let items = items
+                .into_iter()
+                .map(|item| self.prepare_item(item))
+                .collect::<Result<Vec<_>, Error>>()?;
+            self.store()
+                .batch_put_items_with_retry(table_name, items, &self.batch_retry())
+                .await
+        })
+    }
+
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            let keys = keys
+                .into_iter()
+                .map(|key| self.naming().rename_item(key))
+                .collect();
+            self.store()
+                .batch_delete_items_with_retry(table_name, keys, &self.batch_retry())
+                .await
+        })
+    }
+
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        Box::pin(async move {
+            let naming = self.naming();
+            let keys = keys
+                .into_iter()
+                .map(|key| naming.rename_item(key))
+                .collect();
+            let mut result = self
+                .store()
+                .batch_get_items_with_retry(table_name, keys, &self.batch_retry())
+                .await?;
+            result.items = result
+                .items
+                .into_iter()
+                .map(|item| naming.restore_item(item))
+                .collect();
+            Ok(result)
+        })
+    }
+}
+
+impl<S: DynamoStoreApi + ?Sized> DynamoStoreApi for Arc<S> {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        (**self).put_item(table_name, item, condition)
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        (**self).get_item(table_name, key)
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        (**self).delete_item(table_name, key, condition)
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        (**self).query_page(request)
+    }
+
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        (**self).naming()
+    }
+
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        (**self).batch_put_items(table_name, items)
+    }
+
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        (**self).batch_delete_items(table_name, keys)
+    }
+
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        (**self).batch_get_items(table_name, keys)
+    }
+}
+
+#[cfg(feature = "mockall")]
+mockall::mock! {
+    /// A mockall mock of [`DynamoStoreApi`]. Expectations return the result directly,
+    /// e.g. `mock.expect_get_item().returning(|_, _| Ok(None))`.
+    pub DynamoStore {
+        pub fn put_item(
+            &self,
+            table_name: &str,
+            item: Item,
+            condition: Option<Filter>,
+        ) -> Result<(), Error>;
+        pub fn get_item(&self, table_name: &str, key: Item) -> Result<Option<Item>, Error>;
+        pub fn delete_item(
+            &self,
+            table_name: &str,
+            key: Item,
+            condition: Option<Filter>,
+        ) -> Result<(), Error>;
+        pub fn query_page(&self, request: &QueryRequest) -> Result<Page, Error>;
+    }
+}
+
+#[cfg(feature = "mockall")]
+impl DynamoStoreApi for MockDynamoStore {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        let result = MockDynamoStore::put_item(self, table_name, item, condition);
+        Box::pin(async move { result })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        let result = MockDynamoStore::get_item(self, table_name, key);
+        Box::pin(async move { result })
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        let result = MockDynamoStore::delete_item(self, table_name, key, condition);
+        Box::pin(async move { result })
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        let result = MockDynamoStore::query_page(self, request);
+        Box::pin(async move { result })
+    }
+}
+
+pub(crate) type RenderedCondition = (
+    Option<String>,
+    Option<HashMap<String, String>>,
+    Option<HashMap<String, AttributeValue>>,
+);
+
+pub(crate) fn render_condition(condition: Option<Filter>) -> RenderedCondition {
+    let Some(condition) = condition else {
+        return (None, None, None);
+    };
+
+    let mut names = HashMap::new();
+    let mut values = HashMap::new();
+    let expression = condition.render(&mut names, &mut values);
+
+    (
+        Some(expression),
+        Some(names),
+        (!values.is_empty()).then_some(values),
+    )
+}
@@ -0,0 +1,487 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    api::{DynamoStoreApi, Item, QueryRequest, StoreFuture},
+    batch::{BatchGetResult, BatchWriteResult},
+    filter::Filter,
+    keys::ulid,
+    naming::NamingConvention,
+    page::Page,
+    Error,
+};
+
+/// Partition key (`S`) of audit tables; a ULID, so ids sort by time.
+pub const AUDIT_ID_ATTRIBUTE: &str = "audit_id";
+const TABLE_ATTRIBUTE: &str = "table_name";
+const OPERATION_ATTRIBUTE: &str = "operation";
+const ACTOR_ATTRIBUTE: &str = "actor";
+const RECORDED_AT_ATTRIBUTE: &str = "recorded_at";
+const KEY_ATTRIBUTE: &str = "key";
+const BEFORE_ATTRIBUTE: &str = "before";
+const AFTER_ATTRIBUTE: &str = "after";
+
+type Actor = Arc<dyn Fn() -> Option<String> + Send + Sync>;
+
+/// Wraps a [`DynamoStoreApi`] and records every successful put and delete in an
+/// append-only audit table.
+///
+/// Each record holds the table, the operation, the actor returned by the
+/// [`actor`](Self::actor) hook, a timestamp in epoch milliseconds, and the item
+/// written (`after`) or the key deleted (`key`). With
+/// [`capture_before_images`](Self::capture_before_images), the stored item is read
+/// first and recorded as `before`. Reads pass through unchanged. Record attributes
+/// are spelled in the inner store's [`naming`](DynamoStoreApi::naming) convention.
+///
+/// A mutation that succeeded but could not be audited fails with the audit error.
+pub struct AuditedStore<S> {
+    inner: S,
+    audit_table: String,
+    actor: Option<Actor>,
+    before_images: bool,
+    key_names: HashMap<String, Vec<String>>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for AuditedStore<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditedStore")
+            .field("inner", &self.inner)
+            .field("audit_table", &self.audit_table)
+            .field("actor", &self.actor.is_some())
+            .field("before_images", &self.before_images)
+            .field("key_names", &self.key_names)
+            .finish()
+    }
+}
+
+impl<S: DynamoStoreApi> AuditedStore<S> {
+    /// Audits into `audit_table`, which needs [`AUDIT_ID_ATTRIBUTE`], in the inner
+    /// store's naming convention, as partition key.
+    pub fn new(inner: S, audit_table: impl Into<String>) -> Self {
+        Self {
+            inner,
+            audit_table: audit_table.into(),
+            actor: None,
+            before_images: false,
+            key_names: HashMap::new(),
+        }
+    }
+
+    /// Names the actor of each mutation, e.g. the user of the current request from a
+    /// task-local. Records of calls where it returns `None` carry no actor.
+    pub fn actor(mut self, actor: impl Fn() -> Option<String> + Send + Sync + 'static) -> Self {
+        self.actor = Some(Arc::new(actor));
+        self
+    }
+
+    /// Reads the stored item before each single-item delete, and each put to a table
+    /// registered with [`key_names`](Self::key_names), to record it as `before`, at the
+    /// cost of one extra read per write. Batch writes never do.
+    pub fn capture_before_images(mut self, before_images: bool) -> Self {
+        self.before_images = before_images;
+        self
+    }
+
+    /// Names the key attributes of `table_name`, which puts need to look up the item
+    /// they replace.
+    pub fn key_names(
+        mut self,
+        table_name: impl Into<String>,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.key_names.insert(
+            table_name.into(),
+            names.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    async fn before_image(&self, table_name: &str, key: &Item) -> Result<Option<Item>, Error> {
+        if self.before_images {
+            self.inner.get_item(table_name, key.clone()).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn record(
+        &self,
+        table_name: &str,
+        operation: &str,
+        key: Option<Item>,
+        before: Option<Item>,
+        after: Option<Item>,
+    ) -> Item {
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let mut record = HashMap::from([
+            (AUDIT_ID_ATTRIBUTE.to_string(), AttributeValue::S(ulid())),
+            (
+                TABLE_ATTRIBUTE.to_string(),
+                AttributeValue::S(table_name.to_string()),
+            ),
+            (
+                OPERATION_ATTRIBUTE.to_string(),
+                AttributeValue::S(operation.to_string()),
+            ),
+            (
+                RECORDED_AT_ATTRIBUTE.to_string(),
+                AttributeValue::N(recorded_at.to_string()),
+            ),
+        ]);
+        if let Some(actor) = self.actor.as_ref().and_then(|actor| actor()) {
+            record.insert(ACTOR_ATTRIBUTE.to_string(), AttributeValue::S(actor));
+        }
+        for (name, image) in [
+            (KEY_ATTRIBUTE, key),
+            (BEFORE_ATTRIBUTE, before),
+            (AFTER_ATTRIBUTE, after),
+        ] {
+            if let Some(image) = image {
+                record.insert(name.to_string(), AttributeValue::M(image));
+            }
+        }
+        record
+    }
+
+    /// Appends one record; the condition keeps an id collision from overwriting another.
+    async fn append(&self, record: Item) -> Result<(), Error> {
+        // The condition is passed on as written, so it has to name the attribute the
+        // way a renaming store like `TableBoundStore` stores it.
+        let naming = self.inner.naming().await?;
+        self.inner
+            .put_item(
+                &self.audit_table,
+                naming.rename_item(record),
+                Some(Filter::not_exists(naming.rename(AUDIT_ID_ATTRIBUTE))),
+            )
+            .await
+    }
+
+    async fn append_all(&self, records: Vec<Item>) -> Result<(), Error> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let naming = self.inner.naming().await?;
+        let records = records
+            .into_iter()
+            .map(|record| naming.rename_item(record))
+            .collect();
+        let result = self
+            .inner
+            .batch_put_items(&self.audit_table, records)
+            .await?;
+        if result.unprocessed.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnexpectedResponse {
+                operation: "BatchWriteItem",
+                reason: format!(
+                    "{} audit records stayed unprocessed after retries",
+                    result.unprocessed.len()
+                ),
+            })
+        }
+    }
+}
+
+impl<S: DynamoStoreApi> DynamoStoreApi for AuditedStore<S> {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let key: Option<Item> = self.key_names.get(table_name).map(|names| {
+                item.iter()
+                    .filter(|(name, _)| names.contains(name))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            });
+            let before = match &key {
+                Some(key) => self.before_image(table_name, key).await?,
+                None => None,
+            };
+            self.inner
+                .put_item(table_name, item.clone(), condition)
+                .await?;
+            self.append(self.record(table_name, "PutItem", key, before, Some(item)))
+                .await
+        })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        self.inner.get_item(table_name, key)
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let before = self.before_image(table_name, &key).await?;
+            self.inner
+                .delete_item(table_name, key.clone(), condition)
+                .await?;
+            self.append(self.record(table_name, "DeleteItem", Some(key), before, None))
+                .await
+        })
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        self.inner.query_page(request)
+    }
+
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        self.inner.naming()
+    }
+
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            let result = self
+                .inner
+                .batch_put_items(table_name, items.clone())
+                .await?;
+            let records = items
+                .into_iter()
+                .filter(|item| {
+                    !result
+                        .unprocessed
+                        .iter()
+                        .any(|request| request.put_request().is_some_and(|put| put.item() == item))
+                })
+                .map(|item| self.record(table_name, "PutItem", None, None, Some(item)))
+                .collect();
+            self.append_all(records).await?;
+            Ok(result)
+        })
+    }
+
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            let result = self
+                .inner
+                .batch_delete_items(table_name, keys.clone())
+                .await?;
+            let records = keys
+                .into_iter()
+                .filter(|key| {
+                    !result.unprocessed.iter().any(|request| {
+                        request
+                            .delete_request()
+                            .is_some_and(|delete| delete.key() == key)
+                    })
+                })
+                .map(|key| self.record(table_name, "DeleteItem", Some(key), None, None))
+                .collect();
+            self.append_all(records).await?;
+            Ok(result)
+        })
+    }
+
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        self.inner.batch_get_items(table_name, keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{
+        schema::{ScalarType, TableSchema},
+        testing::InMemoryStore,
+    };
+
+    const AUDIT: &str = "audit";
+    const ORDERS: &str = "orders";
+
+    fn store(audit_key: &str) -> InMemoryStore {
+        InMemoryStore::new()
+            .with_table(AUDIT, TableSchema::new(audit_key, ScalarType::S))
+            .with_table(ORDERS, TableSchema::new("order_id", ScalarType::S))
+    }
+
+    fn order(id: &str, total: &str) -> Item {
+        HashMap::from([
+            ("order_id".to_string(), AttributeValue::S(id.to_string())),
+            ("total".to_string(), AttributeValue::N(total.to_string())),
+        ])
+    }
+
+    fn key(id: &str) -> Item {
+        HashMap::from([("order_id".to_string(), AttributeValue::S(id.to_string()))])
+    }
+
+    fn text<'a>(record: &'a Item, name: &str) -> &'a str {
+        record[name].as_s().unwrap()
+    }
+
+    /// Renames items on write like `TableBoundStore` does, and remembers the
+    /// conditions it was given.
+    struct Renaming {
+        inner: InMemoryStore,
+        conditions: Mutex<Vec<Option<Filter>>>,
+    }
+
+    impl DynamoStoreApi for Renaming {
+        fn put_item<'a>(
+            &'a self,
+            table_name: &'a str,
+            item: Item,
+            condition: Option<Filter>,
+        ) -> StoreFuture<'a, ()> {
+            self.conditions.lock().unwrap().push(condition.clone());
+            let item = NamingConvention::CamelCase.rename_item(item);
+            self.inner.put_item(table_name, item, condition)
+        }
+
+        fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+            self.inner.get_item(table_name, key)
+        }
+
+        fn delete_item<'a>(
+            &'a self,
+            table_name: &'a str,
+            key: Item,
+            condition: Option<Filter>,
+        ) -> StoreFuture<'a, ()> {
+            self.inner.delete_item(table_name, key, condition)
+        }
+
+        fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+            self.inner.query_page(request)
+        }
+
+        fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+            Box::pin(async { Ok(NamingConvention::CamelCase) })
+        }
+    }
+
+    #[tokio::test]
+    async fn puts_and_deletes_are_recorded() {
+        let memory = store(AUDIT_ID_ATTRIBUTE);
+        let audited = AuditedStore::new(memory.clone(), AUDIT).actor(|| Some("ada".to_string()));
+
+        audited
+            .put_item(ORDERS, order("1", "10"), None)
+            .await
+            .unwrap();
+        audited.delete_item(ORDERS, key("1"), None).await.unwrap();
+
+        let mut records = memory.items(AUDIT);
+        records.sort_by(|a, b| text(a, AUDIT_ID_ATTRIBUTE).cmp(text(b, AUDIT_ID_ATTRIBUTE)));
+        assert_eq!(records.len(), 2);
+        assert_eq!(text(&records[0], OPERATION_ATTRIBUTE), "PutItem");
+        assert_eq!(
+            records[0][AFTER_ATTRIBUTE],
+            AttributeValue::M(order("1", "10"))
+        );
+        assert_eq!(text(&records[1], OPERATION_ATTRIBUTE), "DeleteItem");
+        assert_eq!(records[1][KEY_ATTRIBUTE], AttributeValue::M(key("1")));
+        for record in &records {
+            assert_eq!(text(record, TABLE_ATTRIBUTE), ORDERS);
+            assert_eq!(text(record, ACTOR_ATTRIBUTE), "ada");
+        }
+    }
+
+    #[tokio::test]
+    async fn before_images_record_the_replaced_item() {
+        let memory = store(AUDIT_ID_ATTRIBUTE);
+        memory
+            .put_item(ORDERS, order("1", "10"), None)
+            .await
+            .unwrap();
+        let audited = AuditedStore::new(memory.clone(), AUDIT)
+            .capture_before_images(true)
+            .key_names(ORDERS, ["order_id"]);
+
+        audited
+            .put_item(ORDERS, order("1", "12"), None)
+            .await
+            .unwrap();
+
+        let records = memory.items(AUDIT);
+        assert_eq!(
+            records[0][BEFORE_ATTRIBUTE],
+            AttributeValue::M(order("1", "10"))
+        );
+        assert_eq!(
+            records[0][AFTER_ATTRIBUTE],
+            AttributeValue::M(order("1", "12"))
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_mutations_are_not_recorded() {
+        let memory = store(AUDIT_ID_ATTRIBUTE);
+        memory
+            .put_item(ORDERS, order("1", "10"), None)
+            .await
+            .unwrap();
+        let audited = AuditedStore::new(memory.clone(), AUDIT);
+
+        let result = audited
+            .put_item(
+                ORDERS,
+                order("1", "12"),
+                Some(Filter::not_exists("order_id")),
+            )
+            .await;
+
+        assert!(result.unwrap_err().is_conditional_check_failed());
+        assert!(memory.items(AUDIT).is_empty());
+    }
+
+    #[tokio::test]
+    async fn records_follow_the_naming_of_a_renaming_store() {
+        let renaming = Renaming {
+            inner: store("auditId").with_table(ORDERS, TableSchema::new("orderId", ScalarType::S)),
+            conditions: Mutex::new(Vec::new()),
+        };
+        let audited = AuditedStore::new(renaming, AUDIT);
+
+        audited
+            .put_item(ORDERS, order("1", "10"), None)
+            .await
+            .unwrap();
+
+        let inner = audited.inner();
+        let records = inner.inner.items(AUDIT);
+        assert_eq!(records.len(), 1);
+        assert!(records[0].contains_key("auditId"));
+        assert_eq!(
+            inner.conditions.lock().unwrap().last(),
+            Some(&Some(Filter::not_exists("auditId")))
+        );
+    }
+}
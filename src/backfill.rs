@@ -0,0 +1,278 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::future::try_join_all;
+
+use crate::{
+    expression::CheckedExpressions, instrument::Operation, throttling::CapacityKind, DynamoDbStore,
+    Error,
+};
+
+/// Partition key (`S`) of the table that holds backfill checkpoints.
+pub const CHECKPOINT_KEY_ATTRIBUTE: &str = "backfill_id";
+const TOTAL_SEGMENTS_ATTRIBUTE: &str = "total_segments";
+const START_KEY_ATTRIBUTE: &str = "start_key";
+const DONE_ATTRIBUTE: &str = "done";
+
+type Transform = Arc<
+    dyn Fn(HashMap<String, AttributeValue>) -> Option<HashMap<String, AttributeValue>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+pub struct BackfillOptions {
+    pub segments: i32,
+    transform: Option<Transform>,
+    checkpoint: Option<(String, String)>,
+}
+
+impl Default for BackfillOptions {
+    fn default() -> Self {
+        Self {
+            segments: 4,
+            transform: None,
+            checkpoint: None,
+        }
+    }
+}
+
+impl fmt::Debug for BackfillOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackfillOptions")
+            .field("segments", &self.segments)
+            .field("transform", &self.transform.is_some())
+            .field("checkpoint", &self.checkpoint)
+            .finish()
+    }
+}
+
+impl BackfillOptions {
+    pub fn segments(mut self, segments: i32) -> Self {
+        self.segments = segments.max(1);
+        self
+    }
+
+    /// Rewrites each item, e.g. to compute the new index's key attributes; returning
+    /// `None` leaves the item alone. Without a transform every item is rewritten as is.
+    pub fn transform(
+        mut self,
+        transform: impl Fn(HashMap<String, AttributeValue>) -> Option<HashMap<String, AttributeValue>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+
+    /// Records progress after every page in the item `backfill_id` of `table_name`, so
+    /// a backfill that is interrupted and run again with the same id and segment count
+    /// resumes where it stopped. The table needs [`CHECKPOINT_KEY_ATTRIBUTE`] as
+    /// partition key.
+    pub fn checkpoint(
+        mut self,
+        table_name: impl Into<String>,
+        backfill_id: impl Into<String>,
+    ) -> Self {
+        self.checkpoint = Some((table_name.into(), backfill_id.into()));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackfillSummary {
+    pub scanned: usize,
+    pub written: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Where a segment stopped, as recorded in the checkpoint item.
+enum SegmentCheckpoint {
+    Start,
+    After(HashMap<String, AttributeValue>),
+    Done,
+}
+
+impl DynamoDbStore {
+    /// Rewrites every item of `table_name` with a parallel scan, to populate the
+    /// attributes of a newly added GSI on existing items.
+    ///
+    /// Items are written back with batch puts, so a concurrent update of an item
+    /// between its scan and its write is overwritten; run backfills while writers
+    /// already set the new attributes themselves.
+    pub async fn backfill(
+        &self,
+        table_name: &str,
+        options: BackfillOptions,
+    ) -> Result<BackfillSummary, Error> {
+        self.warn_on_throttling(
+            "Backfill",
+            table_name,
+            options.segments as usize,
+            &[CapacityKind::Read, CapacityKind::Write],
+        )
+        .await;
+
+        let checkpoints = self.load_checkpoints(&options).await?;
+        let segments = checkpoints
+            .into_iter()
+            .enumerate()
+            .map(|(segment, checkpoint)| {
+                self.backfill_segment(table_name, segment as i32, checkpoint, &options)
+            });
+        let summaries = try_join_all(segments).await?;
+
+        Ok(summaries
+            .into_iter()
+            .fold(BackfillSummary::default(), |total, summary| {
+                BackfillSummary {
+                    scanned: total.scanned + summary.scanned,
+                    written: total.written + summary.written,
+                    skipped: total.skipped + summary.skipped,
+                    failed: total.failed + summary.failed,
+                }
+            }))
+    }
+
+    async fn backfill_segment(
+        &self,
+        table_name: &str,
+        segment: i32,
+        checkpoint: SegmentCheckpoint,
+        options: &BackfillOptions,
+    ) -> Result<BackfillSummary, Error> {
+        let mut scan = self.scan(table_name).segment(segment, options.segments);
+        let mut summary = BackfillSummary::default();
+        match checkpoint {
+            SegmentCheckpoint::Start => {}
+            SegmentCheckpoint::After(key) => scan = scan.start_after(key),
+            SegmentCheckpoint::Done => return Ok(summary),
+        }
+
+        loop {
+            let page = scan.page().await?;
+            let scanned = page.items.len();
+            summary.scanned += scanned;
+
+            let items: Vec<_> = match &options.transform {
+                Some(transform) => page
+                    .items
+                    .into_iter()
+                    .filter_map(|item| transform(item))
+                    .collect(),
+                None => page.items,
+            };
+            summary.skipped += scanned - items.len();
+
+            let result = self.batch_put_items(table_name, items).await?;
+            summary.written += result.processed;
+            summary.failed += result.unprocessed.len();
+
+            let checkpoint = match page.last_evaluated_key {
+                Some(key) => {
+                    scan = scan.start_after(key.clone());
+                    SegmentCheckpoint::After(key)
+                }
+                None => SegmentCheckpoint::Done,
+            };
+            let done = matches!(checkpoint, SegmentCheckpoint::Done);
+            self.save_checkpoint(options, segment, checkpoint).await?;
+
+            if done {
+                return Ok(summary);
+            }
+        }
+    }
+
+    async fn load_checkpoints(
+        &self,
+        options: &BackfillOptions,
+    ) -> Result<Vec<SegmentCheckpoint>, Error> {
+        let mut checkpoints: Vec<_> = (0..options.segments)
+            .map(|_| SegmentCheckpoint::Start)
+            .collect();
+        let Some((table_name, backfill_id)) = &options.checkpoint else {
+            return Ok(checkpoints);
+        };
+        let Some(mut item) = self
+            .get_item(table_name, checkpoint_key(backfill_id))
+            .await?
+        else {
+            return Ok(checkpoints);
+        };
+
+        if let Some(AttributeValue::N(total)) = item.get(TOTAL_SEGMENTS_ATTRIBUTE) {
+            if total.parse() != Ok(options.segments) {
+                return Err(Error::Validation(format!(
+                    "backfill {backfill_id} was started with {total} segments, \
+                     resume it with the same number instead of {}",
+                    options.segments
+                )));
+            }
+        }
+
+        for (segment, checkpoint) in checkpoints.iter_mut().enumerate() {
+            let Some(AttributeValue::M(mut recorded)) = item.remove(&segment_attribute(segment))
+            else {
+                continue;
+            };
+            *checkpoint = match recorded.remove(START_KEY_ATTRIBUTE) {
+                Some(AttributeValue::M(key)) => SegmentCheckpoint::After(key),
+                _ if recorded.contains_key(DONE_ATTRIBUTE) => SegmentCheckpoint::Done,
+                _ => SegmentCheckpoint::Start,
+            };
+        }
+
+        Ok(checkpoints)
+    }
+
+    async fn save_checkpoint(
+        &self,
+        options: &BackfillOptions,
+        segment: i32,
+        checkpoint: SegmentCheckpoint,
+    ) -> Result<(), Error> {
+        let Some((table_name, backfill_id)) = &options.checkpoint else {
+            return Ok(());
+        };
+        let recorded = match checkpoint {
+            SegmentCheckpoint::Start => HashMap::new(),
+            SegmentCheckpoint::After(key) => {
+                HashMap::from([(START_KEY_ATTRIBUTE.to_string(), AttributeValue::M(key))])
+            }
+            SegmentCheckpoint::Done => {
+                HashMap::from([(DONE_ATTRIBUTE.to_string(), AttributeValue::Bool(true))])
+            }
+        };
+
+        let operation = Operation::new("UpdateItem", table_name);
+        let request = self
+            .client()
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(checkpoint_key(backfill_id)))
+            .update_expression("SET #segment = :checkpoint, #total = :total")
+            .expression_attribute_names("#segment", segment_attribute(segment as usize))
+            .expression_attribute_names("#total", TOTAL_SEGMENTS_ATTRIBUTE)
+            .expression_attribute_values(":checkpoint", AttributeValue::M(recorded))
+            .expression_attribute_values(":total", AttributeValue::N(options.segments.to_string()))
+            .checked()?
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+}
+
+fn checkpoint_key(backfill_id: &str) -> HashMap<String, AttributeValue> {
+    HashMap::from([(
+        CHECKPOINT_KEY_ATTRIBUTE.to_string(),
+        AttributeValue::S(backfill_id.to_string()),
+    )])
+}
+
+fn segment_attribute(segment: usize) -> String {
+    format!("segment_{segment}")
+}
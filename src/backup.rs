@@ -0,0 +1,151 @@
+use std::time::{Duration, SystemTime};
+
+use aws_sdk_dynamodb::{
+    primitives::DateTime,
+    types::{BackupDetails, BackupSummary as SdkBackupSummary},
+};
+
+use crate::{instrument::Operation, DynamoDbStore, Error};
+
+pub use aws_sdk_dynamodb::types::BackupStatus;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupSummary {
+    pub arn: String,
+    pub name: String,
+    pub table_name: Option<String>,
+    pub status: Option<BackupStatus>,
+    pub created_at: Option<SystemTime>,
+    pub size_bytes: Option<i64>,
+}
+
+impl From<BackupDetails> for BackupSummary {
+    fn from(details: BackupDetails) -> Self {
+        Self {
+            arn: details.backup_arn().to_string(),
+            name: details.backup_name().to_string(),
+            table_name: None,
+            status: Some(details.backup_status().clone()),
+            created_at: system_time(Some(details.backup_creation_date_time())),
+            size_bytes: details.backup_size_bytes(),
+        }
+    }
+}
+
+impl From<SdkBackupSummary> for BackupSummary {
+    fn from(summary: SdkBackupSummary) -> Self {
+        Self {
+            arn: summary.backup_arn().unwrap_or_default().to_string(),
+            name: summary.backup_name().unwrap_or_default().to_string(),
+            table_name: summary.table_name().map(str::to_string),
+            status: summary.backup_status().cloned(),
+            created_at: system_time(summary.backup_creation_date_time()),
+            size_bytes: summary.backup_size_bytes(),
+        }
+    }
+}
+
+impl DynamoDbStore {
+    pub async fn create_backup(
+        &self,
+        table_name: &str,
+        backup_name: &str,
+    ) -> Result<BackupSummary, Error> {
+        let operation = Operation::new("CreateBackup", table_name);
+        let request = self
+            .client()
+            .create_backup()
+            .table_name(table_name)
+            .backup_name(backup_name)
+            .send();
+        let output = operation.send(request).await?;
+
+        let details = output
+            .backup_details
+            .ok_or_else(|| Error::UnexpectedResponse {
+                operation: "CreateBackup",
+                reason: "response contained no backup details".to_string(),
+            })?;
+
+        Ok(BackupSummary {
+            table_name: Some(table_name.to_string()),
+            ..details.into()
+        })
+    }
+
+    /// Lists backups for one table, or for every table when `table_name` is `None`.
+    pub async fn list_backups(
+        &self,
+        table_name: Option<&str>,
+    ) -> Result<Vec<BackupSummary>, Error> {
+        let mut backups = Vec::new();
+        let mut start_arn = None;
+
+        loop {
+            let operation = Operation::new("ListBackups", table_name.unwrap_or_default());
+            let request = self
+                .client()
+                .list_backups()
+                .set_table_name(table_name.map(str::to_string))
+                .set_exclusive_start_backup_arn(start_arn)
+                .send();
+            let output = operation.send(request).await?;
+
+            backups.extend(
+                output
+                    .backup_summaries
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(BackupSummary::from),
+            );
+
+            match output.last_evaluated_backup_arn {
+                Some(arn) => start_arn = Some(arn),
+                None => return Ok(backups),
+            }
+        }
+    }
+
+    pub async fn delete_backup(&self, backup_arn: &str) -> Result<(), Error> {
+        let operation = Operation::new("DeleteBackup", backup_arn);
+        let request = self.client().delete_backup().backup_arn(backup_arn).send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+}
+
+impl DynamoDbStore {
+    pub async fn restore_table_from_backup(
+        &self,
+        backup_arn: &str,
+        target_table_name: &str,
+    ) -> Result<(), Error> {
+        let operation = Operation::new("RestoreTableFromBackup", target_table_name);
+        let request = self
+            .client()
+            .restore_table_from_backup()
+            .backup_arn(backup_arn)
+            .target_table_name(target_table_name)
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+
+    /// Restores the backup into a new table and waits until it is `ACTIVE`.
+    pub async fn restore_table_from_backup_and_wait(
+        &self,
+        backup_arn: &str,
+        target_table_name: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        self.restore_table_from_backup(backup_arn, target_table_name)
+            .await?;
+        self.wait_until_active(target_table_name, timeout).await
+    }
+}
+
+fn system_time(date_time: Option<&DateTime>) -> Option<SystemTime> {
+    date_time.and_then(|date_time| SystemTime::try_from(*date_time).ok())
+}
@@ -0,0 +1,443 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use aws_sdk_dynamodb::{
+    error::BuildError,
+    types::{AttributeValue, DeleteRequest, KeysAndAttributes, PutRequest, WriteRequest},
+};
+
+use crate::{
+    convert::write_canonical_attributes, instrument::Operation, limits::validate_item, metrics,
+    retry::RetryConfig, DynamoDbStore, Error,
+};
+
+pub(crate) const MAX_BATCH_WRITE_ITEMS: usize = 25;
+pub(crate) const MAX_BATCH_GET_ITEMS: usize = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct BatchWriteResult {
+    pub processed: usize,
+    /// Requests DynamoDB still reported as unprocessed after all retries.
+    pub unprocessed: Vec<WriteRequest>,
+    /// Requests dropped because a later request in the same call wrote the same key.
+    pub duplicates: usize,
+    pub stats: BatchStats,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BatchGetResult {
+    pub items: Vec<HashMap<String, AttributeValue>>,
+    /// Keys DynamoDB still reported as unprocessed after all retries.
+    pub unprocessed: Vec<HashMap<String, AttributeValue>>,
+    /// Keys dropped because they were requested more than once.
+    pub duplicates: usize,
+    /// Keys DynamoDB answered that have no item, unlike `unprocessed` keys, which were
+    /// never answered.
+    pub not_found: Vec<HashMap<String, AttributeValue>>,
+    pub stats: BatchStats,
+}
+
+/// How a batch call went, e.g. to log a job summary or alert when throttling grows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchStats {
+    /// Batch requests of up to 25 writes or 100 gets the call was split into.
+    pub chunks: usize,
+    /// Requests sent again for the unprocessed part of a chunk.
+    pub retries: usize,
+    /// Responses that left part of a chunk unprocessed, which DynamoDB does when the
+    /// table or a partition is throttled.
+    pub throttled: usize,
+    pub elapsed: Duration,
+}
+
+impl BatchStats {
+    /// Adds the counts and time of `other`, e.g. to total the batches of a job.
+    pub fn add(&mut self, other: &BatchStats) {
+        self.chunks += other.chunks;
+        self.retries += other.retries;
+        self.throttled += other.throttled;
+        self.elapsed += other.elapsed;
+    }
+}
+
+impl DynamoDbStore {
+    pub async fn batch_put_items(
+        &self,
+        table_name: &str,
+        items: Vec<HashMap<String, AttributeValue>>,
+    ) -> Result<BatchWriteResult, Error> {
+        self.batch_put_items_with_retry(table_name, items, &RetryConfig::default())
+            .await
+    }
+
+    pub(crate) async fn batch_put_items_with_retry(
+        &self,
+        table_name: &str,
+        items: Vec<HashMap<String, AttributeValue>>,
+        retry: &RetryConfig,
+    ) -> Result<BatchWriteResult, Error> {
+        let requests = items
+            .into_iter()
+            .map(|item| {
+                validate_item(&item)?;
+                let put = PutRequest::builder().set_item(Some(item)).build()?;
+                Ok(WriteRequest::builder().put_request(put).build())
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        self.write_batches(table_name, requests, retry).await
+    }
+
+    pub async fn batch_delete_items(
+        &self,
+        table_name: &str,
+        keys: Vec<HashMap<String, AttributeValue>>,
+    ) -> Result<BatchWriteResult, Error> {
+        self.batch_delete_items_with_retry(table_name, keys, &RetryConfig::default())
+            .await
+    }
+
+    pub(crate) async fn batch_delete_items_with_retry(
+        &self,
+        table_name: &str,
+        keys: Vec<HashMap<String, AttributeValue>>,
+        retry: &RetryConfig,
+    ) -> Result<BatchWriteResult, Error> {
+        let requests = keys
+            .into_iter()
+            .map(|key| {
+                let delete = DeleteRequest::builder().set_key(Some(key)).build()?;
+                Ok(WriteRequest::builder().delete_request(delete).build())
+            })
+            .collect::<Result<Vec<_>, BuildError>>()?;
+
+        self.write_batches(table_name, requests, retry).await
+    }
+
+    /// Writes `requests` in chunks of 25. BatchWriteItem rejects a request that touches
+    /// the same key twice, so only the last request for each key is sent.
+    pub(crate) async fn write_batches(
+        &self,
+        table_name: &str,
+        requests: Vec<WriteRequest>,
+        retry: &RetryConfig,
+    ) -> Result<BatchWriteResult, Error> {
+        let started = Instant::now();
+        let has_puts = requests.iter().any(|request| request.put_request.is_some());
+        // Put requests carry whole items, so their keys can only be told apart by name.
+        // Without DescribeTable permission only identical requests count as duplicates.
+        let key_names = if has_puts && requests.len() > 1 {
+            match self.table_key_names(table_name).await {
+                Ok(key_names) => key_names,
+                Err(err) if err.is_access_denied() => Vec::new(),
+                Err(err) => return Err(err),
+            }
+        } else {
+            Vec::new()
+        };
+        let (requests, duplicates) = dedupe_last_wins(requests, |request| {
+            match (&request.put_request, &request.delete_request) {
+                (Some(put), _) => key_identity(&put.item, &key_names),
+                (_, Some(delete)) => key_identity(&delete.key, &key_names),
+                _ => String::new(),
+            }
+        });
+        let mut result = BatchWriteResult {
+            duplicates,
+            ..BatchWriteResult::default()
+        };
+
+        // Chunks take the requests by value from the front; draining the vector instead
+        // would shift the remaining requests once per chunk.
+        let mut requests = requests.into_iter();
+        loop {
+            let chunk: Vec<WriteRequest> = requests.by_ref().take(MAX_BATCH_WRITE_ITEMS).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            let submitted = chunk.len();
+
+            let unprocessed = self
+                .write_chunk(table_name, chunk, retry, &mut result.stats)
+                .await?;
+            result.processed += submitted - unprocessed.len();
+            result.unprocessed.extend(unprocessed);
+        }
+        result.stats.elapsed = started.elapsed();
+
+        if !result.unprocessed.is_empty() {
+            metrics::record_batch_failures("BatchWriteItem", table_name, result.unprocessed.len());
+        }
+
+        Ok(result)
+    }
+
+    async fn write_chunk(
+        &self,
+        table_name: &str,
+        mut pending: Vec<WriteRequest>,
+        retry: &RetryConfig,
+        stats: &mut BatchStats,
+    ) -> Result<Vec<WriteRequest>, Error> {
+        stats.chunks += 1;
+        for attempt in 0..retry.attempts() {
+            if attempt > 0 {
+                stats.retries += 1;
+                tokio::time::sleep(retry.backoff(attempt - 1)).await;
+            }
+
+            let operation = Operation::new("BatchWriteItem", table_name);
+            let request = self
+                .client()
+                .batch_write_item()
+                .request_items(table_name, pending)
+                .send();
+            let output = operation.send(request).await?;
+
+            pending = output
+                .unprocessed_items
+                .and_then(|mut unprocessed| unprocessed.remove(table_name))
+                .unwrap_or_default();
+            if pending.is_empty() {
+                break;
+            }
+            stats.throttled += 1;
+        }
+
+        Ok(pending)
+    }
+
+    /// Reads the items at `keys` in chunks of 100, retrying unprocessed keys with backoff.
+    ///
+    /// Items are returned in no particular order; keys without an item are listed in
+    /// [`not_found`](BatchGetResult::not_found).
+    /// Duplicate keys are requested once and counted in
+    /// [`duplicates`](BatchGetResult::duplicates).
+    pub async fn batch_get_items(
+        &self,
+        table_name: &str,
+        keys: Vec<HashMap<String, AttributeValue>>,
+    ) -> Result<BatchGetResult, Error> {
+        self.batch_get_items_with_retry(table_name, keys, &RetryConfig::default())
+            .await
+    }
+
+    pub(crate) async fn batch_get_items_with_retry(
+        &self,
+        table_name: &str,
+        keys: Vec<HashMap<String, AttributeValue>>,
+        retry: &RetryConfig,
+    ) -> Result<BatchGetResult, Error> {
+        let started = Instant::now();
+        let (keys, duplicates) = dedupe_last_wins(keys, |key| key_identity(key, &[]));
+        let mut result = BatchGetResult {
+            duplicates,
+            ..BatchGetResult::default()
+        };
+
+        let requested = keys.clone();
+
+        let mut keys = keys.into_iter();
+        loop {
+            let chunk: Vec<_> = keys.by_ref().take(MAX_BATCH_GET_ITEMS).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            let unprocessed = self
+                .get_chunk(
+                    table_name,
+                    chunk,
+                    retry,
+                    &mut result.items,
+                    &mut result.stats,
+                )
+                .await?;
+            result.unprocessed.extend(unprocessed);
+        }
+
+        result.not_found = not_found(requested, &result.items, &result.unprocessed);
+        result.stats.elapsed = started.elapsed();
+        Ok(result)
+    }
+
+    async fn get_chunk(
+        &self,
+        table_name: &str,
+        mut pending: Vec<HashMap<String, AttributeValue>>,
+        retry: &RetryConfig,
+        items: &mut Vec<HashMap<String, AttributeValue>>,
+        stats: &mut BatchStats,
+    ) -> Result<Vec<HashMap<String, AttributeValue>>, Error> {
+        stats.chunks += 1;
+        for attempt in 0..retry.attempts() {
+            if attempt > 0 {
+                stats.retries += 1;
+                tokio::time::sleep(retry.backoff(attempt - 1)).await;
+            }
+
+            let keys = KeysAndAttributes::builder()
+                .set_keys(Some(pending))
+                .build()?;
+            let operation = Operation::new("BatchGetItem", table_name);
+            let request = self
+                .read_client()
+                .batch_get_item()
+                .request_items(table_name, keys)
+                .send();
+            let mut output = operation.send(request).await?;
+
+            items.extend(
+                output
+                    .responses
+                    .as_mut()
+                    .and_then(|responses| responses.remove(table_name))
+                    .unwrap_or_default(),
+            );
+            pending = output
+                .unprocessed_keys
+                .and_then(|mut unprocessed| unprocessed.remove(table_name))
+                .map(|keys| keys.keys)
+                .unwrap_or_default();
+            if pending.is_empty() {
+                break;
+            }
+            stats.throttled += 1;
+        }
+
+        Ok(pending)
+    }
+}
+
+/// Keeps the last of the values with the same identity, in their original order, and
+/// returns how many were dropped.
+fn dedupe_last_wins<T>(values: Vec<T>, identity: impl Fn(&T) -> String) -> (Vec<T>, usize) {
+    let identities: Vec<String> = values.iter().map(&identity).collect();
+    let mut seen = HashSet::new();
+    let mut keep = vec![false; values.len()];
+    for (index, identity) in identities.iter().enumerate().rev() {
+        keep[index] = seen.insert(identity);
+    }
+
+    let total = values.len();
+    let deduped: Vec<T> = values
+        .into_iter()
+        .zip(keep)
+        .filter_map(|(value, keep)| keep.then_some(value))
+        .collect();
+    let duplicates = total - deduped.len();
+    (deduped, duplicates)
+}
+
+/// The `requested` keys that match neither an item nor an unprocessed key. Every key
+/// of one table has the same attribute names, so the first key names them.
+fn not_found(
+    requested: Vec<HashMap<String, AttributeValue>>,
+    items: &[HashMap<String, AttributeValue>],
+    unprocessed: &[HashMap<String, AttributeValue>],
+) -> Vec<HashMap<String, AttributeValue>> {
+    let key_names: Vec<String> = requested
+        .first()
+        .map(|key| key.keys().cloned().collect())
+        .unwrap_or_default();
+    let answered: HashSet<String> = items
+        .iter()
+        .chain(unprocessed)
+        .map(|item| key_identity(item, &key_names))
+        .collect();
+
+    requested
+        .into_iter()
+        .filter(|key| !answered.contains(&key_identity(key, &key_names)))
+        .collect()
+}
+
+/// Formats the key attributes of `item` so that keys DynamoDB considers equal, such as
+/// `N("1")` and `N("1.0")`, give equal strings. Without `key_names`, or when a key
+/// attribute is missing, every attribute counts.
+pub(crate) fn key_identity(item: &HashMap<String, AttributeValue>, key_names: &[String]) -> String {
+    let mut identity = String::new();
+    if !key_names.is_empty() && key_names.iter().all(|name| item.contains_key(name)) {
+        write_canonical_attributes(
+            item.iter().filter(|(name, _)| key_names.contains(name)),
+            &mut identity,
+        );
+    } else {
+        write_canonical_attributes(item, &mut identity);
+    }
+    identity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(pairs: &[(&str, &str)]) -> HashMap<String, AttributeValue> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), AttributeValue::S(value.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn the_last_duplicate_wins_in_original_order() {
+        let values = vec![("a", 1), ("b", 2), ("a", 3), ("c", 4), ("b", 5)];
+
+        let (deduped, duplicates) = dedupe_last_wins(values, |(id, _)| id.to_string());
+
+        assert_eq!(deduped, [("a", 3), ("c", 4), ("b", 5)]);
+        assert_eq!(duplicates, 2);
+    }
+
+    #[test]
+    fn distinct_values_are_kept() {
+        let (deduped, duplicates) = dedupe_last_wins(vec![1, 2, 3], ToString::to_string);
+
+        assert_eq!(deduped, [1, 2, 3]);
+        assert_eq!(duplicates, 0);
+    }
+
+    #[test]
+    fn items_with_the_same_key_share_an_identity() {
+        let key_names = ["pk".to_string(), "sk".to_string()];
+        let first = item(&[("pk", "1"), ("sk", "a"), ("name", "old")]);
+        let second = item(&[("sk", "a"), ("pk", "1"), ("name", "new")]);
+
+        assert_eq!(
+            key_identity(&first, &key_names),
+            key_identity(&second, &key_names)
+        );
+        // Without key names the whole item is compared.
+        assert_ne!(key_identity(&first, &[]), key_identity(&second, &[]));
+    }
+
+    #[test]
+    fn numerically_equal_keys_share_an_identity() {
+        let key = |number: &str| {
+            HashMap::from([
+                ("pk".to_string(), AttributeValue::S("1".to_string())),
+                ("sk".to_string(), AttributeValue::N(number.to_string())),
+            ])
+        };
+
+        assert_eq!(key_identity(&key("1"), &[]), key_identity(&key("1.0"), &[]));
+        assert_eq!(
+            key_identity(&key("10"), &[]),
+            key_identity(&key("1e1"), &[])
+        );
+        assert_ne!(key_identity(&key("1"), &[]), key_identity(&key("10"), &[]));
+    }
+
+    #[test]
+    fn items_missing_a_key_attribute_are_compared_whole() {
+        let key_names = ["pk".to_string(), "sk".to_string()];
+        let first = item(&[("pk", "1"), ("name", "old")]);
+        let second = item(&[("pk", "1"), ("name", "new")]);
+
+        assert_ne!(
+            key_identity(&first, &key_names),
+            key_identity(&second, &key_names)
+        );
+    }
+}
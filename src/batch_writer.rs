@@ -0,0 +1,285 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use aws_sdk_dynamodb::types::{DeleteRequest, PutRequest, WriteRequest};
+use serde::Serialize;
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{
+    batch::{BatchWriteResult, MAX_BATCH_WRITE_ITEMS},
+    limits::validate_item,
+    table::TableBoundStore,
+    Error,
+};
+
+#[derive(Default)]
+struct Buffer {
+    pending: Vec<WriteRequest>,
+    /// When the oldest pending request was buffered.
+    since: Option<Instant>,
+    totals: BatchWriteResult,
+}
+
+impl Buffer {
+    fn take_pending(&mut self) -> Vec<WriteRequest> {
+        self.since = None;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Buffers puts and deletes for one table and sends them as BatchWriteItem requests.
+///
+/// A batch is sent once 25 requests are buffered, or on the next call after the oldest
+/// buffered request waited [`max_latency`](Self::max_latency). Within one batch, the
+/// last request for a key wins. Call [`close`](Self::close) when done; requests still
+/// buffered when the writer is dropped are lost, as are the requests of a batch whose
+/// call failed.
+pub struct BatchWriter {
+    table: TableBoundStore,
+    max_latency: Duration,
+    buffer: Arc<Mutex<Buffer>>,
+}
+
+impl fmt::Debug for BatchWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchWriter")
+            .field("table", &self.table)
+            .field("max_latency", &self.max_latency)
+            .field("pending", &lock(&self.buffer).pending.len())
+            .finish()
+    }
+}
+
+impl TableBoundStore {
+    pub fn batch_writer(&self) -> BatchWriter {
+        BatchWriter {
+            table: self.clone(),
+            max_latency: Duration::from_secs(1),
+            buffer: Arc::default(),
+        }
+    }
+}
+
+impl BatchWriter {
+    /// How long a request may wait in the buffer for a full batch; defaults to 1 s.
+    pub fn max_latency(mut self, max_latency: Duration) -> Self {
+        self.max_latency = max_latency;
+        self
+    }
+
+    pub async fn put<T: Serialize>(&self, item: &T) -> Result<(), Error> {
+        let item = self.table.to_item(item)?;
+        validate_item(&item)?;
+        let put = PutRequest::builder().set_item(Some(item)).build()?;
+
+        self.push(WriteRequest::builder().put_request(put).build())
+            .await
+    }
+
+    pub async fn delete<K: Serialize>(&self, key: &K) -> Result<(), Error> {
+        let key = self.table.to_key(key)?;
+        let delete = DeleteRequest::builder().set_key(Some(key)).build()?;
+
+        self.push(WriteRequest::builder().delete_request(delete).build())
+            .await
+    }
+
+    /// Moves the writer into a background task fed through a channel of `capacity`
+    /// requests, for ingestion loops that should not wait for batch calls. The task also
+    /// flushes once the oldest request waited `max_latency` while no new ones arrive.
+    pub fn spawn(self, capacity: usize) -> BatchWriterHandle {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let table = self.table.clone();
+
+        BatchWriterHandle {
+            sender: BatchWriterSender { table, sender },
+            task: tokio::spawn(self.run(receiver)),
+        }
+    }
+
+    /// Sends every buffered request now.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let pending = lock(&self.buffer).take_pending();
+        self.write(pending).await
+    }
+
+    /// Flushes and returns the totals of every batch this writer sent, including the
+    /// requests that stayed unprocessed after all retries.
+    pub async fn close(self) -> Result<BatchWriteResult, Error> {
+        self.flush().await?;
+        Ok(std::mem::take(&mut lock(&self.buffer).totals))
+    }
+
+    async fn push(&self, request: WriteRequest) -> Result<(), Error> {
+        let due = {
+            let mut buffer = lock(&self.buffer);
+            buffer.pending.push(request);
+            let since = *buffer.since.get_or_insert_with(Instant::now);
+
+            if buffer.pending.len() >= MAX_BATCH_WRITE_ITEMS || since.elapsed() >= self.max_latency
+            {
+                buffer.take_pending()
+            } else {
+                Vec::new()
+            }
+        };
+
+        self.write(due).await
+    }
+
+    /// Feeds requests from `receiver` into the buffer until shutdown or until every
+    /// sender is gone, then closes the writer.
+    async fn run(self, mut receiver: mpsc::Receiver<Message>) -> Result<BatchWriteResult, Error> {
+        loop {
+            let since = lock(&self.buffer).since;
+            let message = match since {
+                Some(since) => {
+                    let wait = self.max_latency.saturating_sub(since.elapsed());
+                    match tokio::time::timeout(wait, receiver.recv()).await {
+                        Ok(message) => message,
+                        Err(_) => {
+                            self.flush().await?;
+                            continue;
+                        }
+                    }
+                }
+                None => receiver.recv().await,
+            };
+
+            match message {
+                Some(Message::Write(request)) => self.push(request).await?,
+                Some(Message::Shutdown) | None => break,
+            }
+        }
+
+        receiver.close();
+        while let Some(message) = receiver.recv().await {
+            if let Message::Write(request) = message {
+                self.push(request).await?;
+            }
+        }
+        self.close().await
+    }
+
+    async fn write(&self, requests: Vec<WriteRequest>) -> Result<(), Error> {
+        if requests.is_empty() {
+            return Ok(());
+        }
+
+        let result = self
+            .table
+            .store()
+            .write_batches(self.table.table_name(), requests, &self.table.batch_retry())
+            .await?;
+
+        let mut buffer = lock(&self.buffer);
+        buffer.totals.processed += result.processed;
+        buffer.totals.duplicates += result.duplicates;
+        buffer.totals.unprocessed.extend(result.unprocessed);
+        buffer.totals.stats.add(&result.stats);
+        Ok(())
+    }
+}
+
+enum Message {
+    Write(WriteRequest),
+    Shutdown,
+}
+
+/// Queues requests for the background task of a [`BatchWriter::spawn`]; clone it for
+/// each producer.
+#[derive(Debug, Clone)]
+pub struct BatchWriterSender {
+    table: TableBoundStore,
+    sender: mpsc::Sender<Message>,
+}
+
+impl BatchWriterSender {
+    /// Queues a put, waiting while the channel is full so that producers slow down to
+    /// the pace of the batch calls.
+    pub async fn put<T: Serialize>(&self, item: &T) -> Result<(), Error> {
+        let item = self.table.to_item(item)?;
+        validate_item(&item)?;
+        let put = PutRequest::builder().set_item(Some(item)).build()?;
+
+        self.send(WriteRequest::builder().put_request(put).build())
+            .await
+    }
+
+    pub async fn delete<K: Serialize>(&self, key: &K) -> Result<(), Error> {
+        let key = self.table.to_key(key)?;
+        let delete = DeleteRequest::builder().set_key(Some(key)).build()?;
+
+        self.send(WriteRequest::builder().delete_request(delete).build())
+            .await
+    }
+
+    /// How many more requests fit into the channel before `put` and `delete` wait.
+    pub fn capacity(&self) -> usize {
+        self.sender.capacity()
+    }
+
+    async fn send(&self, request: WriteRequest) -> Result<(), Error> {
+        self.sender
+            .send(Message::Write(request))
+            .await
+            .map_err(|_| stopped())
+    }
+}
+
+/// The background task of a [`BatchWriter::spawn`].
+///
+/// The task stops at [`shutdown`](Self::shutdown), or once the handle and every
+/// [`BatchWriterSender`] are dropped, and always flushes what it received before. If a
+/// batch call fails, the task stops early and `shutdown` returns the error.
+#[derive(Debug)]
+pub struct BatchWriterHandle {
+    sender: BatchWriterSender,
+    task: JoinHandle<Result<BatchWriteResult, Error>>,
+}
+
+impl BatchWriterHandle {
+    pub fn sender(&self) -> BatchWriterSender {
+        self.sender.clone()
+    }
+
+    pub async fn put<T: Serialize>(&self, item: &T) -> Result<(), Error> {
+        self.sender.put(item).await
+    }
+
+    pub async fn delete<K: Serialize>(&self, key: &K) -> Result<(), Error> {
+        self.sender.delete(key).await
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.sender.capacity()
+    }
+
+    /// Stops accepting requests, flushes everything queued so far and returns the totals
+    /// of the task, like [`BatchWriter::close`]. Senders fail from then on.
+    pub async fn shutdown(self) -> Result<BatchWriteResult, Error> {
+        // A failed send means the task has already stopped; its result tells why.
+        let _ = self.sender.sender.send(Message::Shutdown).await;
+
+        self.task.await.map_err(|err| Error::UnexpectedResponse {
+            operation: "BatchWriteItem",
+            reason: format!("batch writer task failed: {err}"),
+        })?
+    }
+}
+
+fn stopped() -> Error {
+    Error::UnexpectedResponse {
+        operation: "BatchWriteItem",
+        reason: "the batch writer task has stopped".to_string(),
+    }
+}
+
+fn lock(buffer: &Mutex<Buffer>) -> MutexGuard<'_, Buffer> {
+    buffer
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
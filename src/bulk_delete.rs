@@ -0,0 +1,78 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{filter::Filter, DynamoDbStore, Error};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeleteSummary {
+    pub matched: usize,
+    pub deleted: usize,
+    /// Deletes that were still unprocessed after all batch retries.
+    pub failed: usize,
+}
+
+impl DynamoDbStore {
+    /// Deletes every item stored under `partition_value`, page by page.
+    pub async fn delete_partition(
+        &self,
+        table_name: &str,
+        partition_value: AttributeValue,
+    ) -> Result<DeleteSummary, Error> {
+        let description = self.describe_table(table_name).await?;
+        let key_attributes: Vec<String> = std::iter::once(&description.partition_key)
+            .chain(description.sort_key.as_ref())
+            .map(|attribute| attribute.name.clone())
+            .collect();
+
+        let mut query = self
+            .query(table_name)
+            .partition_key(&description.partition_key.name, partition_value)
+            .projection(key_attributes);
+        let mut summary = DeleteSummary::default();
+
+        loop {
+            let page = query.page().await?;
+            summary.matched += page.items.len();
+
+            let result = self.batch_delete_items(table_name, page.items).await?;
+            summary.deleted += result.processed;
+            summary.failed += result.unprocessed.len();
+
+            match page.last_evaluated_key {
+                Some(key) => query = query.start_after(key),
+                None => return Ok(summary),
+            }
+        }
+    }
+
+    /// Scans for items matching `filter` and deletes them, returning how many were removed.
+    pub async fn delete_where(
+        &self,
+        table_name: &str,
+        filter: Filter,
+    ) -> Result<DeleteSummary, Error> {
+        let description = self.describe_table(table_name).await?;
+        let key_attributes = std::iter::once(&description.partition_key)
+            .chain(description.sort_key.as_ref())
+            .map(|attribute| attribute.name.clone());
+
+        let mut scan = self
+            .scan(table_name)
+            .projection(key_attributes)
+            .filter(filter);
+        let mut summary = DeleteSummary::default();
+
+        loop {
+            let page = scan.page().await?;
+            summary.matched += page.items.len();
+
+            let result = self.batch_delete_items(table_name, page.items).await?;
+            summary.deleted += result.processed;
+            summary.failed += result.unprocessed.len();
+
+            match page.last_evaluated_key {
+                Some(key) => scan = scan.start_after(key),
+                None => return Ok(summary),
+            }
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use moka::{sync::Cache, Expiry};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{table::TableBoundStore, versioned::Versioned, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    pub time_to_live: Duration,
+    pub max_capacity: u64,
+    /// Also caches missing items for this long, so repeated lookups of keys that do not
+    /// exist skip DynamoDB. Keep it short: items created elsewhere stay invisible to
+    /// this cache until it expires.
+    pub negative_time_to_live: Option<Duration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            time_to_live: Duration::from_secs(60),
+            max_capacity: 10_000,
+            negative_time_to_live: None,
+        }
+    }
+}
+
+/// A [`TableBoundStore`] with a read-through, in-process item cache.
+///
+/// Writes made through this wrapper update or invalidate the cache; writes made
+/// elsewhere only become visible once the cached entry expires.
+#[derive(Debug, Clone)]
+pub struct CachedTable {
+    table: TableBoundStore,
+    cache: Cache<String, CachedItem>,
+    negative_caching: bool,
+}
+
+/// A cached lookup result; `None` records that the item did not exist.
+type CachedItem = Option<Arc<HashMap<String, AttributeValue>>>;
+
+struct CacheExpiry {
+    time_to_live: Duration,
+    negative_time_to_live: Duration,
+}
+
+impl CacheExpiry {
+    fn for_entry(&self, item: &CachedItem) -> Option<Duration> {
+        match item {
+            Some(_) => Some(self.time_to_live),
+            None => Some(self.negative_time_to_live),
+        }
+    }
+}
+
+impl Expiry<String, CachedItem> for CacheExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        item: &CachedItem,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        self.for_entry(item)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        item: &CachedItem,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        self.for_entry(item)
+    }
+}
+
+impl TableBoundStore {
+    pub fn cached(self, config: CacheConfig) -> CachedTable {
+        CachedTable {
+            table: self,
+            cache: Cache::builder()
+                .expire_after(CacheExpiry {
+                    time_to_live: config.time_to_live,
+                    negative_time_to_live: config.negative_time_to_live.unwrap_or_default(),
+                })
+                .max_capacity(config.max_capacity)
+                .build(),
+            negative_caching: config.negative_time_to_live.is_some(),
+        }
+    }
+}
+
+impl CachedTable {
+    pub fn table(&self) -> &TableBoundStore {
+        &self.table
+    }
+
+    pub async fn get<K: Serialize, T: DeserializeOwned>(
+        &self,
+        key: &K,
+    ) -> Result<Option<T>, Error> {
+        let key = self.table.to_key(key)?;
+        let cache_key = cache_key(&key);
+
+        match self.cache.get(&cache_key) {
+            Some(Some(item)) => return Ok(Some(self.table.read_item(item.as_ref().clone())?)),
+            Some(None) => return Ok(None),
+            None => {}
+        }
+
+        let item = self
+            .table
+            .run(|| async {
+                let output = self
+                    .table
+                    .store()
+                    .get_item_with_options(
+                        self.table.table_name(),
+                        key.clone(),
+                        self.table.defaults(),
+                    )
+                    .await?;
+                Ok(output.item)
+            })
+            .await?;
+        match item {
+            Some(item) => {
+                let deserialized = self.table.read_item(item.clone())?;
+                self.cache.insert(cache_key, Some(Arc::new(item)));
+                Ok(Some(deserialized))
+            }
+            None => {
+                if self.negative_caching {
+                    self.cache.insert(cache_key, None);
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Writes `item` and stores it in the cache.
+    pub async fn put<T: Serialize>(&self, item: &T) -> Result<(), Error> {
+        let item = self.table.to_item(item)?;
+        self.table
+            .run(|| {
+                self.table.store().put_item_with_options(
+                    self.table.table_name(),
+                    item.clone(),
+                    self.table.defaults(),
+                )
+            })
+            .await?;
+
+        match self.key_of(&item).await {
+            Ok(key) => self.cache.insert(cache_key(&key), Some(Arc::new(item))),
+            Err(_) => self.cache.invalidate_all(),
+        }
+        Ok(())
+    }
+
+    pub async fn merge_put<T: Serialize>(&self, item: &T) -> Result<(), Error> {
+        let result = self.table.merge_put(item).await;
+        self.invalidate_item(item).await;
+        result
+    }
+
+    pub async fn put_versioned<T: Serialize + Versioned>(&self, item: &mut T) -> Result<(), Error> {
+        let result = self.table.put_versioned(item).await;
+        self.invalidate_item(item).await;
+        result
+    }
+
+    pub async fn update_versioned<K: Serialize, U: Serialize>(
+        &self,
+        key: &K,
+        expected_version: u64,
+        changes: &U,
+    ) -> Result<u64, Error> {
+        let result = self
+            .table
+            .update_versioned(key, expected_version, changes)
+            .await;
+        self.invalidate_key(key);
+        result
+    }
+
+    pub async fn delete<K: Serialize>(&self, key: &K) -> Result<(), Error> {
+        let result = self.table.delete(key).await;
+        self.invalidate_key(key);
+        result
+    }
+
+    /// Drops the cached item at `key`, e.g. after updating it through another API.
+    pub fn invalidate<K: Serialize>(&self, key: &K) -> Result<(), Error> {
+        let key = self.table.to_key(key)?;
+        self.cache.invalidate(&cache_key(&key));
+        Ok(())
+    }
+
+    pub fn invalidate_all(&self) {
+        self.cache.invalidate_all();
+    }
+
+    // The helpers below run after a write that may have succeeded, so they never fail
+    // it: when the cached entry cannot be found, the whole cache is dropped instead.
+
+    fn invalidate_key<K: Serialize>(&self, key: &K) {
+        if self.invalidate(key).is_err() {
+            self.cache.invalidate_all();
+        }
+    }
+
+    async fn invalidate_item<T: Serialize>(&self, item: &T) {
+        let key = match self.table.to_key(item) {
+            Ok(item) => self.key_of(&item).await,
+            Err(err) => Err(err),
+        };
+        match key {
+            Ok(key) => self.cache.invalidate(&cache_key(&key)),
+            Err(_) => self.cache.invalidate_all(),
+        }
+    }
+
+    async fn key_of(
+        &self,
+        item: &HashMap<String, AttributeValue>,
+    ) -> Result<HashMap<String, AttributeValue>, Error> {
+        self.table
+            .key_names()
+            .await?
+            .iter()
+            .map(|name| {
+                let value = item.get(name).ok_or_else(|| {
+                    Error::Validation(format!("item is missing key attribute {name}"))
+                })?;
+                Ok((name.clone(), value.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Key attributes in name order, formatted so that equal keys give equal strings.
+fn cache_key(key: &HashMap<String, AttributeValue>) -> String {
+    let mut attributes: Vec<_> = key.iter().collect();
+    attributes.sort_by_key(|(name, _)| *name);
+    format!("{attributes:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::offline_store;
+
+    type Record = HashMap<String, String>;
+
+    fn cached(negative_time_to_live: Option<Duration>) -> CachedTable {
+        offline_store().for_table("orders").cached(CacheConfig {
+            negative_time_to_live,
+            ..CacheConfig::default()
+        })
+    }
+
+    fn key(id: &str) -> Record {
+        HashMap::from([("order_id".to_string(), id.to_string())])
+    }
+
+    fn cache(table: &CachedTable, id: &str, item: Option<&[(&str, &str)]>) {
+        let item = item.map(|item| {
+            Arc::new(
+                item.iter()
+                    .map(|(name, value)| (name.to_string(), AttributeValue::S(value.to_string())))
+                    .collect(),
+            )
+        });
+        let key = table.table.to_key(&key(id)).unwrap();
+        table.cache.insert(cache_key(&key), item);
+    }
+
+    #[tokio::test]
+    async fn cached_items_are_served_without_a_call() {
+        let table = cached(None);
+        cache(&table, "1", Some(&[("order_id", "1"), ("status", "paid")]));
+
+        let item: Record = table.get(&key("1")).await.unwrap().unwrap();
+
+        assert_eq!(item["status"], "paid");
+    }
+
+    #[tokio::test]
+    async fn cached_misses_are_served_without_a_call() {
+        let table = cached(Some(Duration::from_secs(1)));
+        cache(&table, "1", None);
+
+        assert_eq!(table.get::<_, Record>(&key("1")).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn uncached_and_invalidated_keys_are_read_from_dynamodb() {
+        let table = cached(None);
+        cache(&table, "1", Some(&[("order_id", "1")]));
+        table.invalidate(&key("1")).unwrap();
+
+        // The offline store fails every call, so a read that got through reached it.
+        assert!(table.get::<_, Record>(&key("1")).await.is_err());
+        assert!(table.get::<_, Record>(&key("2")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn entries_that_cannot_be_located_clear_the_cache() {
+        let table = cached(None);
+        cache(&table, "1", Some(&[("order_id", "1")]));
+        cache(&table, "2", Some(&[("order_id", "2")]));
+
+        // Looking up the key names fails offline, so the entry of `1` cannot be found.
+        table.invalidate_item(&key("1")).await;
+        table.cache.run_pending_tasks();
+
+        assert_eq!(table.cache.entry_count(), 0);
+        assert!(table.get::<_, Record>(&key("2")).await.is_err());
+    }
+}
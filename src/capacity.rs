@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::limits::item_size;
+
+const READ_UNIT_SIZE: usize = 4 * 1024;
+const WRITE_UNIT_SIZE: usize = 1024;
+
+/// How an item is read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReadConsistency {
+    /// Half a read unit per 4 KB.
+    #[default]
+    Eventual,
+    /// One read unit per 4 KB.
+    Strong,
+    /// Two read units per 4 KB, as in TransactGetItems.
+    Transactional,
+}
+
+/// How an item is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WriteMode {
+    /// One write unit per 1 KB.
+    #[default]
+    Standard,
+    /// Two write units per 1 KB, as in TransactWriteItems.
+    Transactional,
+}
+
+impl ReadConsistency {
+    fn units(self, size: usize) -> f64 {
+        let units = size.max(1).div_ceil(READ_UNIT_SIZE) as f64;
+        match self {
+            ReadConsistency::Eventual => units / 2.0,
+            ReadConsistency::Strong => units,
+            ReadConsistency::Transactional => units * 2.0,
+        }
+    }
+}
+
+impl WriteMode {
+    fn units(self, size: usize) -> f64 {
+        let units = size.max(1).div_ceil(WRITE_UNIT_SIZE) as f64;
+        match self {
+            WriteMode::Standard => units,
+            WriteMode::Transactional => units * 2.0,
+        }
+    }
+}
+
+/// Read units of a GetItem returning `item`: one unit per started 4 KB of [`item_size`].
+pub fn estimate_read(item: &HashMap<String, AttributeValue>, consistency: ReadConsistency) -> f64 {
+    consistency.units(item_size(item))
+}
+
+/// Write units of a PutItem or DeleteItem of `item`: one unit per started 1 KB of
+/// [`item_size`]. Writes to indexes are charged on top.
+pub fn estimate_write(item: &HashMap<String, AttributeValue>, mode: WriteMode) -> f64 {
+    mode.units(item_size(item))
+}
+
+/// Write units of an UpdateItem, which is charged for the larger of the item before
+/// and after the update.
+pub fn estimate_update(
+    before: &HashMap<String, AttributeValue>,
+    after: &HashMap<String, AttributeValue>,
+    mode: WriteMode,
+) -> f64 {
+    mode.units(item_size(before).max(item_size(after)))
+}
+
+/// Read units of a BatchGetItem returning `items`; each item is rounded up on its own.
+pub fn estimate_batch_get(
+    items: &[HashMap<String, AttributeValue>],
+    consistency: ReadConsistency,
+) -> f64 {
+    items
+        .iter()
+        .map(|item| estimate_read(item, consistency))
+        .sum()
+}
+
+/// Write units of a BatchWriteItem putting `items`; each item is rounded up on its own.
+pub fn estimate_batch_write(items: &[HashMap<String, AttributeValue>], mode: WriteMode) -> f64 {
+    items.iter().map(|item| estimate_write(item, mode)).sum()
+}
+
+/// Read units of a Query or Scan page that read `items`. Unlike batch reads, the sizes
+/// are added up before rounding, so many small items are cheap. Items removed by a
+/// filter expression still count and have to be included.
+pub fn estimate_query(
+    items: &[HashMap<String, AttributeValue>],
+    consistency: ReadConsistency,
+) -> f64 {
+    consistency.units(items.iter().map(item_size).sum())
+}
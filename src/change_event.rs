@@ -0,0 +1,122 @@
+use std::{collections::HashMap, time::SystemTime};
+
+#[cfg(feature = "lambda-events")]
+use aws_lambda_events::event::dynamodb::{Event, EventRecord};
+use aws_sdk_dynamodb::types::AttributeValue;
+#[cfg(feature = "streams")]
+use aws_sdk_dynamodbstreams::types::Record;
+#[cfg(any(feature = "lambda-events", feature = "streams"))]
+use serde::de::DeserializeOwned;
+
+#[cfg(any(feature = "lambda-events", feature = "streams"))]
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Insert,
+    Modify,
+    Remove,
+}
+
+/// A single item change from a DynamoDB stream, with the images deserialized into `T`.
+#[derive(Debug, Clone)]
+pub struct ChangeEvent<T> {
+    pub kind: ChangeKind,
+    pub event_id: String,
+    pub sequence_number: Option<String>,
+    pub approximate_created_at: Option<SystemTime>,
+    pub keys: HashMap<String, AttributeValue>,
+    /// `None` when the stream view type does not include the old image.
+    pub old: Option<T>,
+    /// `None` when the stream view type does not include the new image.
+    pub new: Option<T>,
+}
+
+#[cfg(any(feature = "lambda-events", feature = "streams"))]
+impl ChangeKind {
+    pub(crate) fn from_event_name(name: &str) -> Option<Self> {
+        match name {
+            "INSERT" => Some(Self::Insert),
+            "MODIFY" => Some(Self::Modify),
+            "REMOVE" => Some(Self::Remove),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "lambda-events")]
+impl<T: DeserializeOwned> ChangeEvent<T> {
+    pub fn from_lambda_record(record: EventRecord) -> Result<Self, Error> {
+        let kind = ChangeKind::from_event_name(&record.event_name).ok_or_else(|| {
+            Error::Validation(format!("unknown stream event name {}", record.event_name))
+        })?;
+        let change = record.change;
+
+        Ok(Self {
+            kind,
+            event_id: record.event_id,
+            sequence_number: change.sequence_number,
+            approximate_created_at: Some(change.approximate_creation_date_time.into()),
+            keys: change.keys.into(),
+            old: image(change.old_image)?,
+            new: image(change.new_image)?,
+        })
+    }
+}
+
+#[cfg(feature = "streams")]
+impl<T: DeserializeOwned> ChangeEvent<T> {
+    pub fn from_stream_record(record: Record) -> Result<Self, Error> {
+        let event_name = record
+            .event_name
+            .as_ref()
+            .map(|name| name.as_str())
+            .unwrap_or_default();
+        let kind = ChangeKind::from_event_name(event_name)
+            .ok_or_else(|| Error::Validation(format!("unknown stream event name {event_name}")))?;
+        let change = record.dynamodb.ok_or_else(|| {
+            Error::Validation("stream record contains no change data".to_string())
+        })?;
+
+        Ok(Self {
+            kind,
+            event_id: record.event_id.unwrap_or_default(),
+            sequence_number: change.sequence_number,
+            approximate_created_at: change
+                .approximate_creation_date_time
+                .and_then(|created_at| created_at.try_into().ok()),
+            keys: serde_dynamo::Item::from(change.keys.unwrap_or_default()).into(),
+            old: stream_image(change.old_image)?,
+            new: stream_image(change.new_image)?,
+        })
+    }
+}
+
+/// Converts every record of a Lambda DynamoDB stream trigger into a [`ChangeEvent`].
+#[cfg(feature = "lambda-events")]
+pub fn parse_lambda_event<T: DeserializeOwned>(event: Event) -> Result<Vec<ChangeEvent<T>>, Error> {
+    event
+        .records
+        .into_iter()
+        .map(ChangeEvent::from_lambda_record)
+        .collect()
+}
+
+#[cfg(feature = "lambda-events")]
+fn image<T: DeserializeOwned>(item: serde_dynamo::Item) -> Result<Option<T>, Error> {
+    if item.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_dynamo::from_item(item)?))
+}
+
+#[cfg(feature = "streams")]
+fn stream_image<T: DeserializeOwned>(
+    item: Option<HashMap<String, aws_sdk_dynamodbstreams::types::AttributeValue>>,
+) -> Result<Option<T>, Error> {
+    match item {
+        Some(item) if !item.is_empty() => Ok(Some(serde_dynamo::from_item(item)?)),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::{primitives::Blob, types::AttributeValue};
+
+use crate::{
+    keys::{composite_key, prefixed, ulid},
+    query::SortKeyCondition,
+    DynamoDbStore, Error,
+};
+
+/// Stays well below the 400 KB item limit, leaving room for keys and attribute names.
+pub const DEFAULT_CHUNK_SIZE: usize = 300 * 1024;
+
+const MANIFEST_SORT_KEY: &str = "MANIFEST";
+const DATA_PREFIX: &str = "DATA";
+const MAX_CHUNKS: usize = 9_999;
+
+const DATA_ATTRIBUTE: &str = "data";
+const VERSION_ATTRIBUTE: &str = "version";
+const CHUNKS_ATTRIBUTE: &str = "chunks";
+const SIZE_ATTRIBUTE: &str = "size";
+
+/// Payloads larger than one item, split across several items of one partition.
+///
+/// Chunks are written as `SK = DATA#<version>#0001…` before a `SK = MANIFEST` item
+/// points readers at the new version, so a write that fails half-way leaves the
+/// previous payload readable. Chunks of replaced or abandoned versions are removed
+/// after a successful write, or later with [`ChunkedPayloads::cleanup`].
+#[derive(Debug, Clone)]
+pub struct ChunkedPayloads {
+    store: DynamoDbStore,
+    table_name: String,
+    partition_key: String,
+    sort_key: String,
+    chunk_size: usize,
+}
+
+impl DynamoDbStore {
+    pub fn chunked_payloads(&self, table_name: impl Into<String>) -> ChunkedPayloads {
+        ChunkedPayloads {
+            store: self.clone(),
+            table_name: table_name.into(),
+            partition_key: "PK".to_string(),
+            sort_key: "SK".to_string(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl ChunkedPayloads {
+    pub fn with_key_attributes(
+        mut self,
+        partition_key: impl Into<String>,
+        sort_key: impl Into<String>,
+    ) -> Self {
+        self.partition_key = partition_key.into();
+        self.sort_key = sort_key.into();
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Stores `payload` under `id`, replacing any previous payload.
+    pub async fn put(&self, id: &str, payload: &[u8]) -> Result<(), Error> {
+        let chunk_count = payload.len().div_ceil(self.chunk_size);
+        if chunk_count > MAX_CHUNKS {
+            return Err(Error::Validation(format!(
+                "payload of {} bytes needs more than {MAX_CHUNKS} chunks of {} bytes",
+                payload.len(),
+                self.chunk_size
+            )));
+        }
+
+        let version = ulid();
+        let chunks = payload
+            .chunks(self.chunk_size)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut item = self.key(id, chunk_sort_key(&version, index));
+                item.insert(
+                    DATA_ATTRIBUTE.to_string(),
+                    AttributeValue::B(Blob::new(chunk)),
+                );
+                item
+            })
+            .collect();
+        let result = self.store.batch_put_items(&self.table_name, chunks).await?;
+        if !result.unprocessed.is_empty() {
+            return Err(Error::UnexpectedResponse {
+                operation: "BatchWriteItem",
+                reason: format!(
+                    "{} chunks of payload {id} remained unprocessed",
+                    result.unprocessed.len()
+                ),
+            });
+        }
+
+        let mut manifest = self.key(id, MANIFEST_SORT_KEY.to_string());
+        manifest.extend([
+            (VERSION_ATTRIBUTE.to_string(), AttributeValue::S(version)),
+            (
+                CHUNKS_ATTRIBUTE.to_string(),
+                AttributeValue::N(chunk_count.to_string()),
+            ),
+            (
+                SIZE_ATTRIBUTE.to_string(),
+                AttributeValue::N(payload.len().to_string()),
+            ),
+        ]);
+        self.store.put_item(&self.table_name, manifest).await?;
+
+        self.cleanup(id).await?;
+        Ok(())
+    }
+
+    /// Reassembles the payload stored under `id`.
+    pub async fn get(&self, id: &str) -> Result<Option<Vec<u8>>, Error> {
+        let Some(manifest) = self.manifest(id).await? else {
+            return Ok(None);
+        };
+
+        let chunks = self
+            .store
+            .query(&self.table_name)
+            .partition_key(&self.partition_key, AttributeValue::S(id.to_string()))
+            .sort_key_begins_with(&self.sort_key, chunk_prefix(&manifest.version))
+            .consistent_read(true)
+            .all()
+            .await?;
+        if chunks.len() != manifest.chunks {
+            return Err(self.corrupt(
+                id,
+                format!(
+                    "expected {} chunks, found {}",
+                    manifest.chunks,
+                    chunks.len()
+                ),
+            ));
+        }
+
+        let mut payload = Vec::with_capacity(manifest.size);
+        for mut chunk in chunks {
+            let Some(AttributeValue::B(data)) = chunk.remove(DATA_ATTRIBUTE) else {
+                return Err(self.corrupt(id, "chunk lacks binary data".to_string()));
+            };
+            payload.extend(data.into_inner());
+        }
+        if payload.len() != manifest.size {
+            return Err(self.corrupt(
+                id,
+                format!("expected {} bytes, found {}", manifest.size, payload.len()),
+            ));
+        }
+
+        Ok(Some(payload))
+    }
+
+    /// Removes the payload stored under `id`. The manifest goes first, so readers never
+    /// see a partially deleted payload.
+    pub async fn delete(&self, id: &str) -> Result<(), Error> {
+        self.store
+            .delete_item(
+                &self.table_name,
+                self.key(id, MANIFEST_SORT_KEY.to_string()),
+            )
+            .await?;
+        self.cleanup(id).await?;
+        Ok(())
+    }
+
+    /// Deletes chunks under `id` that the manifest does not reference, e.g. those left
+    /// behind by an interrupted write. Returns the number of chunks removed.
+    ///
+    /// This also removes chunks of a write still in progress, so payloads under one id
+    /// should not be written concurrently.
+    pub async fn cleanup(&self, id: &str) -> Result<usize, Error> {
+        let current = self
+            .manifest(id)
+            .await?
+            .map(|manifest| chunk_prefix(&manifest.version));
+
+        let orphans: Vec<_> = self
+            .store
+            .query(&self.table_name)
+            .partition_key(&self.partition_key, AttributeValue::S(id.to_string()))
+            .sort_key_begins_with(&self.sort_key, prefixed(DATA_PREFIX, ""))
+            .projection([self.partition_key.as_str(), self.sort_key.as_str()])
+            .consistent_read(true)
+            .all()
+            .await?
+            .into_iter()
+            .filter(|key| match (key.get(&self.sort_key), &current) {
+                (Some(AttributeValue::S(sort_key)), Some(prefix)) => !sort_key.starts_with(prefix),
+                _ => true,
+            })
+            .collect();
+
+        let removed = orphans.len();
+        let result = self
+            .store
+            .batch_delete_items(&self.table_name, orphans)
+            .await?;
+        Ok(removed - result.unprocessed.len())
+    }
+
+    async fn manifest(&self, id: &str) -> Result<Option<Manifest>, Error> {
+        let Some(mut item) = self
+            .store
+            .query(&self.table_name)
+            .partition_key(&self.partition_key, AttributeValue::S(id.to_string()))
+            .sort_key(
+                &self.sort_key,
+                SortKeyCondition::Eq(AttributeValue::S(MANIFEST_SORT_KEY.to_string())),
+            )
+            .consistent_read(true)
+            .all()
+            .await?
+            .pop()
+        else {
+            return Ok(None);
+        };
+
+        let version = match item.remove(VERSION_ATTRIBUTE) {
+            Some(AttributeValue::S(version)) => version,
+            _ => return Err(self.corrupt(id, "manifest lacks a version".to_string())),
+        };
+        let chunks = number(&item, CHUNKS_ATTRIBUTE)
+            .ok_or_else(|| self.corrupt(id, "manifest lacks a chunk count".to_string()))?;
+        let size = number(&item, SIZE_ATTRIBUTE)
+            .ok_or_else(|| self.corrupt(id, "manifest lacks a size".to_string()))?;
+
+        Ok(Some(Manifest {
+            version,
+            chunks,
+            size,
+        }))
+    }
+
+    fn key(&self, id: &str, sort_key: String) -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            (
+                self.partition_key.clone(),
+                AttributeValue::S(id.to_string()),
+            ),
+            (self.sort_key.clone(), AttributeValue::S(sort_key)),
+        ])
+    }
+
+    fn corrupt(&self, id: &str, reason: String) -> Error {
+        Error::UnexpectedResponse {
+            operation: "Query",
+            reason: format!("chunked payload {id} in {}: {reason}", self.table_name),
+        }
+    }
+}
+
+struct Manifest {
+    version: String,
+    chunks: usize,
+    size: usize,
+}
+
+/// `DATA#<version>#`, shared by every chunk of one version.
+fn chunk_prefix(version: &str) -> String {
+    composite_key([DATA_PREFIX, version, ""])
+}
+
+/// Chunk numbers start at 1 and are zero-padded so chunks sort in payload order.
+fn chunk_sort_key(version: &str, index: usize) -> String {
+    composite_key([DATA_PREFIX, version, &format!("{:04}", index + 1)])
+}
+
+fn number(item: &HashMap<String, AttributeValue>, name: &str) -> Option<usize> {
+    match item.get(name) {
+        Some(AttributeValue::N(value)) => value.parse().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, fmt::Write};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::Error;
+
+/// Converts a struct (or anything serializing to a map) into an item.
+pub fn to_attribute_map<T: Serialize>(value: &T) -> Result<HashMap<String, AttributeValue>, Error> {
+    Ok(serde_dynamo::to_item(value)?)
+}
+
+pub fn from_attribute_map<T: DeserializeOwned>(
+    item: HashMap<String, AttributeValue>,
+) -> Result<T, Error> {
+    Ok(serde_dynamo::from_item(item)?)
+}
+
+pub fn to_attribute_value<T: Serialize>(value: &T) -> Result<AttributeValue, Error> {
+    Ok(serde_dynamo::to_attribute_value(value)?)
+}
+
+pub fn from_attribute_value<T: DeserializeOwned>(value: AttributeValue) -> Result<T, Error> {
+    Ok(serde_dynamo::from_attribute_value(value)?)
+}
+
+/// Converts a single attribute to plain JSON; sets become arrays and binary values
+/// arrays of bytes.
+pub fn attribute_value_to_json(value: &AttributeValue) -> Result<Value, Error> {
+    from_attribute_value(value.clone())
+}
+
+pub fn json_to_attribute_value(value: &Value) -> Result<AttributeValue, Error> {
+    to_attribute_value(value)
+}
+
+pub fn attribute_map_to_json(item: &HashMap<String, AttributeValue>) -> Result<Value, Error> {
+    from_attribute_map(item.clone())
+}
+
+/// Appends a text form of `attributes` that is the same for items DynamoDB stores the
+/// same way: attributes and map entries in name order, numbers normalized, and sets
+/// sorted without duplicates. Every part is length-prefixed, so different items never
+/// collide.
+pub(crate) fn write_canonical_attributes<'a>(
+    attributes: impl IntoIterator<Item = (&'a String, &'a AttributeValue)>,
+    out: &mut String,
+) {
+    let mut attributes: Vec<_> = attributes.into_iter().collect();
+    attributes.sort_by_key(|(name, _)| *name);
+
+    let _ = write!(out, "M{}[", attributes.len());
+    for (name, value) in attributes {
+        write_text(name, out);
+        write_canonical(value, out);
+    }
+    out.push(']');
+}
+
+/// Appends the text form of a single value, see [`write_canonical_attributes`].
+pub(crate) fn write_canonical(value: &AttributeValue, out: &mut String) {
+    match value {
+        AttributeValue::S(value) => {
+            out.push('S');
+            write_text(value, out);
+        }
+        AttributeValue::N(value) => {
+            out.push('N');
+            write_text(&canonical_number(value), out);
+        }
+        AttributeValue::B(value) => {
+            out.push('B');
+            write_bytes(value.as_ref(), out);
+        }
+        AttributeValue::Bool(value) => out.push(if *value { 'T' } else { 'F' }),
+        AttributeValue::Null(_) => out.push('0'),
+        AttributeValue::Ss(values) => {
+            let values: Vec<&str> = sorted_set(values.iter().map(String::as_str));
+            write_set("SS", &values, out, |value, out| write_text(value, out));
+        }
+        AttributeValue::Ns(values) => {
+            let numbers: Vec<String> = values.iter().map(|value| canonical_number(value)).collect();
+            let values = sorted_set(numbers.iter().map(String::as_str));
+            write_set("NS", &values, out, |value, out| write_text(value, out));
+        }
+        AttributeValue::Bs(values) => {
+            let values = sorted_set(values.iter().map(|value| value.as_ref()));
+            write_set("BS", &values, out, |value, out| write_bytes(value, out));
+        }
+        AttributeValue::L(values) => {
+            let _ = write!(out, "L{}[", values.len());
+            for value in values {
+                write_canonical(value, out);
+            }
+            out.push(']');
+        }
+        AttributeValue::M(map) => write_canonical_attributes(map, out),
+        // Future value types are compared by their debug form.
+        other => write_text(&format!("{other:?}"), out),
+    }
+}
+
+/// Normalizes a DynamoDB number to `{sign}{digits}e{exponent}` without leading or
+/// trailing zeros, so `1.50`, `+15e-1` and `1.5` all give `15e-1`. Strings that are no
+/// number are returned as they are.
+pub(crate) fn canonical_number(number: &str) -> String {
+    let trimmed = number.trim();
+    let (negative, unsigned) = match trimmed.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()),
+        None => (unsigned, Some(0)),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let is_number = !(integer.is_empty() && fraction.is_empty())
+        && integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|byte| byte.is_ascii_digit());
+    let Some(exponent) = exponent.filter(|_| is_number) else {
+        return number.to_string();
+    };
+
+    let digits = format!("{integer}{fraction}");
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    let significant = digits.trim_end_matches('0');
+    let exponent = exponent - fraction.len() as i64 + (digits.len() - significant.len()) as i64;
+    let sign = if negative { "-" } else { "" };
+    format!("{sign}{significant}e{exponent}")
+}
+
+fn sorted_set<T: Ord>(values: impl Iterator<Item = T>) -> Vec<T> {
+    let mut values: Vec<T> = values.collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+fn write_set<T>(tag: &str, values: &[T], out: &mut String, write: impl Fn(&T, &mut String)) {
+    let _ = write!(out, "{tag}{}[", values.len());
+    for value in values {
+        write(value, out);
+    }
+    out.push(']');
+}
+
+fn write_text(text: &str, out: &mut String) {
+    let _ = write!(out, "{}:{text}", text.len());
+}
+
+fn write_bytes(bytes: &[u8], out: &mut String) {
+    let _ = write!(out, "{}:", bytes.len());
+    for byte in bytes {
+        let _ = write!(out, "{byte:02x}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::primitives::Blob;
+
+    use super::*;
+
+    fn canonical(value: &AttributeValue) -> String {
+        let mut out = String::new();
+        write_canonical(value, &mut out);
+        out
+    }
+
+    fn n(value: &str) -> AttributeValue {
+        AttributeValue::N(value.to_string())
+    }
+
+    #[test]
+    fn equal_numbers_normalize_alike() {
+        for number in ["1.5", "1.50", "+15e-1", "0015E-1", "0.15e1"] {
+            assert_eq!(canonical_number(number), "15e-1", "{number}");
+        }
+        assert_eq!(canonical_number("100"), "1e2");
+        assert_eq!(canonical_number("-0.000"), "0");
+        assert_eq!(canonical_number("-12"), "-12e0");
+        assert_eq!(canonical_number("abc"), "abc");
+    }
+
+    #[test]
+    fn map_order_and_set_order_do_not_matter() {
+        let first = HashMap::from([
+            ("a".to_string(), n("1")),
+            (
+                "b".to_string(),
+                AttributeValue::Ss(vec!["y".into(), "x".into()]),
+            ),
+            (
+                "c".to_string(),
+                AttributeValue::Bs(vec![Blob::new(vec![2]), Blob::new(vec![1])]),
+            ),
+        ]);
+        let second = HashMap::from([
+            (
+                "c".to_string(),
+                AttributeValue::Bs(vec![Blob::new(vec![1]), Blob::new(vec![2])]),
+            ),
+            (
+                "b".to_string(),
+                AttributeValue::Ss(vec!["x".into(), "y".into()]),
+            ),
+            ("a".to_string(), n("1.0")),
+        ]);
+
+        assert_eq!(
+            canonical(&AttributeValue::M(first)),
+            canonical(&AttributeValue::M(second))
+        );
+    }
+
+    #[test]
+    fn lengths_keep_different_values_apart() {
+        let joined = AttributeValue::L(vec![AttributeValue::S("ab".to_string())]);
+        let split = AttributeValue::L(vec![
+            AttributeValue::S("a".to_string()),
+            AttributeValue::S("b".to_string()),
+        ]);
+
+        assert_ne!(canonical(&joined), canonical(&split));
+        assert_ne!(
+            canonical(&n("1")),
+            canonical(&AttributeValue::S("1".to_string()))
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::future::try_join_all;
+
+use crate::{throttling::CapacityKind, DynamoDbStore, Error};
+
+/// The largest `TotalSegments` DynamoDB accepts for a parallel scan.
+const MAX_SEGMENTS: i32 = 1_000_000;
+
+type Transform = Arc<
+    dyn Fn(HashMap<String, AttributeValue>) -> Option<HashMap<String, AttributeValue>>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+pub struct CopyTableOptions {
+    /// Parallel scan segments, from 1 to 1,000,000.
+    pub segments: i32,
+    transform: Option<Transform>,
+}
+
+impl Default for CopyTableOptions {
+    fn default() -> Self {
+        Self {
+            segments: 4,
+            transform: None,
+        }
+    }
+}
+
+impl fmt::Debug for CopyTableOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyTableOptions")
+            .field("segments", &self.segments)
+            .field("transform", &self.transform.is_some())
+            .finish()
+    }
+}
+
+impl CopyTableOptions {
+    pub fn segments(mut self, segments: i32) -> Self {
+        self.segments = segments.clamp(1, MAX_SEGMENTS);
+        self
+    }
+
+    /// Rewrites each item before it is written; returning `None` skips the item.
+    pub fn transform(
+        mut self,
+        transform: impl Fn(HashMap<String, AttributeValue>) -> Option<HashMap<String, AttributeValue>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.transform = Some(Arc::new(transform));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopyTableSummary {
+    pub scanned: usize,
+    pub written: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl DynamoDbStore {
+    /// Copies every item from `source` into `destination` using a parallel scan.
+    pub async fn copy_table(
+        &self,
+        source: &str,
+        destination: &str,
+        options: CopyTableOptions,
+    ) -> Result<CopyTableSummary, Error> {
+        if !(1..=MAX_SEGMENTS).contains(&options.segments) {
+            return Err(Error::Validation(format!(
+                "copy segments must be between 1 and {MAX_SEGMENTS}, got {}",
+                options.segments
+            )));
+        }
+        let concurrency = options.segments as usize;
+        self.warn_on_throttling("CopyTable", source, concurrency, &[CapacityKind::Read])
+            .await;
+        self.warn_on_throttling(
+            "CopyTable",
+            destination,
+            concurrency,
+            &[CapacityKind::Write],
+        )
+        .await;
+
+        let segments = (0..options.segments)
+            .map(|segment| self.copy_segment(source, destination, segment, &options));
+        let summaries = try_join_all(segments).await?;
+
+        Ok(summaries
+            .into_iter()
+            .fold(CopyTableSummary::default(), |total, summary| {
+                CopyTableSummary {
+                    scanned: total.scanned + summary.scanned,
+                    written: total.written + summary.written,
+                    skipped: total.skipped + summary.skipped,
+                    failed: total.failed + summary.failed,
+                }
+            }))
+    }
+
+    async fn copy_segment(
+        &self,
+        source: &str,
+        destination: &str,
+        segment: i32,
+        options: &CopyTableOptions,
+    ) -> Result<CopyTableSummary, Error> {
+        let mut scan = self.scan(source).segment(segment, options.segments);
+        let mut summary = CopyTableSummary::default();
+
+        loop {
+            let page = scan.page().await?;
+            let scanned = page.items.len();
+            summary.scanned += scanned;
+
+            let items: Vec<_> = match &options.transform {
+                Some(transform) => page
+                    .items
+                    .into_iter()
+                    .filter_map(|item| transform(item))
+                    .collect(),
+                None => page.items,
+            };
+            summary.skipped += scanned - items.len();
+
+            let result = self.batch_put_items(destination, items).await?;
+            summary.written += result.processed;
+            summary.failed += result.unprocessed.len();
+
+            match page.last_evaluated_key {
+                Some(key) => scan = scan.start_after(key),
+                None => return Ok(summary),
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::{AttributeValue, ReturnValue};
+
+use crate::{expression::CheckedExpressions, instrument::Operation, DynamoDbStore, Error};
+
+impl DynamoDbStore {
+    /// Atomically adds `delta` to a numeric attribute and returns the new value.
+    ///
+    /// A missing item or attribute counts as `0`.
+    pub async fn increment(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        attribute: &str,
+        delta: i64,
+    ) -> Result<i64, Error> {
+        let operation = Operation::new("UpdateItem", table_name).attributes(&key);
+        let request = self
+            .client()
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(key))
+            .update_expression("ADD #counter :delta")
+            .expression_attribute_names("#counter", attribute)
+            .expression_attribute_values(":delta", AttributeValue::N(delta.to_string()))
+            .return_values(ReturnValue::UpdatedNew)
+            .checked()?
+            .send();
+        let output = operation.send(request).await?;
+
+        match output
+            .attributes
+            .as_ref()
+            .and_then(|attributes| attributes.get(attribute))
+        {
+            Some(AttributeValue::N(value)) => {
+                value.parse().map_err(|_| Error::UnexpectedResponse {
+                    operation: "UpdateItem",
+                    reason: format!("{attribute} is not an integer: {value}"),
+                })
+            }
+            _ => Err(Error::UnexpectedResponse {
+                operation: "UpdateItem",
+                reason: format!("response did not contain {attribute}"),
+            }),
+        }
+    }
+
+    pub async fn decrement(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        attribute: &str,
+        delta: i64,
+    ) -> Result<i64, Error> {
+        let delta = delta
+            .checked_neg()
+            .ok_or_else(|| Error::Validation(format!("cannot decrement {attribute} by {delta}")))?;
+        self.increment(table_name, key, attribute, delta).await
+    }
+}
@@ -0,0 +1,130 @@
+use aws_sdk_dynamodb::{
+    error::BuildError, operation::create_table::CreateTableOutput, types::AttributeDefinition,
+};
+
+use crate::{
+    instrument::Operation,
+    schema::{
+        key_schema, provisioned_throughput, BillingMode, Gsi, KeyAttribute, ScalarType, TableSchema,
+    },
+    DynamoDbStore, Error,
+};
+
+impl DynamoDbStore {
+    pub fn create_table(&self, table_name: impl Into<String>) -> CreateTableBuilder<'_> {
+        CreateTableBuilder {
+            store: self,
+            table_name: table_name.into(),
+            partition_key: None,
+            sort_key: None,
+            billing_mode: BillingMode::PayPerRequest,
+            provisioned_throughput: None,
+            gsis: Vec::new(),
+        }
+    }
+
+    pub async fn create_table_from_schema(
+        &self,
+        table_name: impl Into<String>,
+        schema: &TableSchema,
+    ) -> Result<CreateTableOutput, Error> {
+        CreateTableBuilder {
+            store: self,
+            table_name: table_name.into(),
+            partition_key: Some(schema.partition_key.clone()),
+            sort_key: schema.sort_key.clone(),
+            billing_mode: schema.billing_mode.clone(),
+            provisioned_throughput: schema.provisioned_throughput,
+            gsis: schema.gsis.clone(),
+        }
+        .send()
+        .await
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateTableBuilder<'a> {
+    store: &'a DynamoDbStore,
+    table_name: String,
+    partition_key: Option<KeyAttribute>,
+    sort_key: Option<KeyAttribute>,
+    billing_mode: BillingMode,
+    provisioned_throughput: Option<(i64, i64)>,
+    gsis: Vec<Gsi>,
+}
+
+impl CreateTableBuilder<'_> {
+    pub fn partition_key(mut self, name: impl Into<String>, scalar_type: ScalarType) -> Self {
+        self.partition_key = Some(KeyAttribute::new(name, scalar_type));
+        self
+    }
+
+    pub fn sort_key(mut self, name: impl Into<String>, scalar_type: ScalarType) -> Self {
+        self.sort_key = Some(KeyAttribute::new(name, scalar_type));
+        self
+    }
+
+    pub fn billing_mode(mut self, billing_mode: BillingMode) -> Self {
+        self.billing_mode = billing_mode;
+        self
+    }
+
+    pub fn provisioned_throughput(mut self, read_capacity: i64, write_capacity: i64) -> Self {
+        self.billing_mode = BillingMode::Provisioned;
+        self.provisioned_throughput = Some((read_capacity, write_capacity));
+        self
+    }
+
+    pub fn gsi(mut self, gsi: Gsi) -> Self {
+        self.gsis.push(gsi);
+        self
+    }
+
+    pub async fn send(self) -> Result<CreateTableOutput, Error> {
+        let partition_key = self.partition_key.as_ref().ok_or_else(|| {
+            BuildError::missing_field("partition_key", "a table needs a partition key")
+        })?;
+
+        let mut definitions: Vec<AttributeDefinition> = Vec::new();
+        let key_attributes = std::iter::once(partition_key)
+            .chain(self.sort_key.as_ref())
+            .chain(self.gsis.iter().flat_map(Gsi::key_attributes));
+        for attribute in key_attributes {
+            if !definitions
+                .iter()
+                .any(|definition| definition.attribute_name() == attribute.name)
+            {
+                definitions.push(attribute.definition()?);
+            }
+        }
+
+        let gsis = self
+            .gsis
+            .iter()
+            .map(Gsi::to_sdk)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let throughput = match (&self.billing_mode, self.provisioned_throughput) {
+            (BillingMode::Provisioned, Some((read, write))) => {
+                Some(provisioned_throughput(read, write)?)
+            }
+            _ => None,
+        };
+
+        let operation = Operation::new("CreateTable", &self.table_name);
+        let request = self
+            .store
+            .client()
+            .create_table()
+            .table_name(&self.table_name)
+            .set_key_schema(Some(key_schema(partition_key, self.sort_key.as_ref())?))
+            .set_attribute_definitions(Some(definitions))
+            .billing_mode(self.billing_mode.clone())
+            .set_provisioned_throughput(throughput)
+            .set_global_secondary_indexes((!gsis.is_empty()).then_some(gsis))
+            .send();
+        let result = operation.send(request).await?;
+
+        Ok(result)
+    }
+}
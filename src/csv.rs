@@ -0,0 +1,158 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{batch::MAX_BATCH_WRITE_ITEMS, DynamoDbStore, Error, ImportReport, LineFailure};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvValueType {
+    String,
+    Number,
+    Bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvColumn {
+    pub header: String,
+    pub attribute: String,
+    pub value_type: CsvValueType,
+}
+
+/// Maps CSV columns to top-level attributes of flat items.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvMapping {
+    pub columns: Vec<CsvColumn>,
+}
+
+impl CsvMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn column(
+        mut self,
+        header: impl Into<String>,
+        attribute: impl Into<String>,
+        value_type: CsvValueType,
+    ) -> Self {
+        self.columns.push(CsvColumn {
+            header: header.into(),
+            attribute: attribute.into(),
+            value_type,
+        });
+        self
+    }
+
+    fn to_record(&self, item: &HashMap<String, AttributeValue>) -> Vec<String> {
+        self.columns
+            .iter()
+            .map(|column| match item.get(&column.attribute) {
+                Some(AttributeValue::S(value)) | Some(AttributeValue::N(value)) => value.clone(),
+                Some(AttributeValue::Bool(value)) => value.to_string(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    fn to_item(
+        &self,
+        record: &csv::StringRecord,
+    ) -> Result<HashMap<String, AttributeValue>, String> {
+        let mut item = HashMap::new();
+
+        for (column, value) in self.columns.iter().zip(record.iter()) {
+            if value.is_empty() {
+                continue;
+            }
+
+            let value = match column.value_type {
+                CsvValueType::String => AttributeValue::S(value.to_string()),
+                CsvValueType::Number => {
+                    value.parse::<f64>().map_err(|_| {
+                        format!("column `{}` is not a number: {value}", column.header)
+                    })?;
+                    AttributeValue::N(value.to_string())
+                }
+                CsvValueType::Bool => AttributeValue::Bool(value.parse().map_err(|_| {
+                    format!("column `{}` is not a boolean: {value}", column.header)
+                })?),
+            };
+            item.insert(column.attribute.clone(), value);
+        }
+
+        Ok(item)
+    }
+}
+
+impl DynamoDbStore {
+    /// Writes the mapped columns of every item as CSV, starting with a header row.
+    pub async fn export_csv<W: Write>(
+        &self,
+        table_name: &str,
+        writer: W,
+        mapping: &CsvMapping,
+    ) -> Result<usize, Error> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(mapping.columns.iter().map(|column| &column.header))?;
+
+        let attributes = mapping
+            .columns
+            .iter()
+            .map(|column| column.attribute.clone());
+        let mut scan = self.scan(table_name).projection(attributes);
+        let mut written = 0;
+
+        loop {
+            let page = scan.page().await?;
+            for item in &page.items {
+                writer.write_record(mapping.to_record(item))?;
+                written += 1;
+            }
+
+            match page.last_evaluated_key {
+                Some(key) => scan = scan.start_after(key),
+                None => break,
+            }
+        }
+        writer.flush()?;
+
+        Ok(written)
+    }
+
+    /// Reads CSV with a header row and batch-writes one item per record.
+    pub async fn import_csv<R: Read>(
+        &self,
+        table_name: &str,
+        reader: R,
+        mapping: &CsvMapping,
+    ) -> Result<ImportReport, Error> {
+        let mut reader = csv::Reader::from_reader(reader);
+        let mut report = ImportReport::default();
+        let mut pending = Vec::new();
+
+        for record in reader.records() {
+            let record = record?;
+            let line = record
+                .position()
+                .map(|position| position.line() as usize)
+                .unwrap_or_default();
+
+            match mapping.to_item(&record) {
+                Ok(item) => pending.push((line, item)),
+                Err(reason) => report.failures.push(LineFailure { line, reason }),
+            }
+
+            if pending.len() == MAX_BATCH_WRITE_ITEMS {
+                self.import_batch(table_name, &mut pending, &mut report)
+                    .await?;
+            }
+        }
+        self.import_batch(table_name, &mut pending, &mut report)
+            .await?;
+
+        Ok(report)
+    }
+}
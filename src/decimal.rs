@@ -0,0 +1,194 @@
+use std::{collections::HashSet, str::FromStr};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use rust_decimal::Decimal;
+use serde::{de, Deserializer, Serializer};
+
+use crate::{sets::SetValues, Error};
+
+/// Significant digits a DynamoDB number can hold.
+pub const MAX_PRECISION: usize = 38;
+/// Exponent range of non-zero DynamoDB numbers (1E-130 up to 9.99…E+125).
+const MIN_EXPONENT: i64 = -130;
+const MAX_EXPONENT: i64 = 125;
+
+/// Stores a decimal as a string, for `#[serde(with = "clean_dynamodb_store::decimal")]`.
+/// serde_dynamo reads number attributes through `i64`, `u64` or `f64` only, so a
+/// string is the one serde representation that reads back exactly.
+pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+/// Reads a decimal stored as a string or a number. Numbers with a fraction pass through
+/// `f64` and are only exact up to 15 significant digits.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+    deserializer.deserialize_any(DecimalVisitor)
+}
+
+/// Like the module-level functions, for `Option<Decimal>` fields.
+pub mod option {
+    use rust_decimal::Decimal;
+    use serde::{de, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Decimal>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Decimal>, D::Error> {
+        deserializer.deserialize_option(OptionVisitor)
+    }
+
+    struct OptionVisitor;
+
+    impl<'de> de::Visitor<'de> for OptionVisitor {
+        type Value = Option<Decimal>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an optional decimal")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            super::deserialize(deserializer).map(Some)
+        }
+    }
+}
+
+/// Converts `value` to a number attribute (`N`) without going through `f64`, for `ADD`
+/// updates, numeric sort keys and other places that need a real number.
+pub fn to_number(value: &Decimal) -> Result<AttributeValue, Error> {
+    Ok(AttributeValue::N(number_string(value)?))
+}
+
+/// Reads a decimal from a number attribute, or from a string written by [`serialize`].
+pub fn from_number(value: &AttributeValue) -> Result<Decimal, Error> {
+    match value {
+        AttributeValue::N(number) | AttributeValue::S(number) => parse(number),
+        other => Err(Error::Validation(format!(
+            "expected a number attribute, got {other:?}"
+        ))),
+    }
+}
+
+/// Checks that `number` is a decimal DynamoDB can store exactly: at most 38
+/// significant digits and an exponent between -130 and 125.
+pub fn validate_number(number: &str) -> Result<(), Error> {
+    let invalid = |reason: &str| Error::Validation(format!("invalid number {number:?}: {reason}"));
+
+    let unsigned = number.strip_prefix('-').unwrap_or(number);
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (
+            mantissa,
+            exponent
+                .parse::<i64>()
+                .map_err(|_| invalid("malformed exponent"))?,
+        ),
+        None => (unsigned, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|byte| byte.is_ascii_digit())
+    {
+        return Err(invalid("not a decimal number"));
+    }
+
+    let digits = format!("{integer}{fraction}");
+    let significant = digits.trim_start_matches('0');
+    if significant.is_empty() {
+        return Ok(());
+    }
+    let leading_zeros = digits.len() - significant.len();
+    let significant = significant.trim_end_matches('0');
+    if significant.len() > MAX_PRECISION {
+        return Err(invalid(&format!(
+            "{} significant digits, DynamoDB keeps at most {MAX_PRECISION}",
+            significant.len()
+        )));
+    }
+
+    // Position of the most significant digit relative to the decimal point.
+    let magnitude = integer.len() as i64 - leading_zeros as i64 - 1 + exponent;
+    if !(MIN_EXPONENT..=MAX_EXPONENT).contains(&magnitude) {
+        return Err(invalid("out of DynamoDB's number range"));
+    }
+
+    Ok(())
+}
+
+impl SetValues for HashSet<Decimal> {
+    fn to_set_value(&self) -> Result<AttributeValue, Error> {
+        let numbers = self
+            .iter()
+            .map(number_string)
+            .collect::<Result<_, Error>>()?;
+        Ok(AttributeValue::Ns(numbers))
+    }
+
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+fn number_string(value: &Decimal) -> Result<String, Error> {
+    let number = value.normalize().to_string();
+    validate_number(&number)?;
+    Ok(number)
+}
+
+fn parse(number: &str) -> Result<Decimal, Error> {
+    let parsed = if number.contains(['e', 'E']) {
+        Decimal::from_scientific(number)
+    } else {
+        Decimal::from_str(number)
+    };
+    parsed.map_err(|err| Error::Validation(format!("invalid decimal {number:?}: {err}")))
+}
+
+struct DecimalVisitor;
+
+impl de::Visitor<'_> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a decimal number or string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        parse(value).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+        // The shortest representation that round-trips is what was stored, as long as
+        // the stored number fit into an f64.
+        parse(&value.to_string()).map_err(E::custom)
+    }
+}
@@ -2,13 +2,75 @@ use std::collections::HashMap;
 
 use aws_sdk_dynamodb::{operation::delete_item::DeleteItemOutput, types::AttributeValue};
 
+use crate::{
+    api::render_condition, expression::CheckedExpressions, filter::Filter, global::global_store,
+    instrument::Operation, options::RequestOptions, DynamoDbStore, Error,
+};
+
+impl DynamoDbStore {
+    pub async fn delete_item(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+    ) -> Result<DeleteItemOutput, Error> {
+        self.delete_item_with_options(table_name, key, self.defaults())
+            .await
+    }
+
+    pub async fn delete_item_with_options(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        options: &RequestOptions,
+    ) -> Result<DeleteItemOutput, Error> {
+        self.delete_item_if(table_name, key, None, options).await
+    }
+
+    /// Like [`delete_item_with_options`](Self::delete_item_with_options), failing with a
+    /// conditional check error if `condition` does not hold for the stored item.
+    pub(crate) async fn delete_item_if(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        condition: Option<Filter>,
+        options: &RequestOptions,
+    ) -> Result<DeleteItemOutput, Error> {
+        let options = options.or_defaults(self.defaults());
+        let operation = Operation::new("DeleteItem", table_name).attributes(&key);
+        let (expression, names, values) = render_condition(condition);
+
+        let request = self
+            .client()
+            .delete_item()
+            .table_name(table_name)
+            .set_key(Some(key))
+            .set_condition_expression(expression)
+            .set_expression_attribute_names(names)
+            .set_expression_attribute_values(values)
+            .set_return_values(options.return_values.clone())
+            .set_return_consumed_capacity(options.return_consumed_capacity.clone())
+            .checked()?;
+        let request = async {
+            match options.config_override() {
+                Some(config) => request.customize().config_override(config).send().await,
+                None => request.send().await,
+            }
+        };
+        let result = operation.send(request).await?;
+
+        Ok(result)
+    }
+}
+
+/// Sends a plain DeleteItem through the global store, keeping the SDK error type. Use
+/// [`DynamoDbStore::delete_item`] for request defaults and [`Error`].
 pub async fn delete_item(
     table_name: &str,
     key: HashMap<String, AttributeValue>,
 ) -> Result<DeleteItemOutput, aws_sdk_dynamodb::Error> {
-    let config = aws_config::load_from_env().await;
-
-    let result = aws_sdk_dynamodb::Client::new(&config)
+    let result = global_store()
+        .await
+        .client()
         .delete_item()
         .table_name(table_name)
         .set_key(Some(key))
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use aws_sdk_dynamodb::operation::delete_table::DeleteTableOutput;
+
+use crate::{instrument::Operation, wait::poll_until, DynamoDbStore, Error};
+
+impl DynamoDbStore {
+    pub async fn delete_table(&self, table_name: &str) -> Result<DeleteTableOutput, Error> {
+        let operation = Operation::new("DeleteTable", table_name);
+
+        let request = self.client().delete_table().table_name(table_name).send();
+        let result = operation.send(request).await?;
+
+        Ok(result)
+    }
+
+    /// Returns `false` when the table did not exist.
+    pub async fn delete_table_if_exists(&self, table_name: &str) -> Result<bool, Error> {
+        let operation = Operation::new("DeleteTable", table_name);
+
+        let request = self.client().delete_table().table_name(table_name).send();
+        match operation.send(request).await {
+            Ok(_) => Ok(true),
+            Err(err)
+                if err
+                    .as_service_error()
+                    .is_some_and(|err| err.is_resource_not_found_exception()) =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn wait_until_deleted(
+        &self,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        poll_until(
+            format!("table {table_name} to be deleted"),
+            timeout,
+            || async {
+                let operation = Operation::new("DescribeTable", table_name);
+                let request = self.client().describe_table().table_name(table_name).send();
+
+                match operation.send(request).await {
+                    Ok(_) => Ok(false),
+                    Err(err)
+                        if err
+                            .as_service_error()
+                            .is_some_and(|err| err.is_resource_not_found_exception()) =>
+                    {
+                        Ok(true)
+                    }
+                    Err(err) => Err(err.into()),
+                }
+            },
+        )
+        .await
+    }
+}
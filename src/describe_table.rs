@@ -0,0 +1,139 @@
+use aws_sdk_dynamodb::types::{AttributeDefinition, KeySchemaElement, KeyType};
+
+use crate::{
+    instrument::Operation,
+    schema::{BillingMode, KeyAttribute, ProjectionType},
+    ttl::TtlDescription,
+    DynamoDbStore, Error,
+};
+
+pub use aws_sdk_dynamodb::types::{IndexStatus, TableStatus};
+
+#[derive(Debug, Clone)]
+pub struct TableDescription {
+    pub name: String,
+    pub status: TableStatus,
+    pub partition_key: KeyAttribute,
+    pub sort_key: Option<KeyAttribute>,
+    pub gsis: Vec<IndexDescription>,
+    pub billing_mode: BillingMode,
+    /// Provisioned capacity of the table; `None` for on-demand tables.
+    pub throughput: Option<ProvisionedThroughput>,
+    pub item_count: i64,
+    /// Only filled by [`describe_table_with_ttl`](DynamoDbStore::describe_table_with_ttl),
+    /// which needs a DescribeTimeToLive call and permission on top of DescribeTable.
+    pub ttl: Option<TtlDescription>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvisionedThroughput {
+    pub read_units: i64,
+    pub write_units: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexDescription {
+    pub name: String,
+    pub status: Option<IndexStatus>,
+    pub partition_key: KeyAttribute,
+    pub sort_key: Option<KeyAttribute>,
+    pub projection: Option<ProjectionType>,
+    pub backfilling: bool,
+}
+
+impl DynamoDbStore {
+    /// Like [`describe_table`](Self::describe_table), also describing the TTL settings.
+    pub async fn describe_table_with_ttl(
+        &self,
+        table_name: &str,
+    ) -> Result<TableDescription, Error> {
+        let mut description = self.describe_table(table_name).await?;
+        description.ttl = Some(self.describe_ttl(table_name).await?);
+        Ok(description)
+    }
+
+    pub async fn describe_table(&self, table_name: &str) -> Result<TableDescription, Error> {
+        let operation = Operation::new("DescribeTable", table_name);
+        let request = self.client().describe_table().table_name(table_name).send();
+        let output = operation.send(request).await?;
+
+        let table = output.table.ok_or_else(|| Error::UnexpectedResponse {
+            operation: "DescribeTable",
+            reason: "response contained no table".to_string(),
+        })?;
+
+        let definitions = table.attribute_definitions();
+        let (partition_key, sort_key) = key_attributes(table.key_schema(), definitions);
+        let partition_key = partition_key.ok_or_else(|| Error::UnexpectedResponse {
+            operation: "DescribeTable",
+            reason: format!("table {table_name} has no partition key"),
+        })?;
+
+        let gsis = table
+            .global_secondary_indexes()
+            .iter()
+            .filter_map(|index| {
+                let (partition_key, sort_key) = key_attributes(index.key_schema(), definitions);
+
+                Some(IndexDescription {
+                    name: index.index_name()?.to_string(),
+                    status: index.index_status().cloned(),
+                    partition_key: partition_key?,
+                    sort_key,
+                    projection: index
+                        .projection()
+                        .and_then(|projection| projection.projection_type())
+                        .cloned(),
+                    backfilling: index.backfilling().unwrap_or(false),
+                })
+            })
+            .collect();
+
+        let billing_mode = table
+            .billing_mode_summary()
+            .and_then(|summary| summary.billing_mode())
+            .cloned()
+            .unwrap_or(BillingMode::Provisioned);
+        let throughput = table
+            .provisioned_throughput()
+            .filter(|_| billing_mode == BillingMode::Provisioned)
+            .map(|throughput| ProvisionedThroughput {
+                read_units: throughput.read_capacity_units().unwrap_or_default(),
+                write_units: throughput.write_capacity_units().unwrap_or_default(),
+            });
+
+        Ok(TableDescription {
+            name: table.table_name().unwrap_or(table_name).to_string(),
+            status: table
+                .table_status()
+                .cloned()
+                .unwrap_or(TableStatus::Creating),
+            partition_key,
+            sort_key,
+            gsis,
+            billing_mode,
+            throughput,
+            item_count: table.item_count().unwrap_or_default(),
+            ttl: None,
+        })
+    }
+}
+
+fn key_attributes(
+    schema: &[KeySchemaElement],
+    definitions: &[AttributeDefinition],
+) -> (Option<KeyAttribute>, Option<KeyAttribute>) {
+    let find = |key_type: KeyType| {
+        let name = schema
+            .iter()
+            .find(|element| element.key_type() == &key_type)?
+            .attribute_name();
+        let definition = definitions
+            .iter()
+            .find(|definition| definition.attribute_name() == name)?;
+
+        Some(KeyAttribute::new(name, definition.attribute_type().clone()))
+    };
+
+    (find(KeyType::Hash), find(KeyType::Range))
+}
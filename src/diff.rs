@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::Serialize;
+
+use crate::{update::UpdateExpression, DynamoDbStore, Error};
+
+impl DynamoDbStore {
+    /// Writes only the attributes that differ between `old` and `new`.
+    ///
+    /// Changed or added attributes become `SET` clauses; attributes that are missing or
+    /// null in `new` become `REMOVE` clauses. Nothing is sent when the two are equal.
+    pub async fn update_diff<T: Serialize>(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        old: &T,
+        new: &T,
+    ) -> Result<(), Error> {
+        let old: HashMap<String, AttributeValue> = serde_dynamo::to_item(old)?;
+        let new: HashMap<String, AttributeValue> = serde_dynamo::to_item(new)?;
+
+        let mut update = UpdateExpression::default();
+        for (name, value) in &new {
+            if key.contains_key(name) || matches!(value, AttributeValue::Null(_)) {
+                continue;
+            }
+            if old.get(name) != Some(value) {
+                update.set(name, value.clone());
+            }
+        }
+        for (name, value) in &old {
+            if key.contains_key(name) || matches!(value, AttributeValue::Null(_)) {
+                continue;
+            }
+            if matches!(new.get(name), None | Some(AttributeValue::Null(_))) {
+                update.remove(name);
+            }
+        }
+
+        self.send_update(table_name, key, update).await
+    }
+}
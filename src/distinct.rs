@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::{stream, Stream, TryStreamExt};
+
+use crate::{DynamoDbStore, Error};
+
+impl DynamoDbStore {
+    /// Every distinct partition key value of `table_name`, in scan order.
+    ///
+    /// Scans with a projection of only the partition key, so each page is small, but
+    /// the whole table is still read. Seen values are kept in memory to drop repeats.
+    pub async fn distinct_partition_keys(
+        &self,
+        table_name: &str,
+    ) -> Result<Vec<AttributeValue>, Error> {
+        self.distinct_partition_keys_stream(table_name)
+            .try_collect()
+            .await
+    }
+
+    /// Streams the distinct partition key values of `table_name` as pages arrive, e.g.
+    /// to fan out work per aggregate without waiting for the whole scan.
+    pub fn distinct_partition_keys_stream<'a>(
+        &'a self,
+        table_name: &'a str,
+    ) -> impl Stream<Item = Result<AttributeValue, Error>> + 'a {
+        stream::once(self.cached_description(table_name))
+            .map_ok(move |description| {
+                let name = description.partition_key.name;
+                let mut seen = HashSet::new();
+
+                self.scan(table_name)
+                    .projection([name.clone()])
+                    .pages()
+                    .map_ok(move |page| {
+                        let values: Vec<_> = page
+                            .items
+                            .into_iter()
+                            .filter_map(|mut item| item.remove(&name))
+                            .filter(|value| seen.insert(format!("{value:?}")))
+                            .map(Ok)
+                            .collect();
+                        stream::iter(values)
+                    })
+                    .try_flatten()
+            })
+            .try_flatten()
+    }
+}
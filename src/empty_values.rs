@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::Error;
+
+/// What to do with one kind of empty value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyValueAction {
+    /// Write the value as serde produced it.
+    #[default]
+    Keep,
+    /// Leave the attribute out of the item. List elements become `NULL` instead, so
+    /// the positions of the other elements do not shift.
+    Strip,
+    /// Write the attribute as `NULL`.
+    Null,
+    /// Fail serialization with a validation error naming the attribute.
+    Reject,
+}
+
+/// How empty strings, empty sets and `None` fields are written.
+///
+/// serde writes `None` as `NULL` and happily produces empty strings and sets, while
+/// DynamoDB rejects empty sets everywhere and empty strings in key attributes. The
+/// default keeps every value as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EmptyValuePolicy {
+    pub empty_strings: EmptyValueAction,
+    pub empty_sets: EmptyValueAction,
+    /// Applies to `NULL` values, which is what serde produces for `None`.
+    pub nulls: EmptyValueAction,
+}
+
+impl EmptyValuePolicy {
+    /// Leaves empty strings, empty sets and nulls out of written items.
+    pub fn strip_all() -> Self {
+        Self {
+            empty_strings: EmptyValueAction::Strip,
+            empty_sets: EmptyValueAction::Strip,
+            nulls: EmptyValueAction::Strip,
+        }
+    }
+
+    pub fn reject_all() -> Self {
+        Self {
+            empty_strings: EmptyValueAction::Reject,
+            empty_sets: EmptyValueAction::Reject,
+            nulls: EmptyValueAction::Reject,
+        }
+    }
+
+    pub fn empty_strings(mut self, action: EmptyValueAction) -> Self {
+        self.empty_strings = action;
+        self
+    }
+
+    pub fn empty_sets(mut self, action: EmptyValueAction) -> Self {
+        self.empty_sets = action;
+        self
+    }
+
+    pub fn nulls(mut self, action: EmptyValueAction) -> Self {
+        self.nulls = action;
+        self
+    }
+
+    /// Applies the policy to `item`, including values nested in maps and lists.
+    pub fn apply(
+        &self,
+        item: HashMap<String, AttributeValue>,
+    ) -> Result<HashMap<String, AttributeValue>, Error> {
+        if *self == Self::default() {
+            return Ok(item);
+        }
+
+        self.apply_to_map(item, "")
+    }
+
+    fn apply_to_map(
+        &self,
+        map: HashMap<String, AttributeValue>,
+        parent: &str,
+    ) -> Result<HashMap<String, AttributeValue>, Error> {
+        let mut sanitized = HashMap::with_capacity(map.len());
+        for (name, value) in map {
+            let path = if parent.is_empty() {
+                name.clone()
+            } else {
+                format!("{parent}.{name}")
+            };
+            if let Some(value) = self.apply_to_value(value, &path)? {
+                sanitized.insert(name, value);
+            }
+        }
+        Ok(sanitized)
+    }
+
+    /// Returns `None` for a value that is stripped.
+    fn apply_to_value(
+        &self,
+        value: AttributeValue,
+        path: &str,
+    ) -> Result<Option<AttributeValue>, Error> {
+        let value = match value {
+            AttributeValue::M(map) => {
+                return Ok(Some(AttributeValue::M(self.apply_to_map(map, path)?)))
+            }
+            AttributeValue::L(list) => return self.apply_to_list(list, path).map(Some),
+            value => value,
+        };
+
+        let action = match &value {
+            AttributeValue::S(value) if value.is_empty() => self.empty_strings,
+            AttributeValue::Ss(values) if values.is_empty() => self.empty_sets,
+            AttributeValue::Ns(values) if values.is_empty() => self.empty_sets,
+            AttributeValue::Bs(values) if values.is_empty() => self.empty_sets,
+            AttributeValue::Null(_) => self.nulls,
+            _ => EmptyValueAction::Keep,
+        };
+
+        match action {
+            EmptyValueAction::Keep => Ok(Some(value)),
+            EmptyValueAction::Strip => Ok(None),
+            EmptyValueAction::Null => Ok(Some(AttributeValue::Null(true))),
+            EmptyValueAction::Reject => Err(Error::Validation(format!(
+                "attribute {path} is empty and the empty value policy rejects it"
+            ))),
+        }
+    }
+
+    fn apply_to_list(
+        &self,
+        list: Vec<AttributeValue>,
+        path: &str,
+    ) -> Result<AttributeValue, Error> {
+        let sanitized = list
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| {
+                Ok(self
+                    .apply_to_value(value, &format!("{path}[{index}]"))?
+                    .unwrap_or(AttributeValue::Null(true)))
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(AttributeValue::L(sanitized))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            ("name".to_string(), AttributeValue::S("Ada".to_string())),
+            ("nickname".to_string(), AttributeValue::S(String::new())),
+            ("tags".to_string(), AttributeValue::Ss(Vec::new())),
+            ("manager".to_string(), AttributeValue::Null(true)),
+            (
+                "address".to_string(),
+                AttributeValue::M(HashMap::from([(
+                    "lines".to_string(),
+                    AttributeValue::L(vec![
+                        AttributeValue::S("Main St".to_string()),
+                        AttributeValue::S(String::new()),
+                    ]),
+                )])),
+            ),
+        ])
+    }
+
+    #[test]
+    fn the_default_keeps_every_value() {
+        assert_eq!(EmptyValuePolicy::default().apply(item()).unwrap(), item());
+    }
+
+    #[test]
+    fn stripped_attributes_are_left_out_and_list_elements_become_null() {
+        let sanitized = EmptyValuePolicy::strip_all().apply(item()).unwrap();
+
+        let mut names: Vec<&String> = sanitized.keys().collect();
+        names.sort();
+        assert_eq!(names, ["address", "name"]);
+        assert_eq!(
+            sanitized["address"],
+            AttributeValue::M(HashMap::from([(
+                "lines".to_string(),
+                AttributeValue::L(vec![
+                    AttributeValue::S("Main St".to_string()),
+                    AttributeValue::Null(true),
+                ]),
+            )]))
+        );
+    }
+
+    #[test]
+    fn each_kind_of_empty_value_has_its_own_action() {
+        let policy = EmptyValuePolicy::default()
+            .empty_strings(EmptyValueAction::Null)
+            .empty_sets(EmptyValueAction::Strip);
+
+        let sanitized = policy.apply(item()).unwrap();
+
+        assert_eq!(sanitized["nickname"], AttributeValue::Null(true));
+        assert!(!sanitized.contains_key("tags"));
+        assert_eq!(sanitized["manager"], AttributeValue::Null(true));
+    }
+
+    #[test]
+    fn rejected_values_are_named_by_path() {
+        let policy = EmptyValuePolicy::default().empty_strings(EmptyValueAction::Reject);
+        let item = HashMap::from([(
+            "address".to_string(),
+            AttributeValue::M(HashMap::from([(
+                "lines".to_string(),
+                AttributeValue::L(vec![AttributeValue::S(String::new())]),
+            )])),
+        )]);
+
+        let Err(Error::Validation(message)) = policy.apply(item) else {
+            panic!("expected a validation error");
+        };
+        assert!(message.starts_with("attribute address.lines[0] is empty"));
+    }
+}
@@ -0,0 +1,533 @@
+use std::{collections::HashMap, sync::Arc};
+
+use aws_sdk_dynamodb::{primitives::Blob, types::AttributeValue};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    hmac,
+};
+
+use crate::{
+    api::{DynamoStoreApi, Item, QueryRequest, StoreFuture},
+    batch::{BatchGetResult, BatchWriteResult},
+    convert::write_canonical_attributes,
+    filter::Filter,
+    naming::NamingConvention,
+    page::Page,
+    Error,
+};
+
+pub const DEFAULT_ENVELOPE_ATTRIBUTE: &str = "__envelope";
+
+const DATA_KEY_LEN: usize = 32;
+const ENVELOPE_KEY: &str = "key";
+const ENVELOPE_ATTRIBUTES: &str = "attributes";
+const ENVELOPE_SIGNATURE: &str = "signature";
+
+/// A data key: the plaintext used to encrypt one item and the wrapped form stored in
+/// the item's envelope.
+pub struct DataKey {
+    pub plaintext: Vec<u8>,
+    pub wrapped: Vec<u8>,
+}
+
+/// Produces and unwraps per-item data keys.
+pub trait Keyring: Send + Sync {
+    fn generate_data_key(&self) -> StoreFuture<'_, DataKey>;
+
+    fn unwrap_data_key<'a>(&'a self, wrapped: &'a [u8]) -> StoreFuture<'a, Vec<u8>>;
+}
+
+/// Wraps data keys with a locally held 256-bit AES key.
+pub struct LocalKeyring {
+    key: LessSafeKey,
+}
+
+impl LocalKeyring {
+    pub fn new(master_key: [u8; 32]) -> Self {
+        let key = UnboundKey::new(&AES_256_GCM, &master_key).expect("AES-256 keys are 32 bytes");
+        Self {
+            key: LessSafeKey::new(key),
+        }
+    }
+}
+
+impl Keyring for LocalKeyring {
+    fn generate_data_key(&self) -> StoreFuture<'_, DataKey> {
+        Box::pin(async move {
+            let plaintext = random_bytes(DATA_KEY_LEN)?;
+            let wrapped = seal(&self.key, b"data-key", &plaintext)?;
+            Ok(DataKey { plaintext, wrapped })
+        })
+    }
+
+    fn unwrap_data_key<'a>(&'a self, wrapped: &'a [u8]) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(async move { open(&self.key, b"data-key", wrapped) })
+    }
+}
+
+/// Generates and decrypts data keys with an AWS KMS key.
+#[cfg(feature = "kms")]
+pub struct KmsKeyring {
+    client: aws_sdk_kms::Client,
+    key_id: String,
+}
+
+#[cfg(feature = "kms")]
+impl KmsKeyring {
+    pub fn new(client: aws_sdk_kms::Client, key_id: impl Into<String>) -> Self {
+        Self {
+            client,
+            key_id: key_id.into(),
+        }
+    }
+}
+
+#[cfg(feature = "kms")]
+impl Keyring for KmsKeyring {
+    fn generate_data_key(&self) -> StoreFuture<'_, DataKey> {
+        Box::pin(async move {
+            let output = self
+                .client
+                .generate_data_key()
+                .key_id(&self.key_id)
+                .key_spec(aws_sdk_kms::types::DataKeySpec::Aes256)
+                .send()
+                .await
+                .map_err(aws_sdk_kms::Error::from)?;
+
+            match (output.plaintext, output.ciphertext_blob) {
+                (Some(plaintext), Some(wrapped)) => Ok(DataKey {
+                    plaintext: plaintext.into_inner(),
+                    wrapped: wrapped.into_inner(),
+                }),
+                _ => Err(Error::UnexpectedResponse {
+                    operation: "GenerateDataKey",
+                    reason: "response contains no data key".to_string(),
+                }),
+            }
+        })
+    }
+
+    fn unwrap_data_key<'a>(&'a self, wrapped: &'a [u8]) -> StoreFuture<'a, Vec<u8>> {
+        Box::pin(async move {
+            let output = self
+                .client
+                .decrypt()
+                .key_id(&self.key_id)
+                .ciphertext_blob(aws_sdk_kms::primitives::Blob::new(wrapped))
+                .send()
+                .await
+                .map_err(aws_sdk_kms::Error::from)?;
+
+            output
+                .plaintext
+                .map(|plaintext| plaintext.into_inner())
+                .ok_or_else(|| Error::UnexpectedResponse {
+                    operation: "Decrypt",
+                    reason: "response contains no plaintext".to_string(),
+                })
+        })
+    }
+}
+
+/// Encrypts selected attributes of an item and signs the whole item.
+///
+/// Each item gets its own data key. The wrapped key, the names of the encrypted
+/// attributes and an HMAC over all attributes are stored in an envelope attribute,
+/// so tampering with any attribute is detected on decryption. Key attributes and
+/// attributes used in conditions or filters must not be encrypted.
+#[derive(Clone)]
+pub struct ItemEncryptor {
+    keyring: Arc<dyn Keyring>,
+    attributes: Vec<String>,
+    envelope_attribute: String,
+}
+
+impl ItemEncryptor {
+    pub fn new(keyring: impl Keyring + 'static) -> Self {
+        Self {
+            keyring: Arc::new(keyring),
+            attributes: Vec::new(),
+            envelope_attribute: DEFAULT_ENVELOPE_ATTRIBUTE.to_string(),
+        }
+    }
+
+    pub fn encrypt_attributes(
+        mut self,
+        attributes: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.attributes = attributes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_envelope_attribute(mut self, attribute_name: impl Into<String>) -> Self {
+        self.envelope_attribute = attribute_name.into();
+        self
+    }
+
+    pub async fn encrypt(&self, mut item: Item) -> Result<Item, Error> {
+        let data_key = self.keyring.generate_data_key().await?;
+        let (encryption_key, signing_key) = derive_keys(&data_key.plaintext)?;
+
+        let mut encrypted = Vec::new();
+        for name in &self.attributes {
+            if let Some(value) = item.remove(name) {
+                let plaintext = serde_json::to_vec(&serde_dynamo::AttributeValue::from(value))?;
+                let ciphertext = seal(&encryption_key, name.as_bytes(), &plaintext)?;
+                item.insert(name.clone(), AttributeValue::B(Blob::new(ciphertext)));
+                encrypted.push(name.clone());
+            }
+        }
+        encrypted.sort();
+
+        let mut envelope = HashMap::from([
+            (
+                ENVELOPE_KEY.to_string(),
+                AttributeValue::B(Blob::new(data_key.wrapped)),
+            ),
+            (
+                ENVELOPE_ATTRIBUTES.to_string(),
+                AttributeValue::L(encrypted.into_iter().map(AttributeValue::S).collect()),
+            ),
+        ]);
+        let signature = hmac::sign(&signing_key, &canonical_bytes(&item, &envelope));
+        envelope.insert(
+            ENVELOPE_SIGNATURE.to_string(),
+            AttributeValue::B(Blob::new(signature.as_ref())),
+        );
+
+        item.insert(self.envelope_attribute.clone(), AttributeValue::M(envelope));
+        Ok(item)
+    }
+
+    pub async fn decrypt(&self, mut item: Item) -> Result<Item, Error> {
+        let Some(AttributeValue::M(mut envelope)) = item.remove(&self.envelope_attribute) else {
+            return Err(Error::Encryption(
+                "item has no encryption envelope".to_string(),
+            ));
+        };
+
+        let signature = match envelope.remove(ENVELOPE_SIGNATURE) {
+            Some(AttributeValue::B(signature)) => signature.into_inner(),
+            _ => return Err(malformed_envelope()),
+        };
+        let wrapped = match envelope.get(ENVELOPE_KEY) {
+            Some(AttributeValue::B(wrapped)) => wrapped.as_ref().to_vec(),
+            _ => return Err(malformed_envelope()),
+        };
+        let encrypted: Vec<String> = match envelope.get(ENVELOPE_ATTRIBUTES) {
+            Some(AttributeValue::L(names)) => names
+                .iter()
+                .map(|name| name.as_s().cloned().map_err(|_| malformed_envelope()))
+                .collect::<Result<_, _>>()?,
+            _ => return Err(malformed_envelope()),
+        };
+
+        let plaintext_key = self.keyring.unwrap_data_key(&wrapped).await?;
+        let (encryption_key, signing_key) = derive_keys(&plaintext_key)?;
+        hmac::verify(&signing_key, &canonical_bytes(&item, &envelope), &signature)
+            .map_err(|_| Error::Encryption("item signature does not match".to_string()))?;
+
+        for name in encrypted {
+            let Some(AttributeValue::B(ciphertext)) = item.remove(&name) else {
+                return Err(malformed_envelope());
+            };
+            let plaintext = open(&encryption_key, name.as_bytes(), ciphertext.as_ref())?;
+            let value: serde_dynamo::AttributeValue = serde_json::from_slice(&plaintext)?;
+            item.insert(name, value.into());
+        }
+
+        Ok(item)
+    }
+}
+
+/// Applies an [`ItemEncryptor`] per table around another [`DynamoStoreApi`]; tables
+/// without an encryptor pass through unchanged.
+pub struct EncryptedStore<S> {
+    inner: S,
+    tables: HashMap<String, ItemEncryptor>,
+}
+
+impl<S: DynamoStoreApi> EncryptedStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            tables: HashMap::new(),
+        }
+    }
+
+    pub fn table(mut self, table_name: impl Into<String>, encryptor: ItemEncryptor) -> Self {
+        self.tables.insert(table_name.into(), encryptor);
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    async fn encrypt(&self, table_name: &str, item: Item) -> Result<Item, Error> {
+        match self.tables.get(table_name) {
+            Some(encryptor) => encryptor.encrypt(item).await,
+            None => Ok(item),
+        }
+    }
+
+    async fn decrypt(&self, table_name: &str, items: Vec<Item>) -> Result<Vec<Item>, Error> {
+        let Some(encryptor) = self.tables.get(table_name) else {
+            return Ok(items);
+        };
+
+        let mut decrypted = Vec::with_capacity(items.len());
+        for item in items {
+            decrypted.push(encryptor.decrypt(item).await?);
+        }
+        Ok(decrypted)
+    }
+}
+
+impl<S: DynamoStoreApi> DynamoStoreApi for EncryptedStore<S> {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let item = self.encrypt(table_name, item).await?;
+            self.inner.put_item(table_name, item, condition).await
+        })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        Box::pin(async move {
+            match self.inner.get_item(table_name, key).await? {
+                Some(item) => Ok(self.decrypt(table_name, vec![item]).await?.pop()),
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        self.inner.delete_item(table_name, key, condition)
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        Box::pin(async move {
+            let mut page = self.inner.query_page(request).await?;
+            page.items = self.decrypt(&request.table_name, page.items).await?;
+            Ok(page)
+        })
+    }
+
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        self.inner.naming()
+    }
+
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            let mut encrypted = Vec::with_capacity(items.len());
+            for item in items {
+                encrypted.push(self.encrypt(table_name, item).await?);
+            }
+            self.inner.batch_put_items(table_name, encrypted).await
+        })
+    }
+
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        self.inner.batch_delete_items(table_name, keys)
+    }
+
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        Box::pin(async move {
+            let mut result = self.inner.batch_get_items(table_name, keys).await?;
+            result.items = self.decrypt(table_name, result.items).await?;
+            Ok(result)
+        })
+    }
+}
+
+/// Derives independent encryption and signing keys from one data key.
+fn derive_keys(data_key: &[u8]) -> Result<(LessSafeKey, hmac::Key), Error> {
+    let root = hmac::Key::new(hmac::HMAC_SHA256, data_key);
+    let encryption = hmac::sign(&root, b"encryption");
+    let signing = hmac::sign(&root, b"signing");
+
+    let encryption = UnboundKey::new(&AES_256_GCM, encryption.as_ref())
+        .map_err(|_| Error::Encryption("invalid data key".to_string()))?;
+    Ok((
+        LessSafeKey::new(encryption),
+        hmac::Key::new(hmac::HMAC_SHA256, signing.as_ref()),
+    ))
+}
+
+/// The item and envelope in a canonical form: attributes in name order, numbers
+/// normalized and sets sorted, so the same item always signs the same.
+fn canonical_bytes(item: &Item, envelope: &Item) -> Vec<u8> {
+    let mut canonical = String::new();
+    write_canonical_attributes(item, &mut canonical);
+    write_canonical_attributes(envelope, &mut canonical);
+    canonical.into_bytes()
+}
+
+/// Encrypts `plaintext` bound to `context`, returning the nonce followed by the
+/// ciphertext and tag.
+fn seal(key: &LessSafeKey, context: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+    let nonce_bytes: [u8; NONCE_LEN] = random_bytes(NONCE_LEN)?
+        .try_into()
+        .expect("random_bytes returns the requested length");
+
+    let mut in_out = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce_bytes),
+        Aad::from(context),
+        &mut in_out,
+    )
+    .map_err(|_| Error::Encryption("encryption failed".to_string()))?;
+
+    let mut sealed = nonce_bytes.to_vec();
+    sealed.extend(in_out);
+    Ok(sealed)
+}
+
+fn open(key: &LessSafeKey, context: &[u8], sealed: &[u8]) -> Result<Vec<u8>, Error> {
+    if sealed.len() < NONCE_LEN {
+        return Err(Error::Encryption("ciphertext is too short".to_string()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| Error::Encryption("invalid nonce".to_string()))?;
+
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(context), &mut in_out)
+        .map_err(|_| Error::Encryption("decryption failed".to_string()))?;
+    Ok(plaintext.to_vec())
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|err| Error::Encryption(format!("random number generator failed: {err}")))?;
+    Ok(bytes)
+}
+
+fn malformed_envelope() -> Error {
+    Error::Encryption("malformed encryption envelope".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encryptor() -> ItemEncryptor {
+        ItemEncryptor::new(LocalKeyring::new([7; 32])).encrypt_attributes(["secret", "tags"])
+    }
+
+    fn item() -> Item {
+        HashMap::from([
+            ("pk".to_string(), AttributeValue::S("user#1".to_string())),
+            ("amount".to_string(), AttributeValue::N("1.50".to_string())),
+            (
+                "scores".to_string(),
+                AttributeValue::Ns(vec!["3".to_string(), "1".to_string(), "2".to_string()]),
+            ),
+            (
+                "profile".to_string(),
+                AttributeValue::M(HashMap::from([
+                    ("name".to_string(), AttributeValue::S("Ada".to_string())),
+                    ("age".to_string(), AttributeValue::N("36".to_string())),
+                    ("active".to_string(), AttributeValue::Bool(true)),
+                ])),
+            ),
+            (
+                "secret".to_string(),
+                AttributeValue::S("hunter2".to_string()),
+            ),
+            (
+                "tags".to_string(),
+                AttributeValue::Ss(vec!["b".to_string(), "a".to_string()]),
+            ),
+        ])
+    }
+
+    #[tokio::test]
+    async fn encrypted_items_decrypt_to_the_original() {
+        let encryptor = encryptor();
+
+        let encrypted = encryptor.encrypt(item()).await.unwrap();
+        assert!(matches!(encrypted["secret"], AttributeValue::B(_)));
+        assert!(matches!(encrypted["tags"], AttributeValue::B(_)));
+        assert_eq!(encrypted["pk"], item()["pk"]);
+
+        assert_eq!(encryptor.decrypt(encrypted).await.unwrap(), item());
+    }
+
+    #[tokio::test]
+    async fn signatures_survive_number_and_set_normalization() {
+        let encryptor = encryptor();
+        let mut encrypted = encryptor.encrypt(item()).await.unwrap();
+
+        // DynamoDB returns numbers normalized and sets in its own order.
+        encrypted.insert("amount".to_string(), AttributeValue::N("1.5".to_string()));
+        encrypted.insert(
+            "scores".to_string(),
+            AttributeValue::Ns(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+        );
+
+        let decrypted = encryptor.decrypt(encrypted).await.unwrap();
+        assert_eq!(decrypted["amount"], AttributeValue::N("1.5".to_string()));
+        assert_eq!(decrypted["secret"], item()["secret"]);
+    }
+
+    #[tokio::test]
+    async fn tampered_attributes_fail_verification() {
+        let encryptor = encryptor();
+        let encrypted = encryptor.encrypt(item()).await.unwrap();
+
+        let mut changed = encrypted.clone();
+        changed.insert("amount".to_string(), AttributeValue::N("2".to_string()));
+        assert!(matches!(
+            encryptor.decrypt(changed).await,
+            Err(Error::Encryption(_))
+        ));
+
+        let mut added = encrypted.clone();
+        added.insert("extra".to_string(), AttributeValue::Bool(false));
+        assert!(matches!(
+            encryptor.decrypt(added).await,
+            Err(Error::Encryption(_))
+        ));
+
+        let mut removed = encrypted;
+        removed.remove("profile");
+        assert!(matches!(
+            encryptor.decrypt(removed).await,
+            Err(Error::Encryption(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn items_without_an_envelope_are_rejected() {
+        assert!(matches!(
+            encryptor().decrypt(item()).await,
+            Err(Error::Encryption(_))
+        ));
+    }
+}
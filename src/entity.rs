@@ -0,0 +1,26 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{DynamoDbStore, Error};
+
+/// A struct bound to a table, with a key type that serializes to the table's key
+/// attributes.
+pub trait DynamoEntity: Serialize + DeserializeOwned {
+    const TABLE: &'static str;
+    type Key: Serialize;
+
+    fn key(&self) -> Self::Key;
+}
+
+impl DynamoDbStore {
+    pub async fn save<T: DynamoEntity>(&self, entity: &T) -> Result<(), Error> {
+        self.for_table(T::TABLE).put(entity).await
+    }
+
+    pub async fn load<T: DynamoEntity>(&self, key: &T::Key) -> Result<Option<T>, Error> {
+        self.for_table(T::TABLE).get(key).await
+    }
+
+    pub async fn remove<T: DynamoEntity>(&self, entity: &T) -> Result<(), Error> {
+        self.for_table(T::TABLE).delete(&entity.key()).await
+    }
+}
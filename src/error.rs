@@ -0,0 +1,271 @@
+use std::{fmt, time::Duration};
+
+use aws_sdk_dynamodb::error::{BuildError, ProvideErrorMetadata, SdkError};
+
+#[derive(Debug)]
+pub enum Error {
+    DynamoDb(Box<aws_sdk_dynamodb::Error>),
+    #[cfg(feature = "streams")]
+    DynamoDbStreams(Box<aws_sdk_dynamodbstreams::Error>),
+    Timeout {
+        waiting_for: String,
+        waited: Duration,
+    },
+    UnexpectedResponse {
+        operation: &'static str,
+        reason: String,
+    },
+    Serialization(serde_dynamo::Error),
+    Json(serde_json::Error),
+    Io(std::io::Error),
+    #[cfg(feature = "csv")]
+    Csv(csv::Error),
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+    #[cfg(feature = "test-harness")]
+    Container(testcontainers_modules::testcontainers::TestcontainersError),
+    #[cfg(feature = "kms")]
+    Kms(Box<aws_sdk_kms::Error>),
+    #[cfg(feature = "encryption")]
+    Encryption(String),
+    Validation(String),
+    SchemaMismatch {
+        table_name: String,
+        problems: Vec<String>,
+    },
+    VersionConflict {
+        table_name: String,
+        expected: u64,
+    },
+    IdempotencyInProgress {
+        key: String,
+    },
+}
+
+impl Error {
+    fn dynamodb(&self) -> Option<&aws_sdk_dynamodb::Error> {
+        match self {
+            Self::DynamoDb(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    pub fn is_resource_not_found(&self) -> bool {
+        matches!(
+            self.dynamodb(),
+            Some(aws_sdk_dynamodb::Error::ResourceNotFoundException(_))
+        )
+    }
+
+    pub fn is_resource_in_use(&self) -> bool {
+        matches!(
+            self.dynamodb(),
+            Some(aws_sdk_dynamodb::Error::ResourceInUseException(_))
+        )
+    }
+
+    pub fn is_conditional_check_failed(&self) -> bool {
+        matches!(
+            self.dynamodb(),
+            Some(aws_sdk_dynamodb::Error::ConditionalCheckFailedException(_))
+        )
+    }
+
+    pub fn is_transaction_canceled(&self) -> bool {
+        matches!(
+            self.dynamodb(),
+            Some(aws_sdk_dynamodb::Error::TransactionCanceledException(_))
+        )
+    }
+
+    /// The reason code of each item of a canceled transaction, in request order, e.g.
+    /// `ConditionalCheckFailed`, or `None` for an item that did not cause the cancellation.
+    pub fn cancellation_codes(&self) -> Vec<Option<&str>> {
+        match self.dynamodb() {
+            Some(aws_sdk_dynamodb::Error::TransactionCanceledException(err)) => err
+                .cancellation_reasons()
+                .iter()
+                .map(|reason| reason.code().filter(|code| *code != "None"))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The caller lacks the IAM permission for the request.
+    pub fn is_access_denied(&self) -> bool {
+        self.dynamodb().and_then(ProvideErrorMetadata::code) == Some("AccessDeniedException")
+    }
+
+    #[cfg(feature = "streams")]
+    fn dynamodb_streams(&self) -> Option<&aws_sdk_dynamodbstreams::Error> {
+        match self {
+            Self::DynamoDbStreams(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// The requested stream records are past the 24 hour retention and were removed.
+    #[cfg(feature = "streams")]
+    pub fn is_trimmed_data_access(&self) -> bool {
+        matches!(
+            self.dynamodb_streams(),
+            Some(aws_sdk_dynamodbstreams::Error::TrimmedDataAccessException(
+                _
+            ))
+        )
+    }
+
+    /// A shard iterator was used more than 15 minutes after it was issued.
+    #[cfg(feature = "streams")]
+    pub fn is_expired_iterator(&self) -> bool {
+        matches!(
+            self.dynamodb_streams(),
+            Some(aws_sdk_dynamodbstreams::Error::ExpiredIteratorException(_))
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DynamoDb(err) => write!(f, "DynamoDB request failed: {err}"),
+            #[cfg(feature = "streams")]
+            Self::DynamoDbStreams(err) => write!(f, "DynamoDB Streams request failed: {err}"),
+            Self::Timeout {
+                waiting_for,
+                waited,
+            } => write!(f, "timed out after {waited:?} waiting for {waiting_for}"),
+            Self::UnexpectedResponse { operation, reason } => {
+                write!(f, "unexpected {operation} response: {reason}")
+            }
+            Self::Serialization(err) => write!(f, "item (de)serialization failed: {err}"),
+            Self::Json(err) => write!(f, "JSON (de)serialization failed: {err}"),
+            Self::Io(err) => write!(f, "I/O failed: {err}"),
+            #[cfg(feature = "csv")]
+            Self::Csv(err) => write!(f, "CSV processing failed: {err}"),
+            #[cfg(feature = "yaml")]
+            Self::Yaml(err) => write!(f, "YAML deserialization failed: {err}"),
+            #[cfg(feature = "test-harness")]
+            Self::Container(err) => write!(f, "test container failed: {err}"),
+            #[cfg(feature = "kms")]
+            Self::Kms(err) => write!(f, "KMS request failed: {err}"),
+            #[cfg(feature = "encryption")]
+            Self::Encryption(reason) => write!(f, "item encryption failed: {reason}"),
+            Self::Validation(reason) => write!(f, "validation failed: {reason}"),
+            Self::SchemaMismatch {
+                table_name,
+                problems,
+            } => write!(
+                f,
+                "item does not match the key schema of table {table_name}: {}",
+                problems.join("; ")
+            ),
+            Self::VersionConflict {
+                table_name,
+                expected,
+            } => write!(
+                f,
+                "item in table {table_name} is no longer at version {expected}"
+            ),
+            Self::IdempotencyInProgress { key } => {
+                write!(f, "idempotency key {key} is still being processed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DynamoDb(err) => Some(err.as_ref()),
+            #[cfg(feature = "streams")]
+            Self::DynamoDbStreams(err) => Some(err.as_ref()),
+            Self::Serialization(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::Io(err) => Some(err),
+            #[cfg(feature = "csv")]
+            Self::Csv(err) => Some(err),
+            #[cfg(feature = "yaml")]
+            Self::Yaml(err) => Some(err),
+            #[cfg(feature = "test-harness")]
+            Self::Container(err) => Some(err),
+            #[cfg(feature = "kms")]
+            Self::Kms(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<aws_sdk_dynamodb::Error> for Error {
+    fn from(err: aws_sdk_dynamodb::Error) -> Self {
+        Self::DynamoDb(Box::new(err))
+    }
+}
+
+#[cfg(feature = "streams")]
+impl From<aws_sdk_dynamodbstreams::Error> for Error {
+    fn from(err: aws_sdk_dynamodbstreams::Error) -> Self {
+        Self::DynamoDbStreams(Box::new(err))
+    }
+}
+
+impl From<serde_dynamo::Error> for Error {
+    fn from(err: serde_dynamo::Error) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[cfg(feature = "csv")]
+impl From<csv::Error> for Error {
+    fn from(err: csv::Error) -> Self {
+        Self::Csv(err)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Error> for Error {
+    fn from(err: serde_yaml::Error) -> Self {
+        Self::Yaml(err)
+    }
+}
+
+#[cfg(feature = "test-harness")]
+impl From<testcontainers_modules::testcontainers::TestcontainersError> for Error {
+    fn from(err: testcontainers_modules::testcontainers::TestcontainersError) -> Self {
+        Self::Container(err)
+    }
+}
+
+#[cfg(feature = "kms")]
+impl From<aws_sdk_kms::Error> for Error {
+    fn from(err: aws_sdk_kms::Error) -> Self {
+        Self::Kms(Box::new(err))
+    }
+}
+
+impl From<BuildError> for Error {
+    fn from(err: BuildError) -> Self {
+        Self::DynamoDb(Box::new(err.into()))
+    }
+}
+
+impl<E, R> From<SdkError<E, R>> for Error
+where
+    aws_sdk_dynamodb::Error: From<SdkError<E, R>>,
+{
+    fn from(err: SdkError<E, R>) -> Self {
+        Self::DynamoDb(Box::new(err.into()))
+    }
+}
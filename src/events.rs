@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    expression::CheckedExpressions, instrument::Operation, limits::validate_item,
+    query::SortKeyCondition, DynamoDbStore, Error,
+};
+
+/// Partition key of event tables: one partition per aggregate.
+pub const AGGREGATE_ATTRIBUTE: &str = "aggregate_id";
+/// Numeric sort key of event tables, starting at 1 for the first event of an aggregate.
+pub const SEQUENCE_ATTRIBUTE: &str = "sequence";
+const DATA_ATTRIBUTE: &str = "data";
+const RECORDED_AT_ATTRIBUTE: &str = "recorded_at";
+
+/// An event read back from an aggregate's stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredEvent<T> {
+    pub sequence: u64,
+    /// Milliseconds since the epoch when the event was appended.
+    pub recorded_at: u64,
+    pub data: T,
+}
+
+impl DynamoDbStore {
+    /// Appends `event` to the stream of `aggregate_id` as sequence `expected_sequence + 1`
+    /// and returns that sequence. Pass `0` for the first event of a new aggregate.
+    ///
+    /// Fails with [`Error::VersionConflict`] if another writer appended that sequence
+    /// first; reload the stream and retry the command.
+    ///
+    /// The table needs [`AGGREGATE_ATTRIBUTE`] (`S`) as partition key and
+    /// [`SEQUENCE_ATTRIBUTE`] (`N`) as sort key.
+    pub async fn append_event<T: Serialize>(
+        &self,
+        table_name: &str,
+        aggregate_id: &str,
+        expected_sequence: u64,
+        event: &T,
+    ) -> Result<u64, Error> {
+        let sequence = expected_sequence + 1;
+        let recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let item = HashMap::from([
+            (
+                AGGREGATE_ATTRIBUTE.to_string(),
+                AttributeValue::S(aggregate_id.to_string()),
+            ),
+            (
+                SEQUENCE_ATTRIBUTE.to_string(),
+                AttributeValue::N(sequence.to_string()),
+            ),
+            (
+                DATA_ATTRIBUTE.to_string(),
+                serde_dynamo::to_attribute_value(event)?,
+            ),
+            (
+                RECORDED_AT_ATTRIBUTE.to_string(),
+                AttributeValue::N(recorded_at.to_string()),
+            ),
+        ]);
+        validate_item(&item)?;
+
+        let operation = Operation::new("PutItem", table_name).attributes(&item);
+        let request = self
+            .client()
+            .put_item()
+            .table_name(table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(#sequence)")
+            .expression_attribute_names("#sequence", SEQUENCE_ATTRIBUTE)
+            .checked()?
+            .send();
+
+        match operation.send(request).await.map_err(Error::from) {
+            Ok(_) => Ok(sequence),
+            Err(err) if err.is_conditional_check_failed() => Err(Error::VersionConflict {
+                table_name: table_name.to_string(),
+                expected: expected_sequence,
+            }),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Reads the events of `aggregate_id` from sequence `from_sequence` on, in order.
+    /// Pass `1` to replay the whole stream, or one past a snapshot's sequence.
+    pub async fn read_stream<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        aggregate_id: &str,
+        from_sequence: u64,
+    ) -> Result<Vec<StoredEvent<T>>, Error> {
+        let items = self
+            .query(table_name)
+            .partition_key(
+                AGGREGATE_ATTRIBUTE,
+                AttributeValue::S(aggregate_id.to_string()),
+            )
+            .sort_key(
+                SEQUENCE_ATTRIBUTE,
+                SortKeyCondition::Ge(AttributeValue::N(from_sequence.to_string())),
+            )
+            .consistent_read(true)
+            .all()
+            .await?;
+
+        items
+            .into_iter()
+            .map(|mut item| {
+                let sequence =
+                    number(&item, SEQUENCE_ATTRIBUTE).ok_or_else(|| Error::UnexpectedResponse {
+                        operation: "Query",
+                        reason: format!("event of {aggregate_id} has no sequence number"),
+                    })?;
+                let recorded_at = number(&item, RECORDED_AT_ATTRIBUTE).unwrap_or_default();
+                let data = item
+                    .remove(DATA_ATTRIBUTE)
+                    .unwrap_or(AttributeValue::Null(true));
+
+                Ok(StoredEvent {
+                    sequence,
+                    recorded_at,
+                    data: serde_dynamo::from_attribute_value(data)?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn number(item: &HashMap<String, AttributeValue>, name: &str) -> Option<u64> {
+    match item.get(name) {
+        Some(AttributeValue::N(value)) => value.parse().ok(),
+        _ => None,
+    }
+}
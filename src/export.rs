@@ -0,0 +1,135 @@
+use std::time::{Duration, SystemTime};
+
+use aws_sdk_dynamodb::{primitives::DateTime, types::ExportDescription as SdkExportDescription};
+
+use crate::{instrument::Operation, wait::poll_until, DynamoDbStore, Error};
+
+pub use aws_sdk_dynamodb::types::{ExportFormat, ExportStatus};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportParams {
+    pub table_arn: String,
+    pub s3_bucket: String,
+    pub s3_prefix: Option<String>,
+    pub s3_bucket_owner: Option<String>,
+    pub format: ExportFormat,
+    pub export_time: Option<SystemTime>,
+}
+
+impl ExportParams {
+    pub fn new(table_arn: impl Into<String>, s3_bucket: impl Into<String>) -> Self {
+        Self {
+            table_arn: table_arn.into(),
+            s3_bucket: s3_bucket.into(),
+            s3_prefix: None,
+            s3_bucket_owner: None,
+            format: ExportFormat::DynamodbJson,
+            export_time: None,
+        }
+    }
+
+    pub fn s3_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.s3_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn s3_bucket_owner(mut self, account_id: impl Into<String>) -> Self {
+        self.s3_bucket_owner = Some(account_id.into());
+        self
+    }
+
+    pub fn format(mut self, format: ExportFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Exports the table as it was at `time`; defaults to the current time.
+    pub fn export_time(mut self, time: SystemTime) -> Self {
+        self.export_time = Some(time);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportDescription {
+    pub arn: String,
+    pub status: Option<ExportStatus>,
+    pub manifest: Option<String>,
+    pub item_count: Option<i64>,
+    pub failure_message: Option<String>,
+}
+
+impl From<SdkExportDescription> for ExportDescription {
+    fn from(description: SdkExportDescription) -> Self {
+        Self {
+            arn: description.export_arn().unwrap_or_default().to_string(),
+            status: description.export_status().cloned(),
+            manifest: description.export_manifest().map(str::to_string),
+            item_count: description.item_count(),
+            failure_message: description.failure_message().map(str::to_string),
+        }
+    }
+}
+
+impl DynamoDbStore {
+    /// Starts a point-in-time export to S3; the table needs PITR enabled.
+    pub async fn export_to_s3(&self, params: ExportParams) -> Result<ExportDescription, Error> {
+        let operation = Operation::new("ExportTableToPointInTime", &params.table_arn);
+        let request = self
+            .client()
+            .export_table_to_point_in_time()
+            .table_arn(&params.table_arn)
+            .s3_bucket(params.s3_bucket)
+            .set_s3_prefix(params.s3_prefix)
+            .set_s3_bucket_owner(params.s3_bucket_owner)
+            .export_format(params.format)
+            .set_export_time(params.export_time.map(DateTime::from))
+            .send();
+        let output = operation.send(request).await?;
+
+        description(output.export_description, "ExportTableToPointInTime")
+    }
+
+    pub async fn describe_export(&self, export_arn: &str) -> Result<ExportDescription, Error> {
+        let operation = Operation::new("DescribeExport", export_arn);
+        let request = self
+            .client()
+            .describe_export()
+            .export_arn(export_arn)
+            .send();
+        let output = operation.send(request).await?;
+
+        description(output.export_description, "DescribeExport")
+    }
+
+    /// Waits until the export is no longer `IN_PROGRESS` and returns its final description.
+    pub async fn wait_for_export(
+        &self,
+        export_arn: &str,
+        timeout: Duration,
+    ) -> Result<ExportDescription, Error> {
+        poll_until(
+            format!("export {export_arn} to finish"),
+            timeout,
+            || async {
+                let description = self.describe_export(export_arn).await?;
+                Ok(description.status != Some(ExportStatus::InProgress))
+            },
+        )
+        .await?;
+
+        self.describe_export(export_arn).await
+    }
+}
+
+fn description(
+    description: Option<SdkExportDescription>,
+    operation: &'static str,
+) -> Result<ExportDescription, Error> {
+    description
+        .map(ExportDescription::from)
+        .ok_or_else(|| Error::UnexpectedResponse {
+            operation,
+            reason: "response contained no export description".to_string(),
+        })
+}
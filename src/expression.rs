@@ -0,0 +1,179 @@
+use std::collections::{BTreeSet, HashMap};
+
+use aws_sdk_dynamodb::{
+    operation::{
+        delete_item::builders::DeleteItemFluentBuilder, put_item::builders::PutItemFluentBuilder,
+        query::builders::QueryFluentBuilder, scan::builders::ScanFluentBuilder,
+        update_item::builders::UpdateItemFluentBuilder,
+    },
+    types::AttributeValue,
+};
+
+use crate::Error;
+
+/// Checks that every `#name` and `:value` placeholder used in `expressions` has an entry
+/// in `names` or `values`, and that every entry is used by some expression.
+///
+/// DynamoDB rejects both mistakes with a generic validation error; this names the
+/// offending placeholders before the request is sent.
+pub fn validate_placeholders(
+    expressions: &[&str],
+    names: &HashMap<String, String>,
+    values: &HashMap<String, AttributeValue>,
+) -> Result<(), Error> {
+    let used: BTreeSet<&str> = expressions
+        .iter()
+        .flat_map(|expression| placeholders(expression))
+        .collect();
+
+    let mut problems = Vec::new();
+    let missing: Vec<&str> = used
+        .iter()
+        .copied()
+        .filter(|placeholder| match placeholder.as_bytes()[0] {
+            b'#' => !names.contains_key(*placeholder),
+            _ => !values.contains_key(*placeholder),
+        })
+        .collect();
+    if !missing.is_empty() {
+        problems.push(format!("{} not defined", missing.join(", ")));
+    }
+
+    let mut unused: Vec<&str> = names
+        .keys()
+        .chain(values.keys())
+        .map(String::as_str)
+        .filter(|placeholder| !used.contains(placeholder))
+        .collect();
+    unused.sort_unstable();
+    if !unused.is_empty() {
+        problems.push(format!("{} not used", unused.join(", ")));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Validation(format!(
+            "expression placeholders do not match: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+/// `#name` and `:value` tokens in `expression`.
+fn placeholders(expression: &str) -> impl Iterator<Item = &str> {
+    expression
+        .char_indices()
+        .filter(|(_, c)| matches!(c, '#' | ':'))
+        .map(move |(start, _)| {
+            let length = expression[start + 1..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(expression.len() - start - 1);
+            &expression[start..start + 1 + length]
+        })
+        .filter(|placeholder| placeholder.len() > 1)
+}
+
+/// Validates the expression placeholders of a request builder before it is sent.
+pub(crate) trait CheckedExpressions: Sized {
+    fn checked(self) -> Result<Self, Error>;
+}
+
+macro_rules! checked_expressions {
+    ($builder:ty, $($expression:ident),+) => {
+        impl CheckedExpressions for $builder {
+            fn checked(self) -> Result<Self, Error> {
+                let expressions: Vec<&str> = [$(self.$expression().as_deref()),+]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                let (no_names, no_values) = (HashMap::new(), HashMap::new());
+                validate_placeholders(
+                    &expressions,
+                    self.get_expression_attribute_names()
+                        .as_ref()
+                        .unwrap_or(&no_names),
+                    self.get_expression_attribute_values()
+                        .as_ref()
+                        .unwrap_or(&no_values),
+                )?;
+                Ok(self)
+            }
+        }
+    };
+}
+
+checked_expressions!(PutItemFluentBuilder, get_condition_expression);
+checked_expressions!(DeleteItemFluentBuilder, get_condition_expression);
+checked_expressions!(
+    UpdateItemFluentBuilder,
+    get_update_expression,
+    get_condition_expression
+);
+checked_expressions!(
+    QueryFluentBuilder,
+    get_key_condition_expression,
+    get_filter_expression,
+    get_projection_expression
+);
+checked_expressions!(
+    ScanFluentBuilder,
+    get_filter_expression,
+    get_projection_expression
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::Filter;
+
+    fn names(placeholders: &[&str]) -> HashMap<String, String> {
+        placeholders
+            .iter()
+            .map(|placeholder| (placeholder.to_string(), "attribute".to_string()))
+            .collect()
+    }
+
+    fn values(placeholders: &[&str]) -> HashMap<String, AttributeValue> {
+        placeholders
+            .iter()
+            .map(|placeholder| (placeholder.to_string(), AttributeValue::Null(true)))
+            .collect()
+    }
+
+    #[test]
+    fn matching_placeholders_pass() {
+        let result = validate_placeholders(
+            &["SET #a = :a, #b_1 = if_not_exists(#b_1, :zero)", "#a <> :a"],
+            &names(&["#a", "#b_1"]),
+            &values(&[":a", ":zero"]),
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn missing_and_unused_placeholders_are_named() {
+        let result =
+            validate_placeholders(&["SET #a = :a"], &names(&["#a", "#b"]), &values(&[":c"]));
+
+        let Err(Error::Validation(message)) = result else {
+            panic!("expected a validation error, got {result:?}");
+        };
+        assert_eq!(
+            message,
+            "expression placeholders do not match: :a not defined; #b, :c not used"
+        );
+    }
+
+    #[test]
+    fn rendered_filters_are_consistent() {
+        let mut names = HashMap::new();
+        let mut values = HashMap::new();
+        let expression = Filter::eq("status", AttributeValue::S("OPEN".to_string()))
+            .and(Filter::not_exists("deleted_at"))
+            .render(&mut names, &mut values);
+
+        assert!(validate_placeholders(&[&expression], &names, &values).is_ok());
+    }
+}
@@ -0,0 +1,337 @@
+use std::{sync::Mutex, time::Duration};
+
+use aws_sdk_dynamodb::types::{
+    error::ProvisionedThroughputExceededException, AttributeValue, DeleteRequest, PutRequest,
+    WriteRequest,
+};
+
+use crate::{
+    api::{DynamoStoreApi, Item, QueryRequest, StoreFuture},
+    batch::{BatchGetResult, BatchWriteResult},
+    filter::Filter,
+    naming::NamingConvention,
+    page::Page,
+    Error,
+};
+
+/// Wraps a [`DynamoStoreApi`] and injects faults with configurable probabilities.
+///
+/// Faults are drawn from a seeded generator, so a test run with the same seed and the
+/// same sequence of calls sees the same faults.
+#[derive(Debug)]
+pub struct FaultyStore<S> {
+    inner: S,
+    rng: Mutex<fastrand::Rng>,
+    latency: Option<(Duration, f64)>,
+    throttling: f64,
+    batch_failures: f64,
+    malformed_items: f64,
+}
+
+impl<S: DynamoStoreApi> FaultyStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            rng: Mutex::new(fastrand::Rng::with_seed(0)),
+            latency: None,
+            throttling: 0.0,
+            batch_failures: 0.0,
+            malformed_items: 0.0,
+        }
+    }
+
+    pub fn seed(self, seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(fastrand::Rng::with_seed(seed)),
+            ..self
+        }
+    }
+
+    /// Delays a call by `delay` with the given probability.
+    pub fn latency(mut self, delay: Duration, probability: f64) -> Self {
+        self.latency = Some((delay, probability));
+        self
+    }
+
+    /// Fails a call with `ProvisionedThroughputExceededException` with the given
+    /// probability, before it reaches the wrapped store.
+    pub fn throttling(mut self, probability: f64) -> Self {
+        self.throttling = probability;
+        self
+    }
+
+    /// Reports each request of a batch as unprocessed with the given probability.
+    pub fn partial_batch_failures(mut self, probability: f64) -> Self {
+        self.batch_failures = probability;
+        self
+    }
+
+    /// Replaces one attribute of each returned item with a value of the wrong type with
+    /// the given probability.
+    pub fn malformed_items(mut self, probability: f64) -> Self {
+        self.malformed_items = probability;
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn chance(&self, probability: f64) -> bool {
+        probability > 0.0
+            && self
+                .rng
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .f64()
+                < probability
+    }
+
+    async fn before_call(&self) -> Result<(), Error> {
+        if let Some((delay, probability)) = self.latency {
+            if self.chance(probability) {
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        if self.chance(self.throttling) {
+            return Err(
+                aws_sdk_dynamodb::Error::ProvisionedThroughputExceededException(
+                    ProvisionedThroughputExceededException::builder()
+                        .message("injected throttling")
+                        .build(),
+                )
+                .into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn maybe_malform(&self, mut item: Item) -> Item {
+        if !self.chance(self.malformed_items) || item.is_empty() {
+            return item;
+        }
+
+        let mut names: Vec<&String> = item.keys().collect();
+        names.sort();
+        let index = self
+            .rng
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .usize(..names.len());
+        let name = names[index].clone();
+
+        let replacement = match item[&name] {
+            AttributeValue::Bool(_) => AttributeValue::S("malformed".to_string()),
+            _ => AttributeValue::Bool(true),
+        };
+        item.insert(name, replacement);
+        item
+    }
+
+    /// Splits `entries` into the ones passed on and the ones reported as failed.
+    fn split_batch(&self, entries: Vec<Item>) -> (Vec<Item>, Vec<Item>) {
+        entries
+            .into_iter()
+            .partition(|_| !self.chance(self.batch_failures))
+    }
+}
+
+impl<S: DynamoStoreApi> DynamoStoreApi for FaultyStore<S> {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.before_call().await?;
+            self.inner.put_item(table_name, item, condition).await
+        })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        Box::pin(async move {
+            self.before_call().await?;
+            let item = self.inner.get_item(table_name, key).await?;
+            Ok(item.map(|item| self.maybe_malform(item)))
+        })
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.before_call().await?;
+            self.inner.delete_item(table_name, key, condition).await
+        })
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        Box::pin(async move {
+            self.before_call().await?;
+            let mut page = self.inner.query_page(request).await?;
+            page.items = page
+                .items
+                .into_iter()
+                .map(|item| self.maybe_malform(item))
+                .collect();
+            Ok(page)
+        })
+    }
+
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        self.inner.naming()
+    }
+
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            self.before_call().await?;
+            let (passed, failed) = self.split_batch(items);
+            let mut result = self.inner.batch_put_items(table_name, passed).await?;
+            for item in failed {
+                let put = PutRequest::builder().set_item(Some(item)).build()?;
+                result
+                    .unprocessed
+                    .push(WriteRequest::builder().put_request(put).build());
+            }
+            Ok(result)
+        })
+    }
+
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            self.before_call().await?;
+            let (passed, failed) = self.split_batch(keys);
+            let mut result = self.inner.batch_delete_items(table_name, passed).await?;
+            for key in failed {
+                let delete = DeleteRequest::builder().set_key(Some(key)).build()?;
+                result
+                    .unprocessed
+                    .push(WriteRequest::builder().delete_request(delete).build());
+            }
+            Ok(result)
+        })
+    }
+
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        Box::pin(async move {
+            self.before_call().await?;
+            let (passed, failed) = self.split_batch(keys);
+            let mut result = self.inner.batch_get_items(table_name, passed).await?;
+            result.items = result
+                .items
+                .into_iter()
+                .map(|item| self.maybe_malform(item))
+                .collect();
+            result.unprocessed.extend(failed);
+            Ok(result)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        schema::{ScalarType, TableSchema},
+        testing::InMemoryStore,
+    };
+
+    const TABLE: &str = "items";
+    const CALLS: usize = 1_000;
+
+    fn store() -> InMemoryStore {
+        InMemoryStore::new().with_table(TABLE, TableSchema::new("pk", ScalarType::S))
+    }
+
+    fn item(pk: &str) -> Item {
+        HashMap::from([
+            ("pk".to_string(), AttributeValue::S(pk.to_string())),
+            ("count".to_string(), AttributeValue::N("1".to_string())),
+        ])
+    }
+
+    fn key(pk: &str) -> Item {
+        HashMap::from([("pk".to_string(), AttributeValue::S(pk.to_string()))])
+    }
+
+    /// Which of `CALLS` reads were throttled.
+    async fn throttled(store: &FaultyStore<InMemoryStore>) -> Vec<bool> {
+        let mut throttled = Vec::with_capacity(CALLS);
+        for _ in 0..CALLS {
+            throttled.push(store.get_item(TABLE, key("a")).await.is_err());
+        }
+        throttled
+    }
+
+    #[tokio::test]
+    async fn the_same_seed_gives_the_same_faults() {
+        let faulty = |seed| FaultyStore::new(store()).seed(seed).throttling(0.5);
+
+        let first = throttled(&faulty(7)).await;
+        assert_eq!(first, throttled(&faulty(7)).await);
+        assert_ne!(first, throttled(&faulty(8)).await);
+    }
+
+    #[tokio::test]
+    async fn faults_occur_at_the_configured_rate() {
+        for (probability, expected) in [(0.0, 0..=0), (0.3, 250..=350), (1.0, CALLS..=CALLS)] {
+            let faulty = FaultyStore::new(store()).throttling(probability);
+
+            let count = throttled(&faulty).await.into_iter().filter(|t| *t).count();
+            assert!(
+                expected.contains(&count),
+                "{count} of {CALLS} calls throttled at {probability}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn injected_throttling_looks_like_dynamodb_throttling() {
+        let faulty = FaultyStore::new(store()).throttling(1.0);
+
+        let err = faulty.put_item(TABLE, item("a"), None).await.unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::DynamoDb(ref err)
+                if matches!(**err, aws_sdk_dynamodb::Error::ProvisionedThroughputExceededException(_))
+        ));
+        assert!(faulty.inner().items(TABLE).is_empty());
+    }
+
+    #[tokio::test]
+    async fn certain_batch_failures_and_malformed_items_always_apply() {
+        let items: Vec<Item> = (0..100).map(|i| item(&i.to_string())).collect();
+
+        let failing = FaultyStore::new(store()).partial_batch_failures(1.0);
+        let result = failing.batch_put_items(TABLE, items.clone()).await.unwrap();
+        assert_eq!(result.unprocessed.len(), 100);
+        assert!(failing.inner().items(TABLE).is_empty());
+
+        let malforming = FaultyStore::new(store()).malformed_items(1.0);
+        malforming.batch_put_items(TABLE, items).await.unwrap();
+        let read = malforming.get_item(TABLE, key("1")).await.unwrap().unwrap();
+        assert_ne!(read, item("1"));
+        assert_eq!(read.len(), 2);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+/// A filter or condition expression built from typed parts instead of strings.
+///
+/// Attribute names and values are replaced by generated placeholders when rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Compare {
+        attribute: String,
+        operator: &'static str,
+        value: AttributeValue,
+    },
+    Between {
+        attribute: String,
+        low: AttributeValue,
+        high: AttributeValue,
+    },
+    BeginsWith {
+        attribute: String,
+        prefix: String,
+    },
+    Contains {
+        attribute: String,
+        value: AttributeValue,
+    },
+    Exists(String),
+    NotExists(String),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    fn compare(
+        attribute: impl Into<String>,
+        operator: &'static str,
+        value: AttributeValue,
+    ) -> Self {
+        Self::Compare {
+            attribute: attribute.into(),
+            operator,
+            value,
+        }
+    }
+
+    pub fn eq(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "=", value)
+    }
+
+    pub fn ne(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "<>", value)
+    }
+
+    pub fn lt(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "<", value)
+    }
+
+    pub fn le(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, "<=", value)
+    }
+
+    pub fn gt(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, ">", value)
+    }
+
+    pub fn ge(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::compare(attribute, ">=", value)
+    }
+
+    pub fn between(
+        attribute: impl Into<String>,
+        low: AttributeValue,
+        high: AttributeValue,
+    ) -> Self {
+        Self::Between {
+            attribute: attribute.into(),
+            low,
+            high,
+        }
+    }
+
+    pub fn begins_with(attribute: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self::BeginsWith {
+            attribute: attribute.into(),
+            prefix: prefix.into(),
+        }
+    }
+
+    pub fn contains(attribute: impl Into<String>, value: AttributeValue) -> Self {
+        Self::Contains {
+            attribute: attribute.into(),
+            value,
+        }
+    }
+
+    pub fn exists(attribute: impl Into<String>) -> Self {
+        Self::Exists(attribute.into())
+    }
+
+    pub fn not_exists(attribute: impl Into<String>) -> Self {
+        Self::NotExists(attribute.into())
+    }
+
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Self::And(mut filters) => {
+                filters.push(other);
+                Self::And(filters)
+            }
+            filter => Self::And(vec![filter, other]),
+        }
+    }
+
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Self::Or(mut filters) => {
+                filters.push(other);
+                Self::Or(filters)
+            }
+            filter => Self::Or(vec![filter, other]),
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+
+    /// Renders the expression, adding `#f{i}`/`:f{i}` placeholders to `names` and `values`.
+    pub(crate) fn render(
+        &self,
+        names: &mut HashMap<String, String>,
+        values: &mut HashMap<String, AttributeValue>,
+    ) -> String {
+        match self {
+            Self::Compare {
+                attribute,
+                operator,
+                value,
+            } => {
+                let name = name_placeholder(attribute, names);
+                let value = value_placeholder(value.clone(), values);
+                format!("{name} {operator} {value}")
+            }
+            Self::Between {
+                attribute,
+                low,
+                high,
+            } => {
+                let name = name_placeholder(attribute, names);
+                let low = value_placeholder(low.clone(), values);
+                let high = value_placeholder(high.clone(), values);
+                format!("{name} BETWEEN {low} AND {high}")
+            }
+            Self::BeginsWith { attribute, prefix } => {
+                let name = name_placeholder(attribute, names);
+                let prefix = value_placeholder(AttributeValue::S(prefix.clone()), values);
+                format!("begins_with({name}, {prefix})")
+            }
+            Self::Contains { attribute, value } => {
+                let name = name_placeholder(attribute, names);
+                let value = value_placeholder(value.clone(), values);
+                format!("contains({name}, {value})")
+            }
+            Self::Exists(attribute) => {
+                format!("attribute_exists({})", name_placeholder(attribute, names))
+            }
+            Self::NotExists(attribute) => {
+                format!(
+                    "attribute_not_exists({})",
+                    name_placeholder(attribute, names)
+                )
+            }
+            Self::And(filters) => join(filters, " AND ", names, values),
+            Self::Or(filters) => join(filters, " OR ", names, values),
+            Self::Not(filter) => format!("NOT ({})", filter.render(names, values)),
+        }
+    }
+}
+
+fn join(
+    filters: &[Filter],
+    separator: &str,
+    names: &mut HashMap<String, String>,
+    values: &mut HashMap<String, AttributeValue>,
+) -> String {
+    filters
+        .iter()
+        .map(|filter| format!("({})", filter.render(names, values)))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn name_placeholder(attribute: &str, names: &mut HashMap<String, String>) -> String {
+    if let Some((placeholder, _)) = names.iter().find(|(_, name)| *name == attribute) {
+        return placeholder.clone();
+    }
+
+    let placeholder = format!("#f{}", names.len());
+    names.insert(placeholder.clone(), attribute.to_string());
+    placeholder
+}
+
+fn value_placeholder(
+    value: AttributeValue,
+    values: &mut HashMap<String, AttributeValue>,
+) -> String {
+    let placeholder = format!(":f{}", values.len());
+    values.insert(placeholder.clone(), value);
+    placeholder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(
+        filter: &Filter,
+    ) -> (
+        String,
+        HashMap<String, String>,
+        HashMap<String, AttributeValue>,
+    ) {
+        let mut names = HashMap::new();
+        let mut values = HashMap::new();
+        let expression = filter.render(&mut names, &mut values);
+        (expression, names, values)
+    }
+
+    fn s(value: &str) -> AttributeValue {
+        AttributeValue::S(value.to_string())
+    }
+
+    #[test]
+    fn comparisons_use_generated_placeholders() {
+        let (expression, names, values) = render(&Filter::eq("status", s("OPEN")));
+
+        assert_eq!(expression, "#f0 = :f0");
+        assert_eq!(
+            names,
+            HashMap::from([("#f0".to_string(), "status".to_string())])
+        );
+        assert_eq!(values, HashMap::from([(":f0".to_string(), s("OPEN"))]));
+    }
+
+    #[test]
+    fn an_attribute_used_twice_shares_its_name_placeholder() {
+        let filter = Filter::ge("age", AttributeValue::N("18".to_string()))
+            .and(Filter::lt("age", AttributeValue::N("65".to_string())));
+
+        let (expression, names, values) = render(&filter);
+
+        assert_eq!(expression, "(#f0 >= :f0) AND (#f0 < :f1)");
+        assert_eq!(names.len(), 1);
+        assert_eq!(values.len(), 2);
+    }
+
+    #[test]
+    fn functions_and_nesting_render_in_order() {
+        let filter = Filter::begins_with("sk", "ORDER#")
+            .or(Filter::not_exists("deleted_at"))
+            .or(Filter::contains("tags", s("vip")).negate());
+
+        let (expression, _, values) = render(&filter);
+
+        assert_eq!(
+            expression,
+            "(begins_with(#f0, :f0)) OR (attribute_not_exists(#f1)) OR (NOT (contains(#f2, :f1)))"
+        );
+        assert_eq!(values[":f0"], s("ORDER#"));
+    }
+
+    #[test]
+    fn between_takes_two_values() {
+        let filter = Filter::between(
+            "price",
+            AttributeValue::N("1".to_string()),
+            AttributeValue::N("9".to_string()),
+        );
+
+        assert_eq!(render(&filter).0, "#f0 BETWEEN :f0 AND :f1");
+    }
+
+    #[test]
+    fn placeholders_continue_after_existing_ones() {
+        let mut names = HashMap::from([("#p0".to_string(), "name".to_string())]);
+        let mut values = HashMap::new();
+
+        let expression = Filter::exists("email").render(&mut names, &mut values);
+
+        assert_eq!(expression, "attribute_exists(#f1)");
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    collections::HashMap,
+    mem,
+    path::{Path, PathBuf},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{DynamoDbStore, Error};
+
+/// Handle to the items inserted by [`DynamoDbStore::load_fixtures`].
+///
+/// Call [`cleanup`](Self::cleanup) at the end of a test to delete them again. A handle
+/// dropped without cleanup spawns the deletion on the current Tokio runtime, if any.
+#[derive(Debug)]
+pub struct Fixtures {
+    store: DynamoDbStore,
+    tables: Vec<FixtureTable>,
+}
+
+#[derive(Debug, Clone)]
+struct FixtureTable {
+    table_name: String,
+    keys: Vec<HashMap<String, AttributeValue>>,
+}
+
+impl DynamoDbStore {
+    /// Loads every `*.json` (and, with the `yaml` feature, `*.yaml`/`*.yml`) file in
+    /// `dir` into the table named after the file stem.
+    ///
+    /// Each file holds an array of items. Files are loaded in name order.
+    pub async fn load_fixtures(&self, dir: impl AsRef<Path>) -> Result<Fixtures, Error> {
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, _>>()?;
+        paths.sort();
+
+        let mut fixtures = Fixtures {
+            store: self.clone(),
+            tables: Vec::new(),
+        };
+        for path in paths.iter().filter(|path| is_fixture_file(path)) {
+            let table = self.load_fixture_file(path).await?;
+            fixtures.tables.push(table);
+        }
+
+        Ok(fixtures)
+    }
+
+    async fn load_fixture_file(&self, path: &Path) -> Result<FixtureTable, Error> {
+        let table_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| {
+                Error::Validation(format!("{} is not a valid table name", path.display()))
+            })?
+            .to_string();
+
+        let items = read_items(path)?;
+        let description = self.describe_table(&table_name).await?;
+        let mut key_names = vec![description.partition_key.name];
+        key_names.extend(description.sort_key.map(|sort_key| sort_key.name));
+
+        let keys = items
+            .iter()
+            .map(|item| {
+                key_names
+                    .iter()
+                    .map(|name| match item.get(name) {
+                        Some(value) => Ok((name.clone(), value.clone())),
+                        None => Err(Error::Validation(format!(
+                            "fixture item in {} is missing key attribute {name}",
+                            path.display()
+                        ))),
+                    })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let result = self.batch_put_items(&table_name, items).await?;
+        if !result.unprocessed.is_empty() {
+            return Err(Error::UnexpectedResponse {
+                operation: "BatchWriteItem",
+                reason: format!(
+                    "{} fixture items for {table_name} stayed unprocessed",
+                    result.unprocessed.len()
+                ),
+            });
+        }
+
+        Ok(FixtureTable { table_name, keys })
+    }
+}
+
+impl Fixtures {
+    pub fn tables(&self) -> impl Iterator<Item = &str> {
+        self.tables.iter().map(|table| table.table_name.as_str())
+    }
+
+    pub fn item_count(&self) -> usize {
+        self.tables.iter().map(|table| table.keys.len()).sum()
+    }
+
+    /// Deletes every item that was inserted, in reverse load order.
+    pub async fn cleanup(mut self) -> Result<(), Error> {
+        let tables = mem::take(&mut self.tables);
+        delete_fixtures(&self.store, tables).await
+    }
+}
+
+impl Drop for Fixtures {
+    fn drop(&mut self) {
+        if self.tables.is_empty() {
+            return;
+        }
+
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let store = self.store.clone();
+            let tables = mem::take(&mut self.tables);
+            runtime.spawn(async move {
+                let _ = delete_fixtures(&store, tables).await;
+            });
+        }
+    }
+}
+
+async fn delete_fixtures(store: &DynamoDbStore, tables: Vec<FixtureTable>) -> Result<(), Error> {
+    for table in tables.into_iter().rev() {
+        store
+            .batch_delete_items(&table.table_name, table.keys)
+            .await?;
+    }
+
+    Ok(())
+}
+
+fn is_fixture_file(path: &Path) -> bool {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    match extension {
+        Some("json") => true,
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => true,
+        _ => false,
+    }
+}
+
+fn read_items(path: &Path) -> Result<Vec<HashMap<String, AttributeValue>>, Error> {
+    let contents = std::fs::read_to_string(path)?;
+    let records: Vec<serde_json::Value> = match path.extension().and_then(|ext| ext.to_str()) {
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => serde_yaml::from_str(&contents)?,
+        _ => serde_json::from_str(&contents)?,
+    };
+
+    records
+        .into_iter()
+        .map(|record| Ok(serde_dynamo::to_item(record)?))
+        .collect()
+}
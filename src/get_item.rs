@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::{operation::get_item::GetItemOutput, types::AttributeValue};
+
+use crate::{
+    global::global_store, instrument::Operation, options::RequestOptions,
+    page::projection_expression, DynamoDbStore, Error,
+};
+
+impl DynamoDbStore {
+    pub async fn get_item(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+    ) -> Result<Option<HashMap<String, AttributeValue>>, Error> {
+        let output = self
+            .get_item_with_options(table_name, key, self.defaults())
+            .await?;
+
+        Ok(output.item)
+    }
+
+    /// Like [`get_item`](Self::get_item), returning the whole response, e.g. to read
+    /// the consumed capacity.
+    pub async fn get_item_with_options(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        options: &RequestOptions,
+    ) -> Result<GetItemOutput, Error> {
+        let options = options.or_defaults(self.defaults());
+        let operation = Operation::new("GetItem", table_name).attributes(&key);
+        let mut names = HashMap::new();
+        let projection = options
+            .projection
+            .as_deref()
+            .and_then(|attributes| projection_expression(attributes, &mut names));
+
+        let request = self
+            .read_client()
+            .get_item()
+            .table_name(table_name)
+            .set_key(Some(key))
+            .set_consistent_read(options.consistent_read)
+            .set_projection_expression(projection)
+            .set_expression_attribute_names((!names.is_empty()).then_some(names))
+            .set_return_consumed_capacity(options.return_consumed_capacity.clone());
+        let request = async {
+            match options.config_override() {
+                Some(config) => request.customize().config_override(config).send().await,
+                None => request.send().await,
+            }
+        };
+        let result = operation.send(request).await?;
+
+        Ok(result)
+    }
+}
+
+pub async fn get_item(
+    table_name: &str,
+    key: HashMap<String, AttributeValue>,
+) -> Result<Option<HashMap<String, AttributeValue>>, Error> {
+    global_store().await.get_item(table_name, key).await
+}
@@ -0,0 +1,73 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{filter::Filter, instrument::Operation, table::TableBoundStore, Error};
+
+/// Rounds of read and conditional insert before giving up, which only happens when
+/// the item is deleted and recreated concurrently over and over.
+const MAX_ROUNDS: usize = 3;
+
+impl TableBoundStore {
+    /// Returns the item at `key`, or inserts the value `default_fn` produces if there is
+    /// none yet and returns that.
+    ///
+    /// The insert only succeeds if the item still does not exist, so when two callers
+    /// race for a missing key both get the winner's value. `default_fn` runs at most
+    /// once, and not at all if the item exists.
+    pub async fn get_or_put<K, T, F>(&self, key: &K, default_fn: F) -> Result<T, Error>
+    where
+        K: Serialize,
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> T,
+    {
+        if let Some(item) = self.get(key).await? {
+            return Ok(item);
+        }
+
+        let value = default_fn();
+        let item = self.to_item(&value)?;
+        let key_names = self.key_names().await?;
+        let absent = Filter::not_exists(&key_names[0]);
+
+        for _ in 0..MAX_ROUNDS {
+            let inserted = self
+                .run(|| {
+                    self.store().put_item_if(
+                        self.table_name(),
+                        item.clone(),
+                        Some(absent.clone()),
+                        self.defaults(),
+                    )
+                })
+                .await;
+
+            match inserted {
+                Ok(_) => return Ok(value),
+                Err(err) if err.is_conditional_check_failed() => {}
+                Err(err) => return Err(err),
+            }
+
+            // Another writer won; read its item with a strongly consistent read so the
+            // winner is visible. If it was deleted again in the meantime, try again.
+            let operation = Operation::new("GetItem", self.table_name());
+            let request = self
+                .store()
+                .client()
+                .get_item()
+                .table_name(self.table_name())
+                .set_key(Some(self.to_key(key)?))
+                .consistent_read(true)
+                .send();
+            if let Some(item) = operation.send(request).await?.item {
+                return self.read_item(item);
+            }
+        }
+
+        Err(Error::UnexpectedResponse {
+            operation: "PutItem",
+            reason: format!(
+                "item in {} kept being created and deleted concurrently",
+                self.table_name()
+            ),
+        })
+    }
+}
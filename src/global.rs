@@ -0,0 +1,58 @@
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::OnceCell;
+
+use crate::{DynamoDbStore, Error};
+
+static GLOBAL_STORE: OnceCell<DynamoDbStore> = OnceCell::const_new();
+
+/// Installs `store` as the process-wide store used by the free functions, e.g. one
+/// built from a custom config at the start of a Lambda handler.
+///
+/// Fails if the global store is already set, including by an earlier free function call.
+pub fn init_global_store(store: DynamoDbStore) -> Result<(), Error> {
+    GLOBAL_STORE
+        .set(store)
+        .map_err(|_| Error::Validation("the global store is already initialized".to_string()))
+}
+
+/// The process-wide store, loaded from the environment on first use unless
+/// [`init_global_store`] installed one before.
+pub async fn global_store() -> &'static DynamoDbStore {
+    GLOBAL_STORE.get_or_init(DynamoDbStore::new).await
+}
+
+/// Serializes `item` and puts it into `table_name` through the global store.
+pub async fn put<T: Serialize>(table_name: &str, item: &T) -> Result<(), Error> {
+    global_store().await.for_table(table_name).put(item).await
+}
+
+pub async fn get<K: Serialize, T: DeserializeOwned>(
+    table_name: &str,
+    key: &K,
+) -> Result<Option<T>, Error> {
+    global_store().await.for_table(table_name).get(key).await
+}
+
+pub async fn delete<K: Serialize>(table_name: &str, key: &K) -> Result<(), Error> {
+    global_store().await.for_table(table_name).delete(key).await
+}
+
+/// Returns every item stored under `partition_key = value`, following all pages.
+pub async fn query<T: DeserializeOwned>(
+    table_name: &str,
+    partition_key: &str,
+    value: &impl Serialize,
+) -> Result<Vec<T>, Error> {
+    let table = global_store().await.for_table(table_name);
+    let items = table
+        .store()
+        .query(table_name)
+        .partition_key(partition_key, serde_dynamo::to_attribute_value(value)?)
+        .all()
+        .await?;
+
+    items
+        .into_iter()
+        .map(|item| table.read_item(item))
+        .collect()
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+use aws_sdk_dynamodb::types::{
+    DeleteGlobalSecondaryIndexAction, GlobalSecondaryIndexUpdate, IndexStatus,
+};
+
+use crate::{instrument::Operation, schema::Gsi, wait::poll_until, DynamoDbStore, Error};
+
+impl DynamoDbStore {
+    /// Starts creating the index; DynamoDB backfills it in the background.
+    pub async fn add_gsi(&self, table_name: &str, gsi: Gsi) -> Result<(), Error> {
+        let definitions = gsi
+            .key_attributes()
+            .map(|attribute| attribute.definition())
+            .collect::<Result<Vec<_>, _>>()?;
+        let update = GlobalSecondaryIndexUpdate::builder()
+            .create(gsi.to_create_action()?)
+            .build();
+
+        let operation = Operation::new("UpdateTable", table_name);
+        let request = self
+            .client()
+            .update_table()
+            .table_name(table_name)
+            .set_attribute_definitions(Some(definitions))
+            .global_secondary_index_updates(update)
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_gsi(&self, table_name: &str, index_name: &str) -> Result<(), Error> {
+        let update = GlobalSecondaryIndexUpdate::builder()
+            .delete(
+                DeleteGlobalSecondaryIndexAction::builder()
+                    .index_name(index_name)
+                    .build()?,
+            )
+            .build();
+
+        let operation = Operation::new("UpdateTable", table_name);
+        let request = self
+            .client()
+            .update_table()
+            .table_name(table_name)
+            .global_secondary_index_updates(update)
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+
+    /// Waits until the index is `ACTIVE` and no longer backfilling.
+    pub async fn wait_for_gsi_backfill(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let waiting_for = format!("index {index_name} on table {table_name} to finish backfilling");
+
+        poll_until(waiting_for, timeout, || async {
+            let operation = Operation::new("DescribeTable", table_name);
+            let request = self.client().describe_table().table_name(table_name).send();
+            let output = operation.send(request).await?;
+
+            let index = output.table.as_ref().and_then(|table| {
+                table
+                    .global_secondary_indexes()
+                    .iter()
+                    .find(|index| index.index_name() == Some(index_name))
+            });
+
+            Ok(index.is_some_and(|index| {
+                index.index_status() == Some(&IndexStatus::Active)
+                    && !index.backfilling().unwrap_or(false)
+            }))
+        })
+        .await
+    }
+
+    pub async fn wait_for_gsi_removal(
+        &self,
+        table_name: &str,
+        index_name: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let waiting_for = format!("index {index_name} on table {table_name} to be removed");
+
+        poll_until(waiting_for, timeout, || async {
+            let operation = Operation::new("DescribeTable", table_name);
+            let request = self.client().describe_table().table_name(table_name).send();
+            let output = operation.send(request).await?;
+
+            Ok(!output.table.is_some_and(|table| {
+                table
+                    .global_secondary_indexes()
+                    .iter()
+                    .any(|index| index.index_name() == Some(index_name))
+            }))
+        })
+        .await
+    }
+}
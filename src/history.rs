@@ -0,0 +1,317 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    filter::Filter,
+    keys::Pk,
+    limits::validate_item,
+    query::SortKeyCondition,
+    schema::{ScalarType, TableSchema},
+    table::TableBoundStore,
+    transaction::Transaction,
+    Error,
+};
+
+/// Partition key (`S`) of history tables: the item's key attributes joined into one
+/// string.
+pub const ITEM_KEY_ATTRIBUTE: &str = "item_key";
+/// Sort key (`N`) of history tables: when the revision was written, in epoch
+/// milliseconds.
+pub const REVISION_ATTRIBUTE: &str = "revision";
+const DATA_ATTRIBUTE: &str = "data";
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+/// Attempts to find a free revision number when writes to one item share a millisecond.
+const MAX_ATTEMPTS: u64 = 5;
+
+/// One stored revision of an item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision<T> {
+    /// Epoch milliseconds when the revision was written.
+    pub revision: u64,
+    /// The item as written, or `None` if it was deleted in this revision.
+    pub item: Option<T>,
+}
+
+/// A [`TableBoundStore`] that keeps every revision of its items in a history table.
+///
+/// Each write goes to the table and the history table in one transaction, so the
+/// history never misses a revision the table saw. Writes made without this wrapper
+/// are not recorded.
+#[derive(Debug, Clone)]
+pub struct HistoryTable {
+    table: TableBoundStore,
+    history_table: String,
+}
+
+impl TableBoundStore {
+    pub fn with_history(&self, history_table: impl Into<String>) -> HistoryTable {
+        HistoryTable {
+            table: self.clone(),
+            history_table: history_table.into(),
+        }
+    }
+}
+
+impl HistoryTable {
+    pub fn table(&self) -> &TableBoundStore {
+        &self.table
+    }
+
+    /// Creates the history table if it does not exist yet.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let schema = TableSchema::new(ITEM_KEY_ATTRIBUTE, ScalarType::S)
+            .sort_key(REVISION_ATTRIBUTE, ScalarType::N);
+        let store = self.table.store();
+
+        match store
+            .create_table_from_schema(&self.history_table, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        store
+            .wait_until_active(&self.history_table, WAIT_TIMEOUT)
+            .await
+    }
+
+    /// Writes `item` and records it as a new revision; returns the revision.
+    pub async fn put<T: Serialize>(&self, item: &T) -> Result<u64, Error> {
+        let item = self.table.to_item(item)?;
+        validate_item(&item)?;
+        let item_key = self.item_key(&item).await?;
+
+        self.write_revision(&item_key, Some(item.clone()), |transaction| {
+            transaction.put(self.table.table_name(), item.clone())
+        })
+        .await
+    }
+
+    /// Deletes the item at `key` and records the deletion as a new revision; returns
+    /// the revision.
+    pub async fn delete<K: Serialize>(&self, key: &K) -> Result<u64, Error> {
+        let key = self.table.to_key(key)?;
+        let item_key = self.item_key(&key).await?;
+
+        self.write_revision(&item_key, None, |transaction| {
+            transaction.delete(self.table.table_name(), key.clone())
+        })
+        .await
+    }
+
+    /// Every recorded revision of the item at `key`, oldest first.
+    pub async fn revisions<K: Serialize, T: DeserializeOwned>(
+        &self,
+        key: &K,
+    ) -> Result<Vec<Revision<T>>, Error> {
+        let item_key = self.item_key(&self.table.to_key(key)?).await?;
+        let items = self
+            .table
+            .store()
+            .query(&self.history_table)
+            .partition_key(ITEM_KEY_ATTRIBUTE, AttributeValue::S(item_key))
+            .all()
+            .await?;
+
+        items
+            .into_iter()
+            .map(|item| self.read_revision(item))
+            .collect()
+    }
+
+    /// The item at `key` as it was at `at`: the latest revision written at or before
+    /// it, or `None` if the item did not exist then.
+    pub async fn get_as_of<K: Serialize, T: DeserializeOwned>(
+        &self,
+        key: &K,
+        at: SystemTime,
+    ) -> Result<Option<T>, Error> {
+        let item_key = self.item_key(&self.table.to_key(key)?).await?;
+        let page = self
+            .table
+            .store()
+            .query(&self.history_table)
+            .partition_key(ITEM_KEY_ATTRIBUTE, AttributeValue::S(item_key))
+            .sort_key(
+                REVISION_ATTRIBUTE,
+                SortKeyCondition::Le(number(epoch_millis(at))),
+            )
+            .reverse()
+            .limit(1)
+            .page()
+            .await?;
+
+        match page.items.into_iter().next() {
+            Some(item) => Ok(self.read_revision(item)?.item),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the table change built by `change` together with a history record,
+    /// moving to the next millisecond if another revision already took this one.
+    async fn write_revision(
+        &self,
+        item_key: &str,
+        data: Option<HashMap<String, AttributeValue>>,
+        change: impl Fn(Transaction) -> Transaction,
+    ) -> Result<u64, Error> {
+        let now = epoch_millis(SystemTime::now());
+
+        for revision in now..now + MAX_ATTEMPTS {
+            let mut record = HashMap::from([
+                (
+                    ITEM_KEY_ATTRIBUTE.to_string(),
+                    AttributeValue::S(item_key.to_string()),
+                ),
+                (REVISION_ATTRIBUTE.to_string(), number(revision)),
+            ]);
+            if let Some(data) = &data {
+                record.insert(DATA_ATTRIBUTE.to_string(), AttributeValue::M(data.clone()));
+            }
+
+            let transaction = change(Transaction::new());
+            let record_index = transaction.len();
+            let transaction = transaction.put_if(
+                &self.history_table,
+                record,
+                Filter::not_exists(REVISION_ATTRIBUTE),
+            );
+            match self.table.store().transact_write(transaction).await {
+                Ok(()) => return Ok(revision),
+                Err(err) if revision_taken(&err, record_index) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(Error::UnexpectedResponse {
+            operation: "TransactWriteItems",
+            reason: format!("no free revision for {item_key} after {MAX_ATTEMPTS} attempts"),
+        })
+    }
+
+    fn read_revision<T: DeserializeOwned>(
+        &self,
+        mut record: HashMap<String, AttributeValue>,
+    ) -> Result<Revision<T>, Error> {
+        let revision = match record.get(REVISION_ATTRIBUTE) {
+            Some(AttributeValue::N(value)) => value.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| Error::UnexpectedResponse {
+            operation: "Query",
+            reason: format!("history record in {} has no revision", self.history_table),
+        })?;
+        let item = match record.remove(DATA_ATTRIBUTE) {
+            Some(AttributeValue::M(item)) => Some(self.table.read_item(item)?),
+            _ => None,
+        };
+
+        Ok(Revision { revision, item })
+    }
+
+    /// Joins the key attribute values of `item` in key schema order.
+    async fn item_key(&self, item: &HashMap<String, AttributeValue>) -> Result<String, Error> {
+        let parts = self
+            .table
+            .key_names()
+            .await?
+            .iter()
+            .map(|name| match item.get(name) {
+                Some(AttributeValue::S(value) | AttributeValue::N(value)) => Ok(value.clone()),
+                Some(AttributeValue::B(value)) => Ok(value
+                    .as_ref()
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect()),
+                _ => Err(Error::Validation(format!(
+                    "item is missing key attribute {name}"
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Pk::of(parts).into_inner())
+    }
+}
+
+/// Whether `err` canceled the transaction only because the history record at
+/// `record_index` found its revision already written.
+fn revision_taken(err: &Error, record_index: usize) -> bool {
+    let codes = err.cancellation_codes();
+    codes.get(record_index) == Some(&Some("ConditionalCheckFailed"))
+        && codes
+            .iter()
+            .enumerate()
+            .all(|(index, code)| index == record_index || code.is_none())
+}
+
+fn number(value: u64) -> AttributeValue {
+    AttributeValue::N(value.to_string())
+}
+
+fn epoch_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodb::types::CancellationReason;
+
+    use super::*;
+
+    fn canceled(codes: &[&str]) -> Error {
+        let reasons = codes
+            .iter()
+            .map(|code| CancellationReason::builder().code(*code).build())
+            .collect();
+        let err = aws_sdk_dynamodb::types::error::TransactionCanceledException::builder()
+            .set_cancellation_reasons(Some(reasons))
+            .build();
+        Error::DynamoDb(Box::new(
+            aws_sdk_dynamodb::Error::TransactionCanceledException(err),
+        ))
+    }
+
+    #[test]
+    fn a_taken_revision_is_retried() {
+        assert!(revision_taken(
+            &canceled(&["None", "ConditionalCheckFailed"]),
+            1
+        ));
+    }
+
+    #[test]
+    fn other_cancellations_are_not_retried() {
+        // The table change itself failed.
+        assert!(!revision_taken(
+            &canceled(&["ConditionalCheckFailed", "None"]),
+            1
+        ));
+        // Both failed, so a new revision would not help.
+        assert!(!revision_taken(
+            &canceled(&["TransactionConflict", "ConditionalCheckFailed"]),
+            1
+        ));
+        assert!(!revision_taken(&canceled(&["None", "ThrottlingError"]), 1));
+        // A cancellation without reasons.
+        assert!(!revision_taken(&canceled(&[]), 1));
+    }
+
+    #[test]
+    fn errors_other_than_cancellations_are_not_retried() {
+        let err = aws_sdk_dynamodb::Error::ConditionalCheckFailedException(
+            aws_sdk_dynamodb::types::error::ConditionalCheckFailedException::builder().build(),
+        );
+
+        assert!(!revision_taken(&Error::DynamoDb(Box::new(err)), 1));
+        assert!(!revision_taken(&Error::Validation("bad".to_string()), 1));
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    time::{Duration, SystemTime},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    filter::Filter,
+    instrument::Operation,
+    keys::ulid,
+    schema::{ScalarType, TableSchema},
+    ttl::{ttl_value, DEFAULT_TTL_ATTRIBUTE},
+    DynamoDbStore, Error,
+};
+
+const KEY_ATTRIBUTE: &str = "idempotency_key";
+const STATUS_ATTRIBUTE: &str = "status";
+const RESULT_ATTRIBUTE: &str = "result";
+const LOCKED_UNTIL_ATTRIBUTE: &str = "locked_until";
+const CLAIM_TOKEN_ATTRIBUTE: &str = "claim_token";
+const IN_PROGRESS: &str = "IN_PROGRESS";
+const COMPLETED: &str = "COMPLETED";
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Runs each idempotency key at most once and replays the stored result for
+/// duplicate invocations, e.g. redelivered Lambda events.
+#[derive(Debug, Clone)]
+pub struct IdempotencyStore {
+    store: DynamoDbStore,
+    table_name: String,
+    in_progress_timeout: Duration,
+}
+
+impl IdempotencyStore {
+    pub fn new(store: &DynamoDbStore, table_name: impl Into<String>) -> Self {
+        Self {
+            store: store.clone(),
+            table_name: table_name.into(),
+            in_progress_timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// How long an invocation may run before another one can take over its key,
+    /// e.g. after a Lambda timeout. Should exceed the handler's own timeout.
+    pub fn in_progress_timeout(mut self, in_progress_timeout: Duration) -> Self {
+        self.in_progress_timeout = in_progress_timeout;
+        self
+    }
+
+    /// Creates the record table with TTL enabled if it does not exist yet.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let schema = TableSchema::new(KEY_ATTRIBUTE, ScalarType::S);
+
+        match self
+            .store
+            .create_table_from_schema(&self.table_name, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        self.store
+            .wait_until_active(&self.table_name, WAIT_TIMEOUT)
+            .await?;
+        self.store
+            .enable_ttl(&self.table_name, DEFAULT_TTL_ATTRIBUTE)
+            .await
+    }
+
+    /// Runs `call` unless `key` was seen within `ttl`.
+    ///
+    /// A completed key returns the stored result without running `call`; a key that
+    /// another invocation is still working on fails with
+    /// [`Error::IdempotencyInProgress`]. If `call` fails, the key is released so a
+    /// retry runs it again.
+    ///
+    /// Each claim stores its own token, and the record is only completed or released
+    /// while it still holds that token. A run that outlived `in_progress_timeout` and
+    /// was taken over leaves the newer invocation's record alone.
+    pub async fn execute_idempotent<T, E, F, Fut>(
+        &self,
+        key: &str,
+        ttl: Duration,
+        call: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        E: From<Error>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let token = ulid();
+        if let Some(result) = self.claim(key, &token, ttl).await? {
+            return Ok(result);
+        }
+
+        match call().await {
+            Ok(result) => {
+                self.complete(key, &token, ttl, &result).await?;
+                Ok(result)
+            }
+            Err(err) => {
+                // The handler's error matters more; an unreleased key frees itself once
+                // `in_progress_timeout` passes.
+                if let Err(_release_err) = self.release(key, &token).await {
+                    #[cfg(feature = "wire-logging")]
+                    tracing::warn!(key, error = %_release_err, "releasing idempotency key failed");
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Marks `key` as in progress under `token`, or returns the stored result of a
+    /// completed run.
+    async fn claim<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        token: &str,
+        ttl: Duration,
+    ) -> Result<Option<T>, Error> {
+        let mut item = self.key(key);
+        item.insert(
+            STATUS_ATTRIBUTE.to_string(),
+            AttributeValue::S(IN_PROGRESS.to_string()),
+        );
+        item.insert(
+            CLAIM_TOKEN_ATTRIBUTE.to_string(),
+            AttributeValue::S(token.to_string()),
+        );
+        item.insert(
+            LOCKED_UNTIL_ATTRIBUTE.to_string(),
+            ttl_value(self.in_progress_timeout)?,
+        );
+        item.insert(DEFAULT_TTL_ATTRIBUTE.to_string(), ttl_value(ttl)?);
+
+        let condition = claimable_at(ttl_value(SystemTime::now())?);
+        match self
+            .store
+            .put_item_if(
+                &self.table_name,
+                item,
+                Some(condition),
+                self.store.defaults(),
+            )
+            .await
+        {
+            Ok(_) => return Ok(None),
+            Err(err) if err.is_conditional_check_failed() => {}
+            Err(err) => return Err(err),
+        }
+
+        let operation = Operation::new("GetItem", &self.table_name);
+        let request = self
+            .store
+            .client()
+            .get_item()
+            .table_name(&self.table_name)
+            .set_key(Some(self.key(key)))
+            .consistent_read(true)
+            .send();
+        let mut record = operation.send(request).await?.item.unwrap_or_default();
+
+        let result = record.remove(RESULT_ATTRIBUTE);
+        match (record.get(STATUS_ATTRIBUTE), result) {
+            (Some(AttributeValue::S(status)), Some(result)) if status == COMPLETED => {
+                Ok(Some(serde_dynamo::from_attribute_value(result)?))
+            }
+            _ => Err(Error::IdempotencyInProgress {
+                key: key.to_string(),
+            }),
+        }
+    }
+
+    /// Stores the result if the record still holds `token`.
+    async fn complete<T: Serialize>(
+        &self,
+        key: &str,
+        token: &str,
+        ttl: Duration,
+        result: &T,
+    ) -> Result<(), Error> {
+        let mut item = self.key(key);
+        item.insert(
+            STATUS_ATTRIBUTE.to_string(),
+            AttributeValue::S(COMPLETED.to_string()),
+        );
+        item.insert(
+            RESULT_ATTRIBUTE.to_string(),
+            serde_dynamo::to_attribute_value(result)?,
+        );
+        item.insert(DEFAULT_TTL_ATTRIBUTE.to_string(), ttl_value(ttl)?);
+
+        let condition = claimed_by(token);
+        let defaults = self.store.defaults();
+        match self
+            .store
+            .put_item_if(&self.table_name, item, Some(condition), defaults)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if err.is_conditional_check_failed() => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Deletes the record if it still holds `token`, so the key can be run again.
+    async fn release(&self, key: &str, token: &str) -> Result<(), Error> {
+        let condition = claimed_by(token);
+        let defaults = self.store.defaults();
+        match self
+            .store
+            .delete_item_if(&self.table_name, self.key(key), Some(condition), defaults)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) if err.is_conditional_check_failed() => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn key(&self, key: &str) -> HashMap<String, AttributeValue> {
+        HashMap::from([(
+            KEY_ATTRIBUTE.to_string(),
+            AttributeValue::S(key.to_string()),
+        )])
+    }
+}
+
+/// Free to claim when there is no live record, or only one whose run timed out.
+fn claimable_at(now: AttributeValue) -> Filter {
+    Filter::not_exists(KEY_ATTRIBUTE)
+        .or(Filter::lt(DEFAULT_TTL_ATTRIBUTE, now.clone()))
+        .or(
+            Filter::eq(STATUS_ATTRIBUTE, AttributeValue::S(IN_PROGRESS.to_string()))
+                .and(Filter::lt(LOCKED_UNTIL_ATTRIBUTE, now)),
+        )
+}
+
+fn claimed_by(token: &str) -> Filter {
+    Filter::eq(CLAIM_TOKEN_ATTRIBUTE, AttributeValue::S(token.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::{DynamoStoreApi, Item},
+        testing::InMemoryStore,
+    };
+
+    const TABLE: &str = "idempotency";
+
+    fn records() -> InMemoryStore {
+        InMemoryStore::new().with_table(TABLE, TableSchema::new(KEY_ATTRIBUTE, ScalarType::S))
+    }
+
+    fn number(value: u64) -> AttributeValue {
+        AttributeValue::N(value.to_string())
+    }
+
+    fn record(status: &str, token: &str, locked_until: u64, expires_at: u64) -> Item {
+        let mut item = HashMap::from([(
+            KEY_ATTRIBUTE.to_string(),
+            AttributeValue::S("payment-1".to_string()),
+        )]);
+        item.insert(
+            STATUS_ATTRIBUTE.to_string(),
+            AttributeValue::S(status.to_string()),
+        );
+        item.insert(
+            CLAIM_TOKEN_ATTRIBUTE.to_string(),
+            AttributeValue::S(token.to_string()),
+        );
+        item.insert(LOCKED_UNTIL_ATTRIBUTE.to_string(), number(locked_until));
+        item.insert(DEFAULT_TTL_ATTRIBUTE.to_string(), number(expires_at));
+        item
+    }
+
+    async fn claim(store: &InMemoryStore, existing: Option<Item>, now: u64) -> Result<(), Error> {
+        if let Some(existing) = existing {
+            store.put_item(TABLE, existing, None).await?;
+        }
+        store
+            .put_item(
+                TABLE,
+                record(IN_PROGRESS, "new", now + 60, now + 3_600),
+                Some(claimable_at(number(now))),
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn an_unseen_or_expired_key_is_claimable() {
+        assert!(claim(&records(), None, 100).await.is_ok());
+
+        let expired = record(COMPLETED, "old", 50, 90);
+        assert!(claim(&records(), Some(expired), 100).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn a_timed_out_run_is_taken_over() {
+        let timed_out = record(IN_PROGRESS, "old", 90, 1_000);
+
+        assert!(claim(&records(), Some(timed_out), 100).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn a_running_or_completed_key_is_not_claimable() {
+        for existing in [
+            record(IN_PROGRESS, "old", 150, 1_000),
+            record(COMPLETED, "old", 90, 1_000),
+        ] {
+            let err = claim(&records(), Some(existing), 100).await.unwrap_err();
+            assert!(err.is_conditional_check_failed());
+        }
+    }
+
+    #[tokio::test]
+    async fn a_taken_over_run_leaves_the_new_record_alone() {
+        let store = records();
+        store
+            .put_item(TABLE, record(IN_PROGRESS, "new", 150, 1_000), None)
+            .await
+            .unwrap();
+        let key = HashMap::from([(
+            KEY_ATTRIBUTE.to_string(),
+            AttributeValue::S("payment-1".to_string()),
+        )]);
+
+        let completed = store
+            .put_item(
+                TABLE,
+                record(COMPLETED, "old", 0, 1_000),
+                Some(claimed_by("old")),
+            )
+            .await;
+        let released = store.delete_item(TABLE, key, Some(claimed_by("old"))).await;
+
+        assert!(completed.unwrap_err().is_conditional_check_failed());
+        assert!(released.unwrap_err().is_conditional_check_failed());
+        assert_eq!(store.items(TABLE), [record(IN_PROGRESS, "new", 150, 1_000)]);
+    }
+}
@@ -0,0 +1,84 @@
+use std::{collections::HashMap, fmt::Debug, future::Future, time::Instant};
+
+use aws_sdk_dynamodb::{error::ProvideErrorMetadata, types::AttributeValue};
+
+use crate::metrics;
+
+const THROTTLING_CODES: &[&str] = &[
+    "ProvisionedThroughputExceededException",
+    "ThrottlingException",
+    "RequestLimitExceeded",
+];
+
+pub(crate) struct Operation<'a> {
+    name: &'static str,
+    table: &'a str,
+    #[cfg(feature = "wire-logging")]
+    attributes: Option<String>,
+}
+
+impl<'a> Operation<'a> {
+    pub(crate) fn new(name: &'static str, table: &'a str) -> Self {
+        Self {
+            name,
+            table,
+            #[cfg(feature = "wire-logging")]
+            attributes: None,
+        }
+    }
+
+    #[cfg_attr(not(feature = "wire-logging"), allow(unused_mut, unused_variables))]
+    pub(crate) fn attributes(mut self, attributes: &HashMap<String, AttributeValue>) -> Self {
+        #[cfg(feature = "wire-logging")]
+        {
+            self.attributes = crate::logging::describe(attributes);
+        }
+        self
+    }
+
+    pub(crate) async fn send<T, E, F>(self, call: F) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: ProvideErrorMetadata + Debug,
+    {
+        #[cfg(feature = "xray")]
+        let started_at = std::time::SystemTime::now();
+        let started = Instant::now();
+        let result = call.await;
+        let elapsed = started.elapsed();
+
+        let throttled = match &result {
+            Err(err) => is_throttling(err),
+            Ok(_) => false,
+        };
+        metrics::record_operation(self.name, self.table, elapsed, throttled);
+
+        #[cfg(feature = "xray")]
+        crate::xray::record_subsegment(
+            self.name,
+            self.table,
+            started_at,
+            std::time::SystemTime::now(),
+            match &result {
+                Ok(_) => crate::xray::Outcome::Success,
+                Err(_) => crate::xray::Outcome::Error { throttled },
+            },
+        );
+
+        #[cfg(feature = "wire-logging")]
+        crate::logging::log_call(
+            self.name,
+            self.table,
+            self.attributes.as_deref(),
+            elapsed,
+            &result,
+        );
+
+        result
+    }
+}
+
+pub(crate) fn is_throttling(err: &impl ProvideErrorMetadata) -> bool {
+    err.code()
+        .is_some_and(|code| THROTTLING_CODES.contains(&code))
+}
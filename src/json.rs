@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde_json::Value;
+
+use crate::{
+    convert::{from_attribute_map, to_attribute_map},
+    query::Query,
+    scan::Scan,
+    DynamoDbStore, Error,
+};
+
+impl DynamoDbStore {
+    /// Stores a JSON object as an item; every field becomes an attribute.
+    pub async fn put_json(&self, table_name: &str, item: &Value) -> Result<(), Error> {
+        self.put_item(table_name, json_to_item(item)?).await?;
+        Ok(())
+    }
+
+    /// Reads the item at `key`, a JSON object holding the key attributes.
+    pub async fn get_json(&self, table_name: &str, key: &Value) -> Result<Option<Value>, Error> {
+        match self.get_item(table_name, json_to_item(key)?).await? {
+            Some(item) => Ok(Some(from_attribute_map(item)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn delete_json(&self, table_name: &str, key: &Value) -> Result<(), Error> {
+        self.delete_item(table_name, json_to_item(key)?).await?;
+        Ok(())
+    }
+}
+
+impl Query<'_> {
+    pub async fn all_json(self) -> Result<Vec<Value>, Error> {
+        Ok(serde_dynamo::from_items(self.all().await?)?)
+    }
+}
+
+impl Scan<'_> {
+    pub async fn all_json(self) -> Result<Vec<Value>, Error> {
+        Ok(serde_dynamo::from_items(self.all().await?)?)
+    }
+}
+
+fn json_to_item(value: &Value) -> Result<HashMap<String, AttributeValue>, Error> {
+    if !value.is_object() {
+        return Err(Error::Validation(
+            "a JSON item has to be an object".to_string(),
+        ));
+    }
+
+    to_attribute_map(value)
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{batch::MAX_BATCH_WRITE_ITEMS, throttling::CapacityKind, DynamoDbStore, Error};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub failures: Vec<LineFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineFailure {
+    /// 1-based line number in the input.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl DynamoDbStore {
+    /// Writes every item of the table to `writer` as one JSON document per line.
+    ///
+    /// Items are deserialized into `T` first, so the output matches the shape of the
+    /// application's own type. Returns the number of records written.
+    pub async fn export_jsonl<T, W>(&self, table_name: &str, writer: W) -> Result<usize, Error>
+    where
+        T: DeserializeOwned + Serialize,
+        W: AsyncWrite + Unpin,
+    {
+        self.export_jsonl_with_segments::<T, W>(table_name, writer, 1)
+            .await
+    }
+
+    /// Like [`export_jsonl`](Self::export_jsonl), scanning `segments` parts of the table
+    /// concurrently while a single writer receives the records.
+    pub async fn export_jsonl_with_segments<T, W>(
+        &self,
+        table_name: &str,
+        mut writer: W,
+        segments: i32,
+    ) -> Result<usize, Error>
+    where
+        T: DeserializeOwned + Serialize,
+        W: AsyncWrite + Unpin,
+    {
+        let segments = segments.max(1);
+        self.warn_on_throttling(
+            "ExportJsonl",
+            table_name,
+            segments as usize,
+            &[CapacityKind::Read],
+        )
+        .await;
+
+        let mut pages = stream::select_all((0..segments).map(|segment| {
+            let scan = self.scan(table_name);
+            let scan = if segments > 1 {
+                scan.segment(segment, segments)
+            } else {
+                scan
+            };
+            scan.pages().boxed()
+        }));
+
+        let mut written = 0;
+        while let Some(page) = pages.try_next().await? {
+            let mut buffer = Vec::new();
+            for item in page.items {
+                let record: T = serde_dynamo::from_item(item)?;
+                serde_json::to_writer(&mut buffer, &record)?;
+                buffer.push(b'\n');
+                written += 1;
+            }
+            writer.write_all(&buffer).await?;
+        }
+        writer.flush().await?;
+
+        Ok(written)
+    }
+}
+
+impl DynamoDbStore {
+    /// Reads one JSON document per line, validates it as `T` and batch-writes it.
+    ///
+    /// Lines that fail to parse or stay unprocessed after retries are reported in
+    /// the returned [`ImportReport`] instead of aborting the import.
+    pub async fn import_jsonl<T, R>(
+        &self,
+        table_name: &str,
+        reader: R,
+    ) -> Result<ImportReport, Error>
+    where
+        T: DeserializeOwned + Serialize,
+        R: AsyncBufRead + Unpin,
+    {
+        let mut report = ImportReport::default();
+        let mut pending: Vec<(usize, HashMap<String, AttributeValue>)> = Vec::new();
+        let mut lines = reader.lines();
+        let mut line_number = 0;
+
+        while let Some(line) = lines.next_line().await? {
+            line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match parse_line::<T>(&line) {
+                Ok(item) => pending.push((line_number, item)),
+                Err(err) => report.failures.push(LineFailure {
+                    line: line_number,
+                    reason: err.to_string(),
+                }),
+            }
+
+            if pending.len() == MAX_BATCH_WRITE_ITEMS {
+                self.import_batch(table_name, &mut pending, &mut report)
+                    .await?;
+            }
+        }
+        self.import_batch(table_name, &mut pending, &mut report)
+            .await?;
+
+        Ok(report)
+    }
+
+    pub(crate) async fn import_batch(
+        &self,
+        table_name: &str,
+        pending: &mut Vec<(usize, HashMap<String, AttributeValue>)>,
+        report: &mut ImportReport,
+    ) -> Result<(), Error> {
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let (line_numbers, items): (Vec<usize>, Vec<_>) = pending.drain(..).unzip();
+        let submitted = items.clone();
+        let result = self.batch_put_items(table_name, items).await?;
+        report.imported += result.processed;
+
+        for request in result.unprocessed {
+            let item = request.put_request.map(|put| put.item);
+            let line = submitted
+                .iter()
+                .position(|submitted| Some(submitted) == item.as_ref())
+                .map(|index| line_numbers[index])
+                .unwrap_or_default();
+
+            report.failures.push(LineFailure {
+                line,
+                reason: "still unprocessed after retries".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_line<T>(line: &str) -> Result<HashMap<String, AttributeValue>, Error>
+where
+    T: DeserializeOwned + Serialize,
+{
+    let record: T = serde_json::from_str(line)?;
+    Ok(serde_dynamo::to_item(record)?)
+}
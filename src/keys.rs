@@ -0,0 +1,360 @@
+use std::{
+    fmt,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub const KEY_SEPARATOR: char = '#';
+const ESCAPE: char = '\\';
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const BASE62: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+/// KSUID timestamps count seconds from 2014-05-13T16:53:20Z.
+const KSUID_EPOCH: u64 = 1_400_000_000;
+const ULID_RANDOM_MASK: u128 = (1 << 80) - 1;
+
+/// Last ULID handed out as `(timestamp_ms, random)`, used to stay monotonic within
+/// the same millisecond.
+static LAST_ULID: Mutex<(u64, u128)> = Mutex::new((0, 0));
+
+/// Formats a composite key such as `ORDER#01HZX…` from its parts, escaping separators
+/// inside a part like [`Pk::of`] does, so both give the same key for the same parts.
+pub fn composite_key<I, S>(parts: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    join_escaped(
+        parts.into_iter().map(|part| part.as_ref().to_string()),
+        false,
+    )
+}
+
+/// Returns `"{prefix}#{id}"`, escaping separators inside either part.
+pub fn prefixed(prefix: &str, id: impl AsRef<str>) -> String {
+    composite_key([prefix, id.as_ref()])
+}
+
+/// Splits a key built by [`composite_key`] back into its unescaped parts.
+pub fn split_composite_key(key: &str) -> Vec<String> {
+    split_escaped(key)
+}
+
+/// Joins `prefix` and `value` with [`KEY_SEPARATOR`] without escaping, for stored key
+/// formats that treat `value` as opaque and only ever strip the prefix again.
+pub(crate) fn raw_prefixed(prefix: &str, value: &str) -> String {
+    format!("{prefix}{KEY_SEPARATOR}{value}")
+}
+
+macro_rules! composite_key_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Joins `parts` with [`KEY_SEPARATOR`], escaping separators inside a part.
+            pub fn of<I, S>(parts: I) -> Self
+            where
+                I: IntoIterator<Item = S>,
+                S: fmt::Display,
+            {
+                Self(join_escaped(parts, false))
+            }
+
+            /// Like [`Self::of`], but ends with a separator so that
+            /// `begins_with(prefix(["ORDER"]))` does not also match `ORDERS#…`.
+            pub fn prefix<I, S>(parts: I) -> Self
+            where
+                I: IntoIterator<Item = S>,
+                S: fmt::Display,
+            {
+                Self(join_escaped(parts, true))
+            }
+
+            /// Wraps an already formatted key as read from the table.
+            pub fn from_raw(raw: impl Into<String>) -> Self {
+                Self(raw.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+
+            /// Splits the key back into its unescaped parts.
+            pub fn parts(&self) -> Vec<String> {
+                split_escaped(&self.0)
+            }
+
+            pub fn begins_with(&self, prefix: &Self) -> bool {
+                self.0.starts_with(&prefix.0)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(key: $name) -> Self {
+                key.0
+            }
+        }
+
+        impl From<$name> for AttributeValue {
+            fn from(key: $name) -> Self {
+                AttributeValue::S(key.0)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Self)
+            }
+        }
+    };
+}
+
+composite_key_type!(
+    /// A partition key value built from parts, e.g. `Pk::of(["CUSTOMER", id])`.
+    Pk
+);
+composite_key_type!(
+    /// A sort key value built from parts, e.g. `Sk::of(["ORDER", id, "ITEM", line])`.
+    Sk
+);
+
+fn join_escaped<I, S>(parts: I, trailing_separator: bool) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: fmt::Display,
+{
+    let mut key = String::new();
+    for (index, part) in parts.into_iter().enumerate() {
+        if index > 0 {
+            key.push(KEY_SEPARATOR);
+        }
+        for character in part.to_string().chars() {
+            if character == KEY_SEPARATOR || character == ESCAPE {
+                key.push(ESCAPE);
+            }
+            key.push(character);
+        }
+    }
+    if trailing_separator {
+        key.push(KEY_SEPARATOR);
+    }
+    key
+}
+
+fn split_escaped(key: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut characters = key.chars();
+    while let Some(character) = characters.next() {
+        let current = parts.last_mut().expect("parts is never empty");
+        match character {
+            ESCAPE => current.extend(characters.next()),
+            KEY_SEPARATOR => parts.push(String::new()),
+            _ => current.push(character),
+        }
+    }
+    parts
+}
+
+/// A random (version 4) UUID in its hyphenated form. UUIDs do not sort by creation
+/// time; prefer [`ulid`] or [`ksuid`] for sort keys.
+pub fn uuid() -> String {
+    let mut bytes = random_bytes::<16>();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// A 26 character ULID that sorts lexicographically by creation time.
+///
+/// ULIDs generated in the same millisecond by this process keep increasing.
+pub fn ulid() -> String {
+    let now = millis_since_epoch(SystemTime::now());
+    let mut last = LAST_ULID
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let (timestamp, random) = if now <= last.0 {
+        // Same (or an earlier) millisecond: increment instead of drawing new randomness.
+        match last
+            .1
+            .checked_add(1)
+            .filter(|next| *next <= ULID_RANDOM_MASK)
+        {
+            Some(next) => (last.0, next),
+            None => (last.0 + 1, random_u128() & ULID_RANDOM_MASK),
+        }
+    } else {
+        (now, random_u128() & ULID_RANDOM_MASK)
+    };
+    *last = (timestamp, random);
+    drop(last);
+
+    let value = (u128::from(timestamp) << 80) | random;
+    (0..26)
+        .rev()
+        .map(|index| CROCKFORD[((value >> (index * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+/// Reads the creation time back out of a ULID.
+pub fn ulid_timestamp(ulid: &str) -> Option<SystemTime> {
+    if ulid.len() != 26 {
+        return None;
+    }
+
+    let mut value: u128 = 0;
+    for character in ulid.bytes().take(10) {
+        let digit = CROCKFORD
+            .iter()
+            .position(|candidate| *candidate == character.to_ascii_uppercase())?;
+        value = (value << 5) | digit as u128;
+    }
+
+    Some(UNIX_EPOCH + Duration::from_millis(value as u64))
+}
+
+/// A 27 character KSUID: a second-precision timestamp followed by 128 random bits,
+/// sortable by creation time.
+pub fn ksuid() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        .saturating_sub(KSUID_EPOCH) as u32;
+
+    let mut bytes = [0u8; 20];
+    bytes[..4].copy_from_slice(&seconds.to_be_bytes());
+    bytes[4..].copy_from_slice(&random_bytes::<16>());
+
+    let mut encoded = [b'0'; 27];
+    let mut number = bytes.to_vec();
+    for slot in encoded.iter_mut().rev() {
+        let mut remainder = 0u32;
+        for byte in number.iter_mut() {
+            let accumulator = (remainder << 8) | u32::from(*byte);
+            *byte = (accumulator / 62) as u8;
+            remainder = accumulator % 62;
+        }
+        *slot = BASE62[remainder as usize];
+    }
+
+    encoded.iter().map(|byte| *byte as char).collect()
+}
+
+fn millis_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn random_u128() -> u128 {
+    u128::from_be_bytes(random_bytes::<16>())
+}
+
+pub(crate) fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).expect("operating system random number generator failed");
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separators_inside_parts_are_escaped_and_split_back() {
+        let key = composite_key(["ORDER", "a#b", "c\\d"]);
+
+        assert_eq!(key, "ORDER#a\\#b#c\\\\d");
+        assert_eq!(split_composite_key(&key), ["ORDER", "a#b", "c\\d"]);
+    }
+
+    #[test]
+    fn all_key_builders_agree_on_the_same_parts() {
+        let parts = ["CUSTOMER", "x#1"];
+
+        assert_eq!(composite_key(parts), Pk::of(parts).into_inner());
+        assert_eq!(prefixed(parts[0], parts[1]), Sk::of(parts).into_inner());
+        assert_eq!(Pk::of(parts).parts(), parts);
+    }
+
+    #[test]
+    fn a_prefix_ends_with_a_separator() {
+        let prefix = Sk::prefix(["ORDER"]);
+
+        assert!(Sk::of(["ORDER", "1"]).begins_with(&prefix));
+        assert!(!Sk::of(["ORDERS", "1"]).begins_with(&prefix));
+    }
+
+    #[test]
+    fn raw_prefixed_keeps_the_value_as_is() {
+        assert_eq!(raw_prefixed("TENANT", "a#b"), "TENANT#a#b");
+    }
+
+    #[test]
+    fn ulids_are_monotonic_within_a_process() {
+        let ulids: Vec<String> = (0..1_000).map(|_| ulid()).collect();
+
+        assert!(ulids.iter().all(|ulid| ulid.len() == 26));
+        assert!(ulids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn ulid_timestamp_reads_back_the_creation_millisecond() {
+        let before = millis_since_epoch(SystemTime::now());
+        let timestamp = ulid_timestamp(&ulid()).map(millis_since_epoch).unwrap();
+        let after = millis_since_epoch(SystemTime::now());
+
+        // Ids drawn in the same millisecond may have moved into the next one.
+        assert!(before <= timestamp && timestamp <= after + 1);
+        assert_eq!(ulid_timestamp("too short"), None);
+        assert_eq!(ulid_timestamp("!0000000000000000000000000"), None);
+    }
+
+    #[test]
+    fn ksuids_are_27_base62_characters() {
+        let ksuid = ksuid();
+
+        assert_eq!(ksuid.len(), 27);
+        assert!(ksuid.bytes().all(|byte| BASE62.contains(&byte)));
+    }
+
+    #[test]
+    fn uuids_are_version_4() {
+        let uuid = uuid();
+
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"));
+    }
+}
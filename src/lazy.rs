@@ -0,0 +1,141 @@
+use std::{fmt, future::Future, pin::Pin, sync::Arc};
+
+use tokio::sync::OnceCell;
+
+use crate::{
+    api::{DynamoStoreApi, Item, QueryRequest, StoreFuture},
+    batch::{BatchGetResult, BatchWriteResult},
+    filter::Filter,
+    naming::NamingConvention,
+    page::Page,
+    DynamoDbStore,
+};
+
+type StoreInit = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = DynamoDbStore> + Send>> + Send + Sync>;
+
+/// A [`DynamoDbStore`] that loads its config on first use instead of at construction,
+/// for SnapStart snapshots or provisioned concurrency, where init should not make
+/// network calls.
+///
+/// Concurrent first calls wait for a single initialization. Clones share the store.
+#[derive(Clone)]
+pub struct LazyStore {
+    store: Arc<OnceCell<DynamoDbStore>>,
+    init: StoreInit,
+}
+
+impl fmt::Debug for LazyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyStore")
+            .field("store", &self.store.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for LazyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DynamoDbStore {
+    /// A store loaded from the environment on first use; see [`LazyStore`].
+    pub fn lazy() -> LazyStore {
+        LazyStore::new()
+    }
+}
+
+impl LazyStore {
+    /// Loads the store with [`DynamoDbStore::new`] on first use.
+    pub fn new() -> Self {
+        Self::with_init(DynamoDbStore::new)
+    }
+
+    /// Builds the store with `init` on first use, e.g. from a custom config or with a
+    /// naming convention.
+    pub fn with_init<F, Fut>(init: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DynamoDbStore> + Send + 'static,
+    {
+        Self {
+            store: Arc::default(),
+            init: Arc::new(move || Box::pin(init())),
+        }
+    }
+
+    /// The store, initializing it if this is the first call.
+    pub async fn store(&self) -> &DynamoDbStore {
+        self.store.get_or_init(|| (self.init)()).await
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.store.initialized()
+    }
+}
+
+impl DynamoStoreApi for LazyStore {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            DynamoStoreApi::put_item(self.store().await, table_name, item, condition).await
+        })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        Box::pin(async move { DynamoStoreApi::get_item(self.store().await, table_name, key).await })
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            DynamoStoreApi::delete_item(self.store().await, table_name, key, condition).await
+        })
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        Box::pin(async move { DynamoStoreApi::query_page(self.store().await, request).await })
+    }
+
+    fn naming(&self) -> StoreFuture<'_, NamingConvention> {
+        Box::pin(async move { DynamoStoreApi::naming(self.store().await).await })
+    }
+
+    fn batch_put_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        items: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            DynamoStoreApi::batch_put_items(self.store().await, table_name, items).await
+        })
+    }
+
+    fn batch_delete_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchWriteResult> {
+        Box::pin(async move {
+            DynamoStoreApi::batch_delete_items(self.store().await, table_name, keys).await
+        })
+    }
+
+    fn batch_get_items<'a>(
+        &'a self,
+        table_name: &'a str,
+        keys: Vec<Item>,
+    ) -> StoreFuture<'a, BatchGetResult> {
+        Box::pin(async move {
+            DynamoStoreApi::batch_get_items(self.store().await, table_name, keys).await
+        })
+    }
+}
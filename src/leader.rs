@@ -0,0 +1,181 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::{
+    sync::{oneshot, watch},
+    task::JoinHandle,
+};
+
+use crate::{
+    lock::{LockClient, LockGuard},
+    Error,
+};
+
+/// Campaigns for a leadership lease in the background, so exactly one replica runs a
+/// singleton job at a time.
+#[derive(Debug)]
+pub struct LeaderElector {
+    state: watch::Receiver<bool>,
+    last_error: Arc<Mutex<Option<Error>>>,
+    resign: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl LeaderElector {
+    /// Tries to take the lease on `key` every `check_interval` while not leading, and
+    /// checks that the lease is still held while leading.
+    ///
+    /// Fails with a validation error unless `check_interval` is shorter than the lease
+    /// duration of `locks`; a leader would otherwise keep reporting leadership after
+    /// its lease ran out.
+    pub fn start(
+        locks: LockClient,
+        key: impl Into<String>,
+        check_interval: Duration,
+    ) -> Result<Self, Error> {
+        if check_interval >= locks.lease() {
+            return Err(Error::Validation(format!(
+                "check interval {check_interval:?} must be shorter than the lease duration {:?}",
+                locks.lease()
+            )));
+        }
+
+        let key = key.into();
+        let (state_tx, state) = watch::channel(false);
+        let (resign, mut resign_rx) = oneshot::channel();
+        let last_error = Arc::new(Mutex::new(None));
+        let errors = last_error.clone();
+
+        let task = tokio::spawn(async move {
+            let mut guard: Option<LockGuard> = None;
+
+            loop {
+                match guard.take() {
+                    Some(held) if held.is_held() => guard = Some(held),
+                    _ => {
+                        guard = match locks.try_acquire(&key).await {
+                            Ok(acquired) => acquired,
+                            Err(err) => {
+                                #[cfg(feature = "wire-logging")]
+                                tracing::warn!(key, error = %err, "campaigning for leadership failed");
+                                *lock(&errors) = Some(err);
+                                None
+                            }
+                        }
+                    }
+                }
+                state_tx.send_if_modified(|leader| {
+                    let was_leader = std::mem::replace(leader, guard.is_some());
+                    was_leader != *leader
+                });
+
+                if tokio::time::timeout(check_interval, &mut resign_rx)
+                    .await
+                    .is_ok()
+                {
+                    break;
+                }
+            }
+
+            if let Some(guard) = guard {
+                let _ = guard.release().await;
+            }
+            state_tx.send_if_modified(|leader| std::mem::replace(leader, false));
+        });
+
+        Ok(Self {
+            state,
+            last_error,
+            resign: Some(resign),
+            task,
+        })
+    }
+
+    pub fn is_leader(&self) -> bool {
+        *self.state.borrow()
+    }
+
+    /// The error of the latest failed attempt to take the lease since the last call,
+    /// e.g. a missing lock table. Campaigning carries on after errors.
+    pub fn take_error(&self) -> Option<Error> {
+        lock(&self.last_error).take()
+    }
+
+    /// Waits until leadership is gained or lost and returns the new state.
+    pub async fn changed(&mut self) -> bool {
+        let _ = self.state.changed().await;
+        *self.state.borrow_and_update()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.state.clone()
+    }
+
+    /// Stops campaigning and releases the lease if it is held.
+    pub async fn resign(mut self) {
+        if let Some(resign) = self.resign.take() {
+            let _ = resign.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for LeaderElector {
+    fn drop(&mut self) {
+        if self.resign.is_some() {
+            self.task.abort();
+        }
+    }
+}
+
+fn lock(last_error: &Mutex<Option<Error>>) -> MutexGuard<'_, Option<Error>> {
+    last_error
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::offline_store;
+
+    fn locks(lease: Duration) -> LockClient {
+        LockClient::new(&offline_store(), "locks").lease_duration(lease)
+    }
+
+    #[tokio::test]
+    async fn check_intervals_must_be_shorter_than_the_lease() {
+        for check_interval in [Duration::from_secs(20), Duration::from_secs(30)] {
+            let result =
+                LeaderElector::start(locks(Duration::from_secs(20)), "job", check_interval);
+            assert!(matches!(result, Err(Error::Validation(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_attempts_are_reported() {
+        let elector = LeaderElector::start(
+            locks(Duration::from_secs(20)),
+            "job",
+            Duration::from_millis(10),
+        )
+        .unwrap();
+
+        let error = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(error) = elector.take_error() {
+                    return error;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the offline store fails every attempt");
+
+        assert!(matches!(error, Error::DynamoDb(_)));
+        assert!(!elector.is_leader());
+        elector.resign().await;
+    }
+}
@@ -1,5 +1,194 @@
+pub mod adjacency;
+pub mod api;
+pub mod audit;
+pub mod backfill;
+pub mod backup;
+pub mod batch;
+pub mod batch_writer;
+pub mod bulk_delete;
+#[cfg(feature = "cache")]
+pub mod cache;
+pub mod capacity;
+pub mod change_event;
+pub mod chunked;
+pub mod convert;
+pub mod copy_table;
+pub mod counter;
+pub mod create_table;
+#[cfg(feature = "csv")]
+pub mod csv;
+#[cfg(feature = "decimal")]
+pub mod decimal;
 pub mod delete_item;
+pub mod delete_table;
+pub mod describe_table;
+pub mod diff;
+pub mod distinct;
+pub mod empty_values;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod entity;
+mod error;
+pub mod events;
+pub mod export;
+pub mod expression;
+#[cfg(feature = "testing")]
+pub mod faulty;
+pub mod filter;
+pub mod fixtures;
+pub mod get_item;
+pub mod get_or_put;
+mod global;
+pub mod gsi;
+pub mod history;
+pub mod idempotency;
+mod instrument;
+pub mod json;
+pub mod jsonl;
+pub mod keys;
+pub mod lazy;
+pub mod leader;
+pub mod limits;
+pub mod loader;
+pub mod lock;
+#[cfg(feature = "wire-logging")]
+pub mod logging;
+pub mod merge_put;
+pub mod metrics;
+pub mod migrations;
+pub mod naming;
+pub mod options;
+mod page;
+pub mod patch;
+pub mod projection;
+#[cfg(feature = "proxy")]
+pub mod proxy;
 pub mod put_item;
+pub mod put_many;
+pub mod query;
+pub mod queue;
+pub mod rate_limit;
+pub mod read_preference;
+pub mod repository;
+pub mod retry;
+pub mod scan;
+pub mod schema;
+#[cfg(feature = "tower")]
+pub mod service;
+pub mod session;
+pub mod sets;
+pub mod sharded;
+pub mod single_table;
+pub mod store;
+#[cfg(feature = "streams")]
+pub mod streams;
+pub mod table;
+pub mod tenant;
+#[cfg(feature = "test-harness")]
+pub mod test_harness;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod throttling;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod timestamps;
+pub mod transaction;
+pub mod truncate;
+pub mod ttl;
+pub mod typed_table;
+mod update;
+pub mod validate_schema;
+pub mod versioned;
+mod wait;
+pub mod warm_up;
+#[cfg(feature = "xray")]
+mod xray;
 
+pub use adjacency::{AdjacencyList, Edge};
+#[cfg(feature = "mockall")]
+pub use api::MockDynamoStore;
+pub use api::{DynamoStoreApi, DynamoStoreApiExt, Item, QueryRequest, StoreFuture};
+pub use audit::AuditedStore;
+pub use backfill::{BackfillOptions, BackfillSummary};
+pub use backup::BackupSummary;
+pub use batch::{BatchGetResult, BatchStats, BatchWriteResult};
+pub use batch_writer::{BatchWriter, BatchWriterHandle, BatchWriterSender};
+pub use bulk_delete::DeleteSummary;
+#[cfg(feature = "cache")]
+pub use cache::{CacheConfig, CachedTable};
+pub use change_event::{ChangeEvent, ChangeKind};
+pub use chunked::ChunkedPayloads;
+pub use convert::{
+    attribute_map_to_json, attribute_value_to_json, from_attribute_map, from_attribute_value,
+    json_to_attribute_value, to_attribute_map, to_attribute_value,
+};
+pub use copy_table::{CopyTableOptions, CopyTableSummary};
 pub use delete_item::delete_item;
+pub use describe_table::{IndexDescription, ProvisionedThroughput, TableDescription};
+pub use empty_values::{EmptyValueAction, EmptyValuePolicy};
+#[cfg(feature = "kms")]
+pub use encryption::KmsKeyring;
+#[cfg(feature = "encryption")]
+pub use encryption::{DataKey, EncryptedStore, ItemEncryptor, Keyring, LocalKeyring};
+pub use entity::DynamoEntity;
+pub use error::Error;
+pub use events::StoredEvent;
+pub use export::{ExportDescription, ExportParams};
+pub use expression::validate_placeholders;
+#[cfg(feature = "testing")]
+pub use faulty::FaultyStore;
+pub use filter::Filter;
+pub use fixtures::Fixtures;
+pub use get_item::get_item;
+pub use global::{delete, get, global_store, init_global_store, put, query};
+pub use history::{HistoryTable, Revision};
+pub use idempotency::IdempotencyStore;
+pub use jsonl::{ImportReport, LineFailure};
+pub use keys::{Pk, Sk};
+pub use lazy::LazyStore;
+pub use leader::LeaderElector;
+pub use limits::{item_size, validate_item};
+pub use loader::Loader;
+pub use lock::{LockClient, LockGuard};
+pub use metrics::{disable_emf, enable_emf, EmfConfig};
+pub use migrations::{Migration, Migrator};
+pub use naming::NamingConvention;
+pub use options::RequestOptions;
+pub use page::{AllPages, Page};
+#[cfg(feature = "proxy")]
+pub use proxy::ProxySettings;
 pub use put_item::put_item;
+pub use put_many::PutOutcome;
+pub use query::SortKeyCondition;
+pub use queue::{Message, Queue};
+pub use rate_limit::{RateLimit, RateLimitWindow, RateLimiter};
+pub use read_preference::ReadPreference;
+pub use repository::Repository;
+pub use retry::RetryConfig;
+pub use schema::{BillingMode, Gsi, KeyAttribute, ProjectionType, ScalarType, TableSchema};
+#[cfg(feature = "tower")]
+pub use service::{StoreRequest, StoreResponse, StoreService};
+pub use session::SessionStore;
+pub use sets::SetValues;
+pub use sharded::ShardedTable;
+pub use single_table::{EntityType, SingleTable};
+pub use store::DynamoDbStore;
+pub use table::TableBoundStore;
+pub use tenant::TenantStore;
+#[cfg(feature = "test-harness")]
+pub use test_harness::LocalDynamoDb;
+#[cfg(feature = "testing")]
+pub use testing::InMemoryStore;
+pub use throttling::{CapacityKind, ThrottlingHint};
+pub use transaction::Transaction;
+pub use truncate::TruncateProgress;
+pub use ttl::{ttl_value, Expiry, TtlDescription};
+pub use typed_table::TypedTableStore;
+pub use versioned::Versioned;
+
+#[cfg(feature = "derive")]
+pub use clean_dynamodb_store_derive::{DynamoEntity, DynamoKey};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+}
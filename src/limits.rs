@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::Error;
+
+/// Maps and lists nest at most this deep.
+pub const MAX_NESTING_DEPTH: usize = 32;
+/// Attribute names are between 1 and this many bytes long.
+pub const MAX_ATTRIBUTE_NAME_LENGTH: usize = 65_535;
+pub const MAX_ITEM_SIZE: usize = 400 * 1024;
+
+/// Checks `item` against DynamoDB's structural limits: nesting depth, attribute name
+/// lengths and item size. Errors name the offending attribute path, e.g.
+/// `address.lines[2]`.
+pub fn validate_item(item: &HashMap<String, AttributeValue>) -> Result<(), Error> {
+    validate_map(item, "", 0)?;
+
+    let size = item_size(item);
+    if size > MAX_ITEM_SIZE {
+        return Err(Error::Validation(format!(
+            "item is about {size} bytes, DynamoDB allows at most {MAX_ITEM_SIZE}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Estimates the stored size of `item` the way DynamoDB bills and limits it.
+pub fn item_size(item: &HashMap<String, AttributeValue>) -> usize {
+    item.iter()
+        .map(|(name, value)| name.len() + value_size(value))
+        .sum()
+}
+
+fn validate_map(
+    map: &HashMap<String, AttributeValue>,
+    parent: &str,
+    depth: usize,
+) -> Result<(), Error> {
+    for (name, value) in map {
+        let path = if parent.is_empty() {
+            name.clone()
+        } else {
+            format!("{parent}.{name}")
+        };
+        if name.is_empty() || name.len() > MAX_ATTRIBUTE_NAME_LENGTH {
+            return Err(Error::Validation(format!(
+                "attribute name at {path:?} has to be between 1 and \
+                 {MAX_ATTRIBUTE_NAME_LENGTH} bytes long"
+            )));
+        }
+        validate_value(value, &path, depth)?;
+    }
+    Ok(())
+}
+
+fn validate_value(value: &AttributeValue, path: &str, depth: usize) -> Result<(), Error> {
+    let nested = match value {
+        AttributeValue::M(_) | AttributeValue::L(_) => depth + 1,
+        _ => return Ok(()),
+    };
+    if nested > MAX_NESTING_DEPTH {
+        return Err(Error::Validation(format!(
+            "attribute {path} is nested more than {MAX_NESTING_DEPTH} levels deep"
+        )));
+    }
+
+    match value {
+        AttributeValue::M(map) => validate_map(map, path, nested),
+        AttributeValue::L(list) => list.iter().enumerate().try_for_each(|(index, value)| {
+            validate_value(value, &format!("{path}[{index}]"), nested)
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn value_size(value: &AttributeValue) -> usize {
+    match value {
+        AttributeValue::S(value) => value.len(),
+        AttributeValue::N(value) => number_size(value),
+        AttributeValue::B(value) => value.as_ref().len(),
+        AttributeValue::Ss(values) => values.iter().map(String::len).sum(),
+        AttributeValue::Ns(values) => values.iter().map(|value| number_size(value)).sum(),
+        AttributeValue::Bs(values) => values.iter().map(|value| value.as_ref().len()).sum(),
+        // Maps and lists cost 3 bytes plus 1 byte per element on top of their contents.
+        AttributeValue::M(map) => {
+            3 + map
+                .iter()
+                .map(|(name, value)| 1 + name.len() + value_size(value))
+                .sum::<usize>()
+        }
+        AttributeValue::L(list) => {
+            3 + list
+                .iter()
+                .map(|value| 1 + value_size(value))
+                .sum::<usize>()
+        }
+        _ => 1,
+    }
+}
+
+/// Numbers take one byte per two significant digits plus one byte.
+fn number_size(number: &str) -> usize {
+    let digits = number
+        .split(['e', 'E'])
+        .next()
+        .unwrap_or_default()
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .skip_while(|digit| *digit == b'0')
+        .count();
+    digits.div_ceil(2) + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(value: &str) -> AttributeValue {
+        AttributeValue::S(value.to_string())
+    }
+
+    fn nested(depth: usize) -> AttributeValue {
+        (0..depth).fold(s("leaf"), |value, _| AttributeValue::L(vec![value]))
+    }
+
+    fn validation_message(item: &HashMap<String, AttributeValue>) -> String {
+        match validate_item(item) {
+            Err(Error::Validation(message)) => message,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn sizes_count_names_and_values() {
+        let item = HashMap::from([
+            ("id".to_string(), s("abc")),
+            ("n".to_string(), AttributeValue::N("12345".to_string())),
+            ("ok".to_string(), AttributeValue::Bool(true)),
+            ("tags".to_string(), AttributeValue::L(vec![s("a"), s("bc")])),
+        ]);
+
+        // 2 + 3, 1 + 4, 2 + 1, 4 + 3 + (1 + 1) + (1 + 2)
+        assert_eq!(item_size(&item), 5 + 5 + 3 + 12);
+    }
+
+    #[test]
+    fn leading_zeros_and_exponents_do_not_count() {
+        assert_eq!(number_size("0.001"), 2);
+        assert_eq!(number_size("-12.5e10"), 3);
+    }
+
+    #[test]
+    fn nesting_is_limited_and_reported_by_path() {
+        let allowed = HashMap::from([("deep".to_string(), nested(MAX_NESTING_DEPTH))]);
+        let too_deep = HashMap::from([(
+            "address".to_string(),
+            AttributeValue::M(HashMap::from([(
+                "lines".to_string(),
+                nested(MAX_NESTING_DEPTH),
+            )])),
+        )]);
+
+        assert!(validate_item(&allowed).is_ok());
+        assert!(validation_message(&too_deep).starts_with("attribute address.lines[0]"));
+    }
+
+    #[test]
+    fn empty_attribute_names_are_rejected() {
+        let item = HashMap::from([(String::new(), s("value"))]);
+
+        assert!(validation_message(&item).contains("between 1 and"));
+    }
+
+    #[test]
+    fn items_over_400_kb_are_rejected() {
+        let fits = HashMap::from([("b".to_string(), s(&"x".repeat(MAX_ITEM_SIZE - 1)))]);
+        let too_large = HashMap::from([("b".to_string(), s(&"x".repeat(MAX_ITEM_SIZE)))]);
+
+        assert!(validate_item(&fits).is_ok());
+        assert!(validation_message(&too_large).starts_with("item is about 409601 bytes"));
+    }
+}
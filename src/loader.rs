@@ -0,0 +1,199 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::future::try_join_all;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{batch::key_identity, table::TableBoundStore, typed_table::TypedTableStore, Error};
+
+type Item = HashMap<String, AttributeValue>;
+type Waiter = oneshot::Sender<Result<Option<Item>, Error>>;
+type PendingKeys = Arc<Mutex<HashMap<String, PendingKey>>>;
+
+struct PendingKey {
+    key: Item,
+    waiters: Vec<Waiter>,
+}
+
+/// Coalesces the `load` calls of one table into BatchGetItem requests.
+///
+/// The first call starts a short window; every key requested during the window is
+/// fetched with one batch get, and keys requested more than once are fetched once.
+/// Clones share the pending batch, so one loader per request (e.g. per GraphQL query)
+/// batches across all of its resolvers.
+pub struct Loader<K, T> {
+    table: TableBoundStore,
+    window: Duration,
+    pending: PendingKeys,
+    types: PhantomData<fn() -> (K, T)>,
+}
+
+impl<K, T> Clone for Loader<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+            window: self.window,
+            pending: self.pending.clone(),
+            types: PhantomData,
+        }
+    }
+}
+
+impl<K, T> fmt::Debug for Loader<K, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loader")
+            .field("table", &self.table)
+            .field("window", &self.window)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TableBoundStore {
+    pub fn loader<K, T>(&self) -> Loader<K, T> {
+        Loader {
+            table: self.clone(),
+            window: Duration::from_millis(1),
+            pending: Arc::default(),
+            types: PhantomData,
+        }
+    }
+}
+
+impl<T, K> TypedTableStore<T, K>
+where
+    T: Serialize + DeserializeOwned,
+    K: Serialize,
+{
+    pub fn loader(&self) -> Loader<K, T> {
+        self.table().loader()
+    }
+}
+
+impl<K, T> Loader<K, T>
+where
+    K: Serialize,
+    T: DeserializeOwned,
+{
+    /// How long the first `load` of a batch waits for more keys; defaults to 1 ms.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Loads the item at `key` as part of the current batch.
+    ///
+    /// If the batch request fails, every caller in the batch gets an
+    /// [`Error::UnexpectedResponse`] carrying the original error message.
+    pub async fn load(&self, key: &K) -> Result<Option<T>, Error> {
+        let key = self.table.to_key(key)?;
+        let identity = key_identity(&key, &[]);
+        let (sender, receiver) = oneshot::channel();
+
+        let first = {
+            let mut pending = lock(&self.pending);
+            let first = pending.is_empty();
+            pending
+                .entry(identity)
+                .or_insert_with(|| PendingKey {
+                    key,
+                    waiters: Vec::new(),
+                })
+                .waiters
+                .push(sender);
+            first
+        };
+        if first {
+            tokio::spawn(dispatch(
+                self.table.clone(),
+                self.window,
+                self.pending.clone(),
+            ));
+        }
+
+        let item = receiver.await.map_err(|_| Error::UnexpectedResponse {
+            operation: "BatchGetItem",
+            reason: "loader batch was dropped before it completed".to_string(),
+        })??;
+        item.map(|item| self.table.read_item(item)).transpose()
+    }
+
+    /// Loads several keys as part of the current batch, in the order of `keys`.
+    pub async fn load_many(&self, keys: &[K]) -> Result<Vec<Option<T>>, Error> {
+        try_join_all(keys.iter().map(|key| self.load(key))).await
+    }
+}
+
+/// Waits for the window to close, then fetches every pending key and answers its
+/// waiters.
+async fn dispatch(table: TableBoundStore, window: Duration, pending: PendingKeys) {
+    tokio::time::sleep(window).await;
+    let batch = std::mem::take(&mut *lock(&pending));
+    let keys = batch.values().map(|pending| pending.key.clone()).collect();
+
+    let result = async {
+        let key_names = table.key_names().await?.to_vec();
+        let result = table
+            .store()
+            .batch_get_items_with_retry(table.table_name(), keys, &table.batch_retry())
+            .await?;
+        Ok::<_, Error>((key_names, result))
+    }
+    .await;
+
+    let (key_names, result) = match result {
+        Ok(result) => result,
+        Err(err) => {
+            let reason = err.to_string();
+            for waiter in batch.into_values().flat_map(|pending| pending.waiters) {
+                let _ = waiter.send(Err(Error::UnexpectedResponse {
+                    operation: "BatchGetItem",
+                    reason: reason.clone(),
+                }));
+            }
+            return;
+        }
+    };
+
+    let mut found: HashMap<String, Item> = result
+        .items
+        .into_iter()
+        .map(|item| (key_identity(&item, &key_names), item))
+        .collect();
+    let unprocessed: HashSet<String> = result
+        .unprocessed
+        .iter()
+        .map(|key| key_identity(key, &[]))
+        .collect();
+
+    for (identity, pending) in batch {
+        let mut item = found.remove(&identity);
+        let mut waiters = pending.waiters.into_iter().peekable();
+        while let Some(waiter) = waiters.next() {
+            let answer = if unprocessed.contains(&identity) {
+                Err(Error::UnexpectedResponse {
+                    operation: "BatchGetItem",
+                    reason: "key stayed unprocessed after retries".to_string(),
+                })
+            } else if waiters.peek().is_some() {
+                Ok(item.clone())
+            } else {
+                // The last waiter of a key takes the item itself.
+                Ok(item.take())
+            };
+            let _ = waiter.send(answer);
+        }
+    }
+}
+
+fn lock(pending: &PendingKeys) -> MutexGuard<'_, HashMap<String, PendingKey>> {
+    pending
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
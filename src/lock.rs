@@ -0,0 +1,475 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::{AttributeValue, ReturnValue};
+use tokio::task::JoinHandle;
+
+use crate::{
+    expression::CheckedExpressions,
+    filter::Filter,
+    instrument::Operation,
+    keys::ulid,
+    schema::{ScalarType, TableSchema},
+    ttl::DEFAULT_TTL_ATTRIBUTE,
+    update::with_condition,
+    DynamoDbStore, Error,
+};
+
+const KEY_ATTRIBUTE: &str = "lock_key";
+const OWNER_ATTRIBUTE: &str = "owner";
+/// Identifies one acquisition, so that two acquisitions by the same owner never share
+/// a lease.
+const TOKEN_ATTRIBUTE: &str = "lease_token";
+const EXPIRES_ATTRIBUTE: &str = "lease_expires_at";
+const FENCE_ATTRIBUTE: &str = "fence";
+/// How long an expired lock item is kept before TTL may remove it.
+const TTL_GRACE: Duration = Duration::from_secs(24 * 60 * 60);
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Hands out leases on named locks stored in a DynamoDB table.
+#[derive(Debug, Clone)]
+pub struct LockClient {
+    store: DynamoDbStore,
+    table_name: String,
+    owner: String,
+    lease_duration: Duration,
+    heartbeat_interval: Duration,
+    retry_interval: Duration,
+}
+
+/// A held lock. The lease is renewed in the background until the guard is released
+/// or dropped; a dropped guard simply lets its lease run out.
+#[derive(Debug)]
+pub struct LockGuard {
+    client: LockClient,
+    key: String,
+    token: String,
+    fencing_token: u64,
+    lost: Arc<AtomicBool>,
+    heartbeat: JoinHandle<()>,
+}
+
+impl LockClient {
+    pub fn new(store: &DynamoDbStore, table_name: impl Into<String>) -> Self {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        Self {
+            store: store.clone(),
+            table_name: table_name.into(),
+            owner: format!("{}-{started}", std::process::id()),
+            lease_duration: Duration::from_secs(20),
+            heartbeat_interval: Duration::from_secs(5),
+            retry_interval: Duration::from_millis(500),
+        }
+    }
+
+    pub fn owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = owner.into();
+        self
+    }
+
+    pub fn lease_duration(mut self, lease_duration: Duration) -> Self {
+        self.lease_duration = lease_duration;
+        self
+    }
+
+    pub fn heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    pub fn retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    pub(crate) fn lease(&self) -> Duration {
+        self.lease_duration
+    }
+
+    /// Creates the lock table with TTL on the lease attribute if it does not exist yet.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let schema = TableSchema::new(KEY_ATTRIBUTE, ScalarType::S);
+
+        match self
+            .store
+            .create_table_from_schema(&self.table_name, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        self.store
+            .wait_until_active(&self.table_name, WAIT_TIMEOUT)
+            .await?;
+        self.store
+            .enable_ttl(&self.table_name, DEFAULT_TTL_ATTRIBUTE)
+            .await
+    }
+
+    /// Takes the lock if it is free or expired; returns `None` otherwise.
+    ///
+    /// A lock held through another guard is not free, even if that guard came from this
+    /// client or a clone of it; extend a held lease with [`LockGuard::renew`] instead.
+    pub async fn try_acquire(&self, key: &str) -> Result<Option<LockGuard>, Error> {
+        let now = epoch_millis(SystemTime::now());
+        let token = format!("{}:{}", self.owner, ulid());
+        let operation = Operation::new("UpdateItem", &self.table_name);
+        let request = self
+            .lease_update(key)
+            .update_expression(
+                "SET #owner = :owner, #token = :token, #expires = :expires, #ttl = :ttl \
+                 ADD #fence :one",
+            )
+            .expression_attribute_names("#owner", OWNER_ATTRIBUTE)
+            .expression_attribute_names("#token", TOKEN_ATTRIBUTE)
+            .expression_attribute_names("#fence", FENCE_ATTRIBUTE)
+            .expression_attribute_values(":owner", AttributeValue::S(self.owner.clone()))
+            .expression_attribute_values(":token", AttributeValue::S(token.clone()))
+            .expression_attribute_values(":one", AttributeValue::N("1".to_string()))
+            .return_values(ReturnValue::AllNew);
+        let request = with_condition(request, &free_at(now)).checked()?.send();
+
+        let output = match operation.send(request).await.map_err(Error::from) {
+            Ok(output) => output,
+            Err(err) if err.is_conditional_check_failed() => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let fencing_token = match output
+            .attributes
+            .as_ref()
+            .and_then(|attributes| attributes.get(FENCE_ATTRIBUTE))
+        {
+            Some(AttributeValue::N(fence)) => fence.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| Error::UnexpectedResponse {
+            operation: "UpdateItem",
+            reason: format!("lock {key} has no fencing token"),
+        })?;
+
+        Ok(Some(self.guard(key, token, fencing_token)))
+    }
+
+    /// Retries [`try_acquire`](Self::try_acquire) every retry interval until `timeout`.
+    pub async fn acquire(&self, key: &str, timeout: Duration) -> Result<LockGuard, Error> {
+        let started = Instant::now();
+
+        loop {
+            if let Some(guard) = self.try_acquire(key).await? {
+                return Ok(guard);
+            }
+            if started.elapsed() >= timeout {
+                return Err(Error::Timeout {
+                    waiting_for: format!("lock {key}"),
+                    waited: timeout,
+                });
+            }
+
+            tokio::time::sleep(self.retry_interval).await;
+        }
+    }
+
+    /// Returns the current owner of `key` if its lease has not expired.
+    pub async fn owner_of(&self, key: &str) -> Result<Option<String>, Error> {
+        let key_item = HashMap::from([(
+            KEY_ATTRIBUTE.to_string(),
+            AttributeValue::S(key.to_string()),
+        )]);
+        let Some(item) = self.store.get_item(&self.table_name, key_item).await? else {
+            return Ok(None);
+        };
+
+        let now = epoch_millis(SystemTime::now());
+        let expires_at = match item.get(EXPIRES_ATTRIBUTE) {
+            Some(AttributeValue::N(expires_at)) => expires_at.parse().unwrap_or_default(),
+            _ => 0,
+        };
+        match item.get(OWNER_ATTRIBUTE) {
+            Some(AttributeValue::S(owner)) if expires_at > now => Ok(Some(owner.clone())),
+            _ => Ok(None),
+        }
+    }
+
+    fn guard(&self, key: &str, token: String, fencing_token: u64) -> LockGuard {
+        let lost = Arc::new(AtomicBool::new(false));
+        let heartbeat = tokio::spawn({
+            let client = self.clone();
+            let key = key.to_string();
+            let token = token.clone();
+            let lost = lost.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(client.heartbeat_interval).await;
+                    if !matches!(client.renew(&key, &token, fencing_token).await, Ok(true)) {
+                        lost.store(true, Ordering::SeqCst);
+                        break;
+                    }
+                }
+            }
+        });
+
+        LockGuard {
+            client: self.clone(),
+            key: key.to_string(),
+            token,
+            fencing_token,
+            lost,
+            heartbeat,
+        }
+    }
+
+    /// Returns `false` when the lease now belongs to someone else.
+    async fn renew(&self, key: &str, token: &str, fencing_token: u64) -> Result<bool, Error> {
+        let operation = Operation::new("UpdateItem", &self.table_name);
+        let request = self
+            .lease_update(key)
+            .update_expression("SET #expires = :expires, #ttl = :ttl");
+        let request = with_condition(request, &held_by(token, fencing_token))
+            .checked()?
+            .send();
+
+        match operation.send(request).await.map_err(Error::from) {
+            Ok(_) => Ok(true),
+            Err(err) if err.is_conditional_check_failed() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Ends the lease early but keeps the item, so fencing tokens keep increasing.
+    async fn release(&self, key: &str, token: &str, fencing_token: u64) -> Result<(), Error> {
+        let operation = Operation::new("UpdateItem", &self.table_name);
+        let request = self
+            .store
+            .client()
+            .update_item()
+            .table_name(&self.table_name)
+            .key(KEY_ATTRIBUTE, AttributeValue::S(key.to_string()))
+            .update_expression("SET #expires = :zero")
+            .expression_attribute_names("#expires", EXPIRES_ATTRIBUTE)
+            .expression_attribute_values(":zero", AttributeValue::N("0".to_string()));
+        let request = with_condition(request, &held_by(token, fencing_token))
+            .checked()?
+            .send();
+
+        match operation.send(request).await.map_err(Error::from) {
+            Ok(_) => Ok(()),
+            Err(err) if err.is_conditional_check_failed() => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn lease_update(
+        &self,
+        key: &str,
+    ) -> aws_sdk_dynamodb::operation::update_item::builders::UpdateItemFluentBuilder {
+        let expires_at = SystemTime::now() + self.lease_duration;
+        let ttl = (expires_at + TTL_GRACE)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        self.store
+            .client()
+            .update_item()
+            .table_name(&self.table_name)
+            .key(KEY_ATTRIBUTE, AttributeValue::S(key.to_string()))
+            .expression_attribute_names("#expires", EXPIRES_ATTRIBUTE)
+            .expression_attribute_names("#ttl", DEFAULT_TTL_ATTRIBUTE)
+            .expression_attribute_values(
+                ":expires",
+                AttributeValue::N(epoch_millis(expires_at).to_string()),
+            )
+            .expression_attribute_values(":ttl", AttributeValue::N(ttl.to_string()))
+    }
+}
+
+impl LockGuard {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Increases with every acquisition of the same key; pass it to downstream writes
+    /// so they can reject requests from a holder whose lease has already expired.
+    pub fn fencing_token(&self) -> u64 {
+        self.fencing_token
+    }
+
+    /// `false` once a heartbeat found the lease taken over or failed to renew it.
+    pub fn is_held(&self) -> bool {
+        !self.lost.load(Ordering::SeqCst)
+    }
+
+    /// Extends the lease by a full lease duration now, e.g. before a step that may
+    /// outlast the next heartbeat. Returns `false`, and marks the guard as no longer
+    /// held, when the lease now belongs to someone else.
+    pub async fn renew(&self) -> Result<bool, Error> {
+        let renewed = self
+            .client
+            .renew(&self.key, &self.token, self.fencing_token)
+            .await?;
+        if !renewed {
+            self.lost.store(true, Ordering::SeqCst);
+        }
+        Ok(renewed)
+    }
+
+    pub async fn release(self) -> Result<(), Error> {
+        self.heartbeat.abort();
+        self.client
+            .release(&self.key, &self.token, self.fencing_token)
+            .await
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        self.heartbeat.abort();
+    }
+}
+
+fn epoch_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+/// Free when the lock was never taken or its last lease has run out.
+fn free_at(now: u128) -> Filter {
+    Filter::not_exists(KEY_ATTRIBUTE).or(Filter::lt(
+        EXPIRES_ATTRIBUTE,
+        AttributeValue::N(now.to_string()),
+    ))
+}
+
+/// Still the lease taken by the acquisition that got `token` and `fencing_token`.
+fn held_by(token: &str, fencing_token: u64) -> Filter {
+    Filter::eq(TOKEN_ATTRIBUTE, AttributeValue::S(token.to_string())).and(Filter::eq(
+        FENCE_ATTRIBUTE,
+        AttributeValue::N(fencing_token.to_string()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::{DynamoStoreApi, Item},
+        testing::InMemoryStore,
+    };
+
+    const TABLE: &str = "locks";
+
+    fn locks() -> InMemoryStore {
+        InMemoryStore::new().with_table(TABLE, TableSchema::new(KEY_ATTRIBUTE, ScalarType::S))
+    }
+
+    fn lease(token: &str, fencing_token: u64, expires_at: u128) -> Item {
+        HashMap::from([
+            (
+                KEY_ATTRIBUTE.to_string(),
+                AttributeValue::S("jobs".to_string()),
+            ),
+            (
+                OWNER_ATTRIBUTE.to_string(),
+                AttributeValue::S("worker".to_string()),
+            ),
+            (
+                TOKEN_ATTRIBUTE.to_string(),
+                AttributeValue::S(token.to_string()),
+            ),
+            (
+                FENCE_ATTRIBUTE.to_string(),
+                AttributeValue::N(fencing_token.to_string()),
+            ),
+            (
+                EXPIRES_ATTRIBUTE.to_string(),
+                AttributeValue::N(expires_at.to_string()),
+            ),
+        ])
+    }
+
+    #[tokio::test]
+    async fn a_missing_lock_is_free() {
+        let store = locks();
+
+        let taken = store
+            .put_item(TABLE, lease("worker:1", 1, 2_000), Some(free_at(1_000)))
+            .await;
+
+        assert!(taken.is_ok());
+    }
+
+    #[tokio::test]
+    async fn a_live_lease_excludes_other_acquisitions_of_the_same_owner() {
+        let store = locks();
+        store
+            .put_item(TABLE, lease("worker:1", 1, 2_000), None)
+            .await
+            .unwrap();
+
+        let err = store
+            .put_item(TABLE, lease("worker:2", 2, 3_000), Some(free_at(1_000)))
+            .await
+            .unwrap_err();
+
+        assert!(err.is_conditional_check_failed());
+    }
+
+    #[tokio::test]
+    async fn an_expired_lease_can_be_taken_over() {
+        let store = locks();
+        store
+            .put_item(TABLE, lease("worker:1", 1, 1_000), None)
+            .await
+            .unwrap();
+
+        let taken = store
+            .put_item(TABLE, lease("other:1", 2, 3_000), Some(free_at(2_000)))
+            .await;
+
+        assert!(taken.is_ok());
+    }
+
+    #[tokio::test]
+    async fn only_the_acquiring_token_and_fence_hold_the_lease() {
+        let store = locks();
+        store
+            .put_item(TABLE, lease("worker:1", 1, 2_000), None)
+            .await
+            .unwrap();
+
+        for (token, fencing_token) in [("worker:2", 1), ("worker:1", 2)] {
+            let err = store
+                .put_item(
+                    TABLE,
+                    lease(token, fencing_token, 3_000),
+                    Some(held_by(token, fencing_token)),
+                )
+                .await
+                .unwrap_err();
+            assert!(err.is_conditional_check_failed());
+        }
+
+        let renewed = store
+            .put_item(
+                TABLE,
+                lease("worker:1", 1, 3_000),
+                Some(held_by("worker:1", 1)),
+            )
+            .await;
+        assert!(renewed.is_ok());
+    }
+}
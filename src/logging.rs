@@ -0,0 +1,96 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
+
+use aws_sdk_dynamodb::{error::ProvideErrorMetadata, types::AttributeValue};
+
+type Redactor = Arc<dyn Fn(&str, &AttributeValue) -> String + Send + Sync>;
+
+static REDACTOR: RwLock<Option<Redactor>> = RwLock::new(None);
+static LOG_VALUES: AtomicBool = AtomicBool::new(false);
+
+/// Writes attribute values to wire logs in their `Debug` form. Off by default, when only
+/// attribute names are logged, since items commonly hold personal data.
+pub fn log_attribute_values(enabled: bool) {
+    LOG_VALUES.store(enabled, Ordering::Relaxed);
+}
+
+/// Controls how attribute values appear in wire logs, e.g. to mask personal data.
+/// Setting a redactor turns value logging on, rendering each value through it.
+pub fn set_redactor(redactor: impl Fn(&str, &AttributeValue) -> String + Send + Sync + 'static) {
+    *REDACTOR.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(redactor));
+}
+
+pub fn clear_redactor() {
+    *REDACTOR.write().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+pub(crate) fn describe(attributes: &HashMap<String, AttributeValue>) -> Option<String> {
+    if !tracing::enabled!(target: "clean_dynamodb_store::wire", tracing::Level::DEBUG) {
+        return None;
+    }
+
+    let redactor = REDACTOR
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+
+    let log_values = LOG_VALUES.load(Ordering::Relaxed);
+
+    let mut names: Vec<&String> = attributes.keys().collect();
+    names.sort();
+
+    let rendered: Vec<String> = names
+        .into_iter()
+        .map(|name| {
+            let value = &attributes[name];
+            match &redactor {
+                Some(redactor) => format!("{name}={}", redactor(name, value)),
+                None if log_values => format!("{name}={value:?}"),
+                None => name.clone(),
+            }
+        })
+        .collect();
+
+    Some(format!("{{{}}}", rendered.join(", ")))
+}
+
+pub(crate) fn log_call<T, E>(
+    operation: &str,
+    table: &str,
+    attributes: Option<&str>,
+    elapsed: Duration,
+    result: &Result<T, E>,
+) where
+    E: ProvideErrorMetadata + Debug,
+{
+    let attributes = attributes.unwrap_or("-");
+    let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+
+    match result {
+        Ok(_) => tracing::debug!(
+            target: "clean_dynamodb_store::wire",
+            operation,
+            table,
+            attributes,
+            elapsed_ms,
+            "dynamodb call succeeded"
+        ),
+        Err(err) => tracing::debug!(
+            target: "clean_dynamodb_store::wire",
+            operation,
+            table,
+            attributes,
+            elapsed_ms,
+            error_code = err.code().unwrap_or("unknown"),
+            error_message = err.message().unwrap_or_default(),
+            "dynamodb call failed"
+        ),
+    }
+}
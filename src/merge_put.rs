@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::Serialize;
+
+use crate::{
+    instrument::Operation, limits::validate_item, table::TableBoundStore, update::UpdateExpression,
+    Error,
+};
+
+impl TableBoundStore {
+    /// Upserts `item` attribute by attribute, keeping stored attributes that `item`
+    /// does not carry (unlike [`put`](Self::put), which replaces the whole item).
+    ///
+    /// Null attributes are not written.
+    pub async fn merge_put<T: Serialize>(&self, item: &T) -> Result<(), Error> {
+        self.run(|| async {
+            let mut attributes = self.to_item(item)?;
+            validate_item(&attributes)?;
+
+            let mut key = HashMap::new();
+            for name in self.key_names().await? {
+                let value = attributes.remove(name).ok_or_else(|| {
+                    Error::Validation(format!("item is missing key attribute {name}"))
+                })?;
+                key.insert(name.clone(), value);
+            }
+
+            let mut update = UpdateExpression::default();
+            for (name, value) in attributes {
+                if !matches!(value, AttributeValue::Null(_)) {
+                    update.set(&name, value);
+                }
+            }
+
+            if update.is_empty() {
+                // An UpdateItem without expression creates a key-only item and leaves
+                // an existing one as it is.
+                let operation = Operation::new("UpdateItem", self.table_name()).attributes(&key);
+                let request = self
+                    .store()
+                    .client()
+                    .update_item()
+                    .table_name(self.table_name())
+                    .set_key(Some(key))
+                    .send();
+                operation.send(request).await?;
+                return Ok(());
+            }
+            self.store()
+                .send_update(self.table_name(), key, update)
+                .await
+        })
+        .await
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    io::Write,
+    sync::RwLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Map, Value};
+
+static EMF: RwLock<Option<EmfConfig>> = RwLock::new(None);
+
+#[derive(Debug, Clone)]
+pub struct EmfConfig {
+    pub namespace: String,
+    pub dimensions: Vec<(String, String)>,
+}
+
+impl EmfConfig {
+    pub fn new(namespace: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            dimensions: Vec::new(),
+        }
+    }
+
+    pub fn dimension(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.dimensions.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Writes CloudWatch Embedded Metric Format lines to stdout for every store call.
+pub fn enable_emf(config: EmfConfig) {
+    *EMF.write().unwrap_or_else(|err| err.into_inner()) = Some(config);
+}
+
+pub fn disable_emf() {
+    *EMF.write().unwrap_or_else(|err| err.into_inner()) = None;
+}
+
+pub(crate) fn record_operation(operation: &str, table: &str, latency: Duration, throttled: bool) {
+    emit(
+        operation,
+        table,
+        &[
+            ("Latency", latency.as_secs_f64() * 1000.0, "Milliseconds"),
+            ("Throttles", if throttled { 1.0 } else { 0.0 }, "Count"),
+        ],
+    );
+}
+
+pub(crate) fn record_batch_failures(operation: &str, table: &str, failed: usize) {
+    emit(
+        operation,
+        table,
+        &[("BatchFailures", failed as f64, "Count")],
+    );
+}
+
+pub(crate) fn record_throttling_hint(job: &str, table: &str) {
+    emit(job, table, &[("ThrottlingHints", 1.0, "Count")]);
+}
+
+fn emit(operation: &str, table: &str, metrics: &[(&str, f64, &str)]) {
+    let guard = EMF.read().unwrap_or_else(|err| err.into_inner());
+    let Some(config) = guard.as_ref() else {
+        return;
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    let mut dimension_names = vec!["TableName".to_string(), "Operation".to_string()];
+    let mut record = Map::new();
+    record.insert("TableName".into(), Value::from(table));
+    record.insert("Operation".into(), Value::from(operation));
+    for (name, value) in &config.dimensions {
+        dimension_names.push(name.clone());
+        record.insert(name.clone(), Value::from(value.as_str()));
+    }
+
+    let definitions: Vec<Value> = metrics
+        .iter()
+        .map(|(name, _, unit)| json!({ "Name": name, "Unit": unit }))
+        .collect();
+    for (name, value, _) in metrics {
+        record.insert((*name).into(), Value::from(*value));
+    }
+
+    record.insert(
+        "_aws".into(),
+        json!({
+            "Timestamp": timestamp,
+            "CloudWatchMetrics": [{
+                "Namespace": config.namespace,
+                "Dimensions": [dimension_names],
+                "Metrics": definitions,
+            }],
+        }),
+    );
+
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", Value::Object(record));
+}
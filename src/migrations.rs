@@ -0,0 +1,295 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+use crate::{
+    expression::CheckedExpressions,
+    instrument::Operation,
+    schema::{Gsi, ScalarType, TableSchema},
+    CopyTableOptions, DynamoDbStore, Error,
+};
+
+const VERSION_ATTRIBUTE: &str = "version";
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub type MigrationFuture<'a> = Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>>;
+
+type Transform = Arc<
+    dyn Fn(HashMap<String, AttributeValue>) -> Option<HashMap<String, AttributeValue>>
+        + Send
+        + Sync,
+>;
+type Custom = Arc<dyn for<'a> Fn(&'a DynamoDbStore) -> MigrationFuture<'a> + Send + Sync>;
+
+#[derive(Clone)]
+enum Step {
+    CreateTable {
+        table_name: String,
+        schema: TableSchema,
+    },
+    AddGsi {
+        table_name: String,
+        gsi: Gsi,
+    },
+    EnableTtl {
+        table_name: String,
+        attribute_name: String,
+    },
+    Backfill {
+        table_name: String,
+        transform: Transform,
+    },
+    Custom(Custom),
+}
+
+#[derive(Clone)]
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    step: Step,
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Migration {
+    fn new(version: u64, name: impl Into<String>, step: Step) -> Self {
+        Self {
+            version,
+            name: name.into(),
+            step,
+        }
+    }
+
+    pub fn create_table(
+        version: u64,
+        name: impl Into<String>,
+        table_name: impl Into<String>,
+        schema: TableSchema,
+    ) -> Self {
+        let table_name = table_name.into();
+        Self::new(version, name, Step::CreateTable { table_name, schema })
+    }
+
+    pub fn add_gsi(
+        version: u64,
+        name: impl Into<String>,
+        table_name: impl Into<String>,
+        gsi: Gsi,
+    ) -> Self {
+        let table_name = table_name.into();
+        Self::new(version, name, Step::AddGsi { table_name, gsi })
+    }
+
+    pub fn enable_ttl(
+        version: u64,
+        name: impl Into<String>,
+        table_name: impl Into<String>,
+        attribute_name: impl Into<String>,
+    ) -> Self {
+        let table_name = table_name.into();
+        let attribute_name = attribute_name.into();
+        Self::new(
+            version,
+            name,
+            Step::EnableTtl {
+                table_name,
+                attribute_name,
+            },
+        )
+    }
+
+    /// Rewrites every item of the table through `transform`; `None` leaves the item untouched.
+    pub fn backfill(
+        version: u64,
+        name: impl Into<String>,
+        table_name: impl Into<String>,
+        transform: impl Fn(HashMap<String, AttributeValue>) -> Option<HashMap<String, AttributeValue>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let table_name = table_name.into();
+        let transform = Arc::new(transform);
+        Self::new(
+            version,
+            name,
+            Step::Backfill {
+                table_name,
+                transform,
+            },
+        )
+    }
+
+    pub fn custom(
+        version: u64,
+        name: impl Into<String>,
+        run: impl for<'a> Fn(&'a DynamoDbStore) -> MigrationFuture<'a> + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(version, name, Step::Custom(Arc::new(run)))
+    }
+
+    async fn apply(&self, store: &DynamoDbStore) -> Result<(), Error> {
+        match &self.step {
+            Step::CreateTable { table_name, schema } => {
+                store.create_table_from_schema(table_name, schema).await?;
+                store.wait_until_active(table_name, WAIT_TIMEOUT).await
+            }
+            Step::AddGsi { table_name, gsi } => {
+                store.add_gsi(table_name, gsi.clone()).await?;
+                store
+                    .wait_for_gsi_backfill(table_name, &gsi.name, WAIT_TIMEOUT)
+                    .await
+            }
+            Step::EnableTtl {
+                table_name,
+                attribute_name,
+            } => store.enable_ttl(table_name, attribute_name).await,
+            Step::Backfill {
+                table_name,
+                transform,
+            } => {
+                let transform = transform.clone();
+                let options = CopyTableOptions::default().transform(move |item| transform(item));
+                store.copy_table(table_name, table_name, options).await?;
+                Ok(())
+            }
+            Step::Custom(run) => run(store).await,
+        }
+    }
+}
+
+/// Applies pending migrations in version order and records them in a bookkeeping table.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    store: DynamoDbStore,
+    table_name: String,
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    pub fn new(store: &DynamoDbStore, table_name: impl Into<String>) -> Self {
+        Self {
+            store: store.clone(),
+            table_name: table_name.into(),
+            migrations: Vec::new(),
+        }
+    }
+
+    pub fn migration(mut self, migration: Migration) -> Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    pub async fn applied(&self) -> Result<BTreeSet<u64>, Error> {
+        let items = self
+            .store
+            .scan(&self.table_name)
+            .projection([VERSION_ATTRIBUTE])
+            .consistent_read(true)
+            .all()
+            .await?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| match item.get(VERSION_ATTRIBUTE) {
+                Some(AttributeValue::N(version)) => version.parse().ok(),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Returns the versions that were applied by this run.
+    pub async fn run(&self) -> Result<Vec<u64>, Error> {
+        let mut migrations: Vec<&Migration> = self.migrations.iter().collect();
+        migrations.sort_by_key(|migration| migration.version);
+        if let Some(pair) = migrations
+            .windows(2)
+            .find(|pair| pair[0].version == pair[1].version)
+        {
+            return Err(Error::Validation(format!(
+                "duplicate migration version {}",
+                pair[0].version
+            )));
+        }
+
+        self.ensure_bookkeeping_table().await?;
+        let applied = self.applied().await?;
+
+        let mut newly_applied = Vec::new();
+        for migration in migrations {
+            if applied.contains(&migration.version) {
+                continue;
+            }
+
+            migration.apply(&self.store).await?;
+            if self.record(migration).await? {
+                newly_applied.push(migration.version);
+            }
+        }
+
+        Ok(newly_applied)
+    }
+
+    async fn ensure_bookkeeping_table(&self) -> Result<(), Error> {
+        let schema = TableSchema::new(VERSION_ATTRIBUTE, ScalarType::N);
+
+        match self
+            .store
+            .create_table_from_schema(&self.table_name, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => {}
+            Err(err) => return Err(err),
+        }
+
+        self.store
+            .wait_until_active(&self.table_name, WAIT_TIMEOUT)
+            .await
+    }
+
+    /// Returns `false` when another runner recorded the same version first.
+    async fn record(&self, migration: &Migration) -> Result<bool, Error> {
+        let applied_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let operation = Operation::new("PutItem", &self.table_name);
+        let request = self
+            .store
+            .client()
+            .put_item()
+            .table_name(&self.table_name)
+            .item(
+                VERSION_ATTRIBUTE,
+                AttributeValue::N(migration.version.to_string()),
+            )
+            .item("name", AttributeValue::S(migration.name.clone()))
+            .item("applied_at", AttributeValue::N(applied_at.to_string()))
+            .condition_expression("attribute_not_exists(#version)")
+            .expression_attribute_names("#version", VERSION_ATTRIBUTE)
+            .checked()?
+            .send();
+
+        match operation.send(request).await.map_err(Error::from) {
+            Ok(_) => Ok(true),
+            Err(err) if err.is_conditional_check_failed() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
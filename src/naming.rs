@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+
+/// How attribute names are spelled in the table.
+///
+/// Items are serialized with their Rust field names and renamed on the way out; stored
+/// names are turned back into `snake_case` on the way in. Only top-level attributes are
+/// renamed, so nested structs and map keys keep their names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NamingConvention {
+    /// Writes the names serde produced.
+    #[default]
+    AsIs,
+    /// `createdAt`
+    CamelCase,
+    /// `created_at`
+    SnakeCase,
+    /// `CreatedAt`
+    PascalCase,
+}
+
+impl NamingConvention {
+    /// Spells `name` in this convention, e.g. `created_at` becomes `createdAt`.
+    pub fn rename(&self, name: &str) -> String {
+        let words = words(name);
+        match self {
+            Self::AsIs => name.to_string(),
+            Self::SnakeCase => words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+            Self::PascalCase => words.iter().map(|word| capitalize(word)).collect(),
+            Self::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| {
+                    if index == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Renames the attributes of a serialized item to their table names.
+    pub(crate) fn rename_item(
+        &self,
+        item: HashMap<String, AttributeValue>,
+    ) -> HashMap<String, AttributeValue> {
+        if *self == Self::AsIs {
+            return item;
+        }
+
+        item.into_iter()
+            .map(|(name, value)| (self.rename(&name), value))
+            .collect()
+    }
+
+    /// Renames the attributes of a stored item back to Rust field names.
+    pub(crate) fn restore_item(
+        &self,
+        item: HashMap<String, AttributeValue>,
+    ) -> HashMap<String, AttributeValue> {
+        if matches!(self, Self::AsIs | Self::SnakeCase) {
+            return item;
+        }
+
+        item.into_iter()
+            .map(|(name, value)| (Self::SnakeCase.rename(&name), value))
+            .collect()
+    }
+}
+
+/// Splits `name` at underscores, dashes and lower-to-upper case changes, so
+/// `created_at`, `createdAt` and `CreatedAt` all give `created`, `at`.
+fn words(name: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for part in name.split(['_', '-']).filter(|part| !part.is_empty()) {
+        let mut start = 0;
+        let mut previous: Option<char> = None;
+        for (index, c) in part.char_indices() {
+            if c.is_uppercase()
+                && previous.is_some_and(|previous| previous.is_lowercase() || previous.is_numeric())
+            {
+                words.push(&part[start..index]);
+                start = index;
+            }
+            previous = Some(c);
+        }
+        words.push(&part[start..]);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_convention_reads_every_spelling() {
+        for name in ["created_at", "createdAt", "CreatedAt", "created-at"] {
+            assert_eq!(NamingConvention::SnakeCase.rename(name), "created_at");
+            assert_eq!(NamingConvention::CamelCase.rename(name), "createdAt");
+            assert_eq!(NamingConvention::PascalCase.rename(name), "CreatedAt");
+        }
+        assert_eq!(NamingConvention::AsIs.rename("created-at"), "created-at");
+    }
+
+    #[test]
+    fn digits_and_acronyms_split_into_words() {
+        assert_eq!(
+            NamingConvention::SnakeCase.rename("address2Line"),
+            "address2_line"
+        );
+        assert_eq!(NamingConvention::CamelCase.rename("user_id"), "userId");
+        assert_eq!(NamingConvention::SnakeCase.rename("userID"), "user_id");
+    }
+
+    #[test]
+    fn items_are_renamed_and_restored_at_the_top_level_only() {
+        let nested = AttributeValue::M(HashMap::from([(
+            "street_name".to_string(),
+            AttributeValue::S("Main St".to_string()),
+        )]));
+        let item = HashMap::from([("home_address".to_string(), nested.clone())]);
+
+        let stored = NamingConvention::PascalCase.rename_item(item.clone());
+
+        assert_eq!(stored, HashMap::from([("HomeAddress".to_string(), nested)]));
+        assert_eq!(NamingConvention::PascalCase.restore_item(stored), item);
+    }
+}
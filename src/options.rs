@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use aws_sdk_dynamodb::{
+    config::{self, timeout::TimeoutConfig},
+    types::{ReturnConsumedCapacity, ReturnValue},
+};
+
+use crate::RetryConfig;
+
+/// Per-call settings for the `*_with_options` methods, so new knobs do not need new
+/// method signatures. Settings that do not apply to an operation are ignored, e.g.
+/// `consistent_read` for a put.
+///
+/// The same type holds the defaults of a store or table; settings left at `None` in a
+/// call fall back to them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestOptions {
+    pub consistent_read: Option<bool>,
+    pub return_values: Option<ReturnValue>,
+    /// Limits the whole call, including the SDK's own retries.
+    pub timeout: Option<Duration>,
+    /// Replaces the client's retry settings for this call.
+    pub retry: Option<RetryConfig>,
+    pub return_consumed_capacity: Option<ReturnConsumedCapacity>,
+    /// Page size of queries and scans.
+    pub limit: Option<i32>,
+    /// Attributes a get returns; all of them when unset.
+    pub projection: Option<Vec<String>>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn consistent_read(mut self, consistent_read: bool) -> Self {
+        self.consistent_read = Some(consistent_read);
+        self
+    }
+
+    pub fn return_values(mut self, return_values: ReturnValue) -> Self {
+        self.return_values = Some(return_values);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    pub fn return_consumed_capacity(mut self, consumed_capacity: ReturnConsumedCapacity) -> Self {
+        self.return_consumed_capacity = Some(consumed_capacity);
+        self
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn projection(mut self, attributes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.projection = Some(attributes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Fills the settings this value leaves unset from `defaults`.
+    pub fn or_defaults(&self, defaults: &RequestOptions) -> RequestOptions {
+        RequestOptions {
+            consistent_read: self.consistent_read.or(defaults.consistent_read),
+            return_values: self
+                .return_values
+                .clone()
+                .or_else(|| defaults.return_values.clone()),
+            timeout: self.timeout.or(defaults.timeout),
+            retry: self.retry.or(defaults.retry),
+            return_consumed_capacity: self
+                .return_consumed_capacity
+                .clone()
+                .or_else(|| defaults.return_consumed_capacity.clone()),
+            limit: self.limit.or(defaults.limit),
+            projection: self
+                .projection
+                .clone()
+                .or_else(|| defaults.projection.clone()),
+        }
+    }
+
+    /// Client configuration to override for this call, if any.
+    pub(crate) fn config_override(&self) -> Option<config::Builder> {
+        if self.timeout.is_none() && self.retry.is_none() {
+            return None;
+        }
+
+        let mut builder = config::Builder::default();
+        if let Some(timeout) = self.timeout {
+            builder.set_timeout_config(Some(
+                TimeoutConfig::builder().operation_timeout(timeout).build(),
+            ));
+        }
+        if let Some(retry) = self.retry {
+            builder.set_retry_config(Some(
+                config::retry::RetryConfig::standard()
+                    .with_max_attempts(retry.max_attempts)
+                    .with_initial_backoff(retry.initial_backoff)
+                    .with_max_backoff(retry.max_backoff),
+            ));
+        }
+        Some(builder)
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::{stream, Stream, TryStreamExt};
+
+use crate::{api::StoreFuture, Error};
+
+#[derive(Debug, Clone, Default)]
+pub struct Page {
+    pub items: Vec<HashMap<String, AttributeValue>>,
+    pub last_evaluated_key: Option<HashMap<String, AttributeValue>>,
+    pub scanned_count: i32,
+}
+
+impl Page {
+    pub fn is_last(&self) -> bool {
+        self.last_evaluated_key.is_none()
+    }
+}
+
+/// Every item of an auto-paginated query or scan, with what it took to read them.
+#[derive(Debug, Clone, Default)]
+pub struct AllPages {
+    pub items: Vec<HashMap<String, AttributeValue>>,
+    /// Number of items returned by each page, in order; its length is the page count.
+    pub items_per_page: Vec<usize>,
+    /// Items read before filtering, summed over all pages. Read capacity is charged for
+    /// these, not for `items`.
+    pub scanned_count: i64,
+}
+
+impl AllPages {
+    pub fn pages(&self) -> usize {
+        self.items_per_page.len()
+    }
+
+    /// Adds `page` and returns the key to continue after, if there is one.
+    pub(crate) fn push(&mut self, page: Page) -> Option<HashMap<String, AttributeValue>> {
+        self.items_per_page.push(page.items.len());
+        self.scanned_count += i64::from(page.scanned_count);
+        self.items.extend(page.items);
+        page.last_evaluated_key
+    }
+}
+
+/// Where the next page of a query or scan starts and how many items it may evaluate.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cursor {
+    pub(crate) start_key: Option<HashMap<String, AttributeValue>>,
+    pub(crate) limit: Option<i32>,
+}
+
+/// Streams the pages `fetch` returns from `cursor` on; the next request is only sent when
+/// the stream is polled.
+pub(crate) fn stream_pages<'a, F>(
+    fetch: F,
+    cursor: Cursor,
+) -> impl Stream<Item = Result<Page, Error>> + 'a
+where
+    F: Fn(Cursor) -> StoreFuture<'a, Page> + 'a,
+{
+    stream::try_unfold(Some(cursor), move |cursor| {
+        let next_page = cursor.map(|cursor| (cursor.limit, fetch(cursor)));
+        async move {
+            let Some((limit, next_page)) = next_page else {
+                return Ok(None);
+            };
+
+            let page = next_page.await?;
+            let next = page.last_evaluated_key.clone().map(|start_key| Cursor {
+                start_key: Some(start_key),
+                limit,
+            });
+            Ok(Some((page, next)))
+        }
+    })
+}
+
+/// Streams at most `n` items, lowering the limit of each page to the items still missing.
+pub(crate) fn stream_items<'a, F>(
+    fetch: F,
+    cursor: Cursor,
+    n: usize,
+) -> impl Stream<Item = Result<HashMap<String, AttributeValue>, Error>> + 'a
+where
+    F: Fn(Cursor) -> StoreFuture<'a, Page> + 'a,
+{
+    stream::try_unfold((Some(cursor), n), move |(cursor, remaining)| {
+        let limit = cursor.as_ref().and_then(|cursor| cursor.limit);
+        let next_page = cursor.filter(|_| remaining > 0).map(|cursor| {
+            fetch(Cursor {
+                limit: Some(bounded_limit(cursor.limit, remaining)),
+                ..cursor
+            })
+        });
+        async move {
+            let Some(next_page) = next_page else {
+                return Ok(None);
+            };
+
+            let page = next_page.await?;
+            let remaining = remaining.saturating_sub(page.items.len());
+            let next = page.last_evaluated_key.map(|start_key| Cursor {
+                start_key: Some(start_key),
+                limit,
+            });
+            Ok::<_, Error>(Some((
+                stream::iter(page.items.into_iter().map(Ok)),
+                (next, remaining),
+            )))
+        }
+    })
+    .try_flatten()
+}
+
+/// Reads up to `max` items into one page whose `last_evaluated_key` continues after the
+/// last of them.
+pub(crate) async fn collect_items<'a, F>(
+    fetch: F,
+    mut cursor: Cursor,
+    max: usize,
+) -> Result<Page, Error>
+where
+    F: Fn(Cursor) -> StoreFuture<'a, Page> + 'a,
+{
+    // An empty page without a key would claim that nothing follows.
+    if max == 0 {
+        return Err(Error::Validation(
+            "collect_limited needs a max of at least 1".to_string(),
+        ));
+    }
+
+    let limit = cursor.limit;
+    let mut collected = Page::default();
+    while collected.items.len() < max {
+        cursor.limit = Some(bounded_limit(limit, max - collected.items.len()));
+        let page = fetch(cursor.clone()).await?;
+        collected.items.extend(page.items);
+        collected.scanned_count = collected.scanned_count.saturating_add(page.scanned_count);
+        collected.last_evaluated_key = page.last_evaluated_key.clone();
+
+        match page.last_evaluated_key {
+            Some(start_key) => cursor.start_key = Some(start_key),
+            None => break,
+        }
+    }
+
+    Ok(collected)
+}
+
+/// Reads every page from `cursor` on.
+pub(crate) async fn read_all_pages<'a, F>(fetch: F, mut cursor: Cursor) -> Result<AllPages, Error>
+where
+    F: Fn(Cursor) -> StoreFuture<'a, Page> + 'a,
+{
+    let mut all = AllPages::default();
+
+    loop {
+        let page = fetch(cursor.clone()).await?;
+
+        match all.push(page) {
+            Some(start_key) => cursor.start_key = Some(start_key),
+            None => return Ok(all),
+        }
+    }
+}
+
+/// The `Limit` for the next page of a bounded read: the configured limit, lowered to the
+/// number of items still missing. `Limit` caps the items a page evaluates, so the page
+/// never returns more than were asked for.
+pub(crate) fn bounded_limit(limit: Option<i32>, remaining: usize) -> i32 {
+    let remaining = i32::try_from(remaining).unwrap_or(i32::MAX);
+    limit.map_or(remaining, |limit| limit.min(remaining))
+}
+
+pub(crate) fn projection_expression(
+    attributes: &[String],
+    names: &mut HashMap<String, String>,
+) -> Option<String> {
+    if attributes.is_empty() {
+        return None;
+    }
+
+    let placeholders: Vec<String> = attributes
+        .iter()
+        .enumerate()
+        .map(|(index, attribute)| {
+            let placeholder = format!("#p{index}");
+            names.insert(placeholder.clone(), attribute.clone());
+            placeholder
+        })
+        .collect();
+
+    Some(placeholders.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    const TOTAL: usize = 10;
+    const PAGE_SIZE: usize = 4;
+
+    fn item(index: usize) -> HashMap<String, AttributeValue> {
+        HashMap::from([("n".to_string(), AttributeValue::N(index.to_string()))])
+    }
+
+    fn index(item: &HashMap<String, AttributeValue>) -> usize {
+        match &item["n"] {
+            AttributeValue::N(n) => n.parse().unwrap(),
+            other => panic!("unexpected index {other:?}"),
+        }
+    }
+
+    /// Serves items `0..TOTAL` in pages of at most `PAGE_SIZE` and records the limit
+    /// of every request.
+    fn fetcher(
+        limits: Arc<Mutex<Vec<Option<i32>>>>,
+    ) -> impl Fn(Cursor) -> StoreFuture<'static, Page> {
+        move |cursor: Cursor| -> StoreFuture<'static, Page> {
+            limits.lock().unwrap().push(cursor.limit);
+            let start = cursor.start_key.as_ref().map_or(0, |key| index(key) + 1);
+            let size = cursor.limit.map_or(PAGE_SIZE, |limit| limit as usize);
+            let end = (start + size.min(PAGE_SIZE)).min(TOTAL);
+            let page = Page {
+                items: (start..end).map(item).collect(),
+                last_evaluated_key: (end < TOTAL).then(|| item(end - 1)),
+                scanned_count: (end - start) as i32,
+            };
+            Box::pin(async move { Ok(page) })
+        }
+    }
+
+    #[tokio::test]
+    async fn all_pages_are_read_and_counted() {
+        let limits = Arc::default();
+
+        let all = read_all_pages(fetcher(limits), Cursor::default())
+            .await
+            .unwrap();
+
+        assert_eq!(all.items.len(), TOTAL);
+        assert_eq!(all.items_per_page, [4, 4, 2]);
+        assert_eq!(all.pages(), 3);
+        assert_eq!(all.scanned_count, 10);
+    }
+
+    #[tokio::test]
+    async fn streamed_pages_follow_the_last_key() {
+        let limits = Arc::default();
+
+        let pages: Vec<Page> = stream_pages(fetcher(limits), Cursor::default())
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(pages.len(), 3);
+        assert!(pages[2].is_last());
+    }
+
+    #[tokio::test]
+    async fn taking_items_lowers_the_limit_to_what_is_missing() {
+        let limits = Arc::new(Mutex::new(Vec::new()));
+
+        let items: Vec<_> = stream_items(fetcher(limits.clone()), Cursor::default(), 6)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            items.iter().map(index).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(*limits.lock().unwrap(), [Some(6), Some(2)]);
+    }
+
+    #[tokio::test]
+    async fn collected_items_continue_after_the_last_one() {
+        let limits = Arc::default();
+        let cursor = Cursor {
+            start_key: Some(item(1)),
+            limit: Some(3),
+        };
+
+        let page = collect_items(fetcher(limits), cursor, 5).await.unwrap();
+
+        assert_eq!(
+            page.items.iter().map(index).collect::<Vec<_>>(),
+            [2, 3, 4, 5, 6]
+        );
+        assert_eq!(page.last_evaluated_key, Some(item(6)));
+        assert_eq!(page.scanned_count, 5);
+    }
+
+    #[tokio::test]
+    async fn collecting_nothing_is_rejected() {
+        let limits = Arc::new(Mutex::new(Vec::new()));
+
+        let result = collect_items(fetcher(limits.clone()), Cursor::default(), 0).await;
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert!(limits.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn bounded_limits_never_exceed_the_configured_one() {
+        assert_eq!(bounded_limit(None, 7), 7);
+        assert_eq!(bounded_limit(Some(5), 7), 5);
+        assert_eq!(bounded_limit(Some(5), 2), 2);
+        assert_eq!(bounded_limit(None, usize::MAX), i32::MAX);
+    }
+}
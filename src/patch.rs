@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::Serialize;
+
+use crate::{update::UpdateExpression, DynamoDbStore, Error};
+
+impl DynamoDbStore {
+    /// Sets every non-null attribute of `partial` on the item at `key`.
+    ///
+    /// Null attributes (`None` fields) are left untouched, as are attributes that are
+    /// part of the key.
+    pub async fn patch<T: Serialize>(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        partial: &T,
+    ) -> Result<(), Error> {
+        self.patch_item(table_name, key, partial, false).await
+    }
+
+    /// Like [`patch`](Self::patch), but removes the attributes that are null in `partial`.
+    ///
+    /// Skip fields that should stay untouched with
+    /// `#[serde(skip_serializing_if = "Option::is_none")]`.
+    pub async fn patch_removing_nulls<T: Serialize>(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        partial: &T,
+    ) -> Result<(), Error> {
+        self.patch_item(table_name, key, partial, true).await
+    }
+
+    async fn patch_item<T: Serialize>(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        partial: &T,
+        remove_nulls: bool,
+    ) -> Result<(), Error> {
+        let attributes: HashMap<String, AttributeValue> = serde_dynamo::to_item(partial)?;
+
+        let mut update = UpdateExpression::default();
+        for (name, value) in attributes {
+            if key.contains_key(&name) {
+                continue;
+            }
+
+            match value {
+                AttributeValue::Null(_) if remove_nulls => update.remove(&name),
+                AttributeValue::Null(_) => {}
+                value => update.set(&name, value),
+            }
+        }
+
+        self.send_update(table_name, key, update).await
+    }
+}
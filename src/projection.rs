@@ -0,0 +1,91 @@
+use serde::{
+    de::{self, value, DeserializeOwned, Visitor},
+    forward_to_deserialize_any, Deserializer, Serialize,
+};
+
+use crate::{table::TableBoundStore, typed_table::TypedTableStore, Error};
+
+/// The field names `T` deserializes from, read from its `Deserialize` impl, so
+/// `#[serde(rename)]` and `#[serde(skip)]` are honored.
+///
+/// Fails for types that are not structs with named fields, including structs with
+/// `#[serde(flatten)]` fields, whose attributes are not known up front.
+pub fn projection_fields<T: DeserializeOwned>() -> Result<&'static [&'static str], Error> {
+    let mut fields = None;
+    let _ = T::deserialize(FieldNames {
+        fields: &mut fields,
+    });
+
+    fields.ok_or_else(|| {
+        Error::Validation(format!(
+            "{} is not a struct with named fields and cannot be used as a projection",
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+impl TableBoundStore {
+    /// Reads only the attributes that `T` has fields for, e.g. a small summary of a wide
+    /// item, which costs less read capacity than fetching the whole item.
+    pub async fn get_projection<K: Serialize, T: DeserializeOwned>(
+        &self,
+        key: &K,
+    ) -> Result<Option<T>, Error> {
+        let attributes = projection_fields::<T>()?
+            .iter()
+            .map(|field| self.naming().rename(field))
+            .collect::<Vec<_>>();
+        let options = self.defaults().clone().projection(attributes);
+
+        self.run(|| async {
+            let key = self.to_key(key)?;
+            let output = self
+                .store()
+                .get_item_with_options(self.table_name(), key, &options)
+                .await?;
+            output.item.map(|item| self.read_item(item)).transpose()
+        })
+        .await
+    }
+}
+
+impl<T, K> TypedTableStore<T, K>
+where
+    T: Serialize + DeserializeOwned,
+    K: Serialize,
+{
+    /// Reads a reduced view `P` of the item at `key`; see
+    /// [`TableBoundStore::get_projection`].
+    pub async fn get_projection<P: DeserializeOwned>(&self, key: &K) -> Result<Option<P>, Error> {
+        self.table().get_projection(key).await
+    }
+}
+
+/// A deserializer that only records the field list a struct asks for, then fails.
+struct FieldNames<'a> {
+    fields: &'a mut Option<&'static [&'static str]>,
+}
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = Some(fields);
+        Err(de::Error::custom("field names recorded"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+        identifier ignored_any
+    }
+}
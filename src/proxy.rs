@@ -0,0 +1,95 @@
+use std::fmt;
+
+use aws_config::BehaviorVersion;
+use aws_sdk_dynamodb::config::SharedHttpClient;
+use aws_smithy_http_client::{
+    proxy::ProxyConfig,
+    tls::{rustls_provider::CryptoMode, Provider},
+    Builder, Connector,
+};
+
+use crate::{DynamoDbStore, Error};
+
+/// An HTTP(S) proxy for all requests of a store, including the credential providers'.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProxySettings {
+    url: String,
+    no_proxy: Vec<String>,
+    basic_auth: Option<(String, String)>,
+}
+
+impl fmt::Debug for ProxySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxySettings")
+            .field("url", &self.url)
+            .field("no_proxy", &self.no_proxy)
+            .field(
+                "basic_auth",
+                &self.basic_auth.as_ref().map(|(username, _)| username),
+            )
+            .finish()
+    }
+}
+
+impl ProxySettings {
+    /// Sends both HTTP and HTTPS requests through the proxy at `url`, e.g.
+    /// `http://proxy.corp.example:3128`.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            no_proxy: Vec::new(),
+            basic_auth: None,
+        }
+    }
+
+    /// Hosts to connect to directly, in `NO_PROXY` syntax: host names, `*.suffix`
+    /// patterns, IP addresses or CIDR ranges.
+    pub fn no_proxy(mut self, hosts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.no_proxy.extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn basic_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.basic_auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// An HTTP client that connects through the proxy, for building an `SdkConfig` by
+    /// hand with `http_client`.
+    pub fn http_client(&self) -> Result<SharedHttpClient, Error> {
+        let mut proxy = ProxyConfig::all(self.url.as_str())
+            .map_err(|err| Error::Validation(format!("invalid proxy {}: {err}", self.url)))?;
+        if let Some((username, password)) = &self.basic_auth {
+            proxy = proxy.with_basic_auth(username, password);
+        }
+        if !self.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(self.no_proxy.join(","));
+        }
+
+        Ok(
+            Builder::new().build_with_connector_fn(move |settings, components| {
+                let mut connector = Connector::builder().proxy_config(proxy.clone());
+                connector.set_connector_settings(settings.cloned());
+                if let Some(components) = components {
+                    connector.set_sleep_impl(components.sleep_impl());
+                }
+                connector
+                    .tls_provider(Provider::Rustls(CryptoMode::AwsLc))
+                    .build()
+            }),
+        )
+    }
+}
+
+impl DynamoDbStore {
+    /// Like [`new`](Self::new), but connects through `proxy`, for networks that cannot
+    /// reach DynamoDB directly.
+    pub async fn new_with_proxy(proxy: &ProxySettings) -> Result<Self, Error> {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .http_client(proxy.http_client()?)
+            .load()
+            .await;
+
+        Ok(Self::from_config(&config))
+    }
+}
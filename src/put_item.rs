@@ -1,13 +1,76 @@
 use aws_sdk_dynamodb::{operation::put_item::PutItemOutput, types::AttributeValue};
 use std::collections::HashMap;
 
+use crate::{
+    api::render_condition, expression::CheckedExpressions, filter::Filter, global::global_store,
+    instrument::Operation, limits::validate_item, options::RequestOptions, DynamoDbStore, Error,
+};
+
+impl DynamoDbStore {
+    pub async fn put_item(
+        &self,
+        table_name: &str,
+        item: HashMap<String, AttributeValue>,
+    ) -> Result<PutItemOutput, Error> {
+        self.put_item_with_options(table_name, item, self.defaults())
+            .await
+    }
+
+    pub async fn put_item_with_options(
+        &self,
+        table_name: &str,
+        item: HashMap<String, AttributeValue>,
+        options: &RequestOptions,
+    ) -> Result<PutItemOutput, Error> {
+        self.put_item_if(table_name, item, None, options).await
+    }
+
+    /// Like [`put_item_with_options`](Self::put_item_with_options), failing with a
+    /// conditional check error if `condition` does not hold for the stored item.
+    pub(crate) async fn put_item_if(
+        &self,
+        table_name: &str,
+        item: HashMap<String, AttributeValue>,
+        condition: Option<Filter>,
+        options: &RequestOptions,
+    ) -> Result<PutItemOutput, Error> {
+        let options = options.or_defaults(self.defaults());
+        validate_item(&item)?;
+        let operation = Operation::new("PutItem", table_name).attributes(&item);
+        let (expression, names, values) = render_condition(condition);
+
+        let request = self
+            .client()
+            .put_item()
+            .table_name(table_name)
+            .set_item(Some(item))
+            .set_condition_expression(expression)
+            .set_expression_attribute_names(names)
+            .set_expression_attribute_values(values)
+            .set_return_values(options.return_values.clone())
+            .set_return_consumed_capacity(options.return_consumed_capacity.clone())
+            .checked()?;
+        let request = async {
+            match options.config_override() {
+                Some(config) => request.customize().config_override(config).send().await,
+                None => request.send().await,
+            }
+        };
+        let result = operation.send(request).await?;
+
+        Ok(result)
+    }
+}
+
+/// Sends a plain PutItem through the global store, keeping the SDK error type. Use
+/// [`DynamoDbStore::put_item`] for item validation, request defaults and [`Error`].
 pub async fn put_item(
     table_name: &str,
     item: HashMap<String, AttributeValue>,
 ) -> Result<PutItemOutput, aws_sdk_dynamodb::Error> {
-    let config = aws_config::load_from_env().await;
-
-    let result = aws_sdk_dynamodb::Client::new(&config)
+    let result = global_store()
+        .await
+        .client()
         .put_item()
         .table_name(table_name)
         .set_item(Some(item))
@@ -0,0 +1,49 @@
+use futures_util::{stream, StreamExt};
+
+use crate::{
+    api::{DynamoStoreApi, Item},
+    filter::Filter,
+    DynamoDbStore, Error,
+};
+
+/// What became of one put of [`DynamoDbStore::put_many_conditional`].
+#[derive(Debug)]
+pub enum PutOutcome {
+    Written,
+    /// The condition did not hold, e.g. the item already existed; nothing was written.
+    ConditionFailed,
+    Failed(Error),
+}
+
+impl PutOutcome {
+    pub fn is_written(&self) -> bool {
+        matches!(self, Self::Written)
+    }
+}
+
+impl DynamoDbStore {
+    /// Puts each item with its own condition, running up to `concurrency` puts at once,
+    /// and returns one outcome per item in input order.
+    ///
+    /// BatchWriteItem cannot carry conditions, so this sends one PutItem per item. A
+    /// failed put does not stop the others. With `Filter::not_exists` on the partition
+    /// key, re-running an import leaves already imported items untouched.
+    pub async fn put_many_conditional(
+        &self,
+        table_name: &str,
+        puts: Vec<(Item, Filter)>,
+        concurrency: usize,
+    ) -> Vec<PutOutcome> {
+        stream::iter(puts)
+            .map(|(item, condition)| async move {
+                match DynamoStoreApi::put_item(self, table_name, item, Some(condition)).await {
+                    Ok(()) => PutOutcome::Written,
+                    Err(err) if err.is_conditional_check_failed() => PutOutcome::ConditionFailed,
+                    Err(err) => PutOutcome::Failed(err),
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::Stream;
+
+use crate::{
+    api::StoreFuture,
+    expression::CheckedExpressions,
+    filter::Filter,
+    instrument::Operation,
+    options::RequestOptions,
+    page::{
+        collect_items, projection_expression, read_all_pages, stream_items, stream_pages, AllPages,
+        Cursor, Page,
+    },
+    retry::RetryConfig,
+    DynamoDbStore, Error,
+};
+
+impl DynamoDbStore {
+    pub fn query(&self, table_name: impl Into<String>) -> Query<'_> {
+        Query {
+            store: self,
+            table_name: table_name.into(),
+            index_name: None,
+            partition_key: None,
+            sort_key: None,
+            projection: Vec::new(),
+            filter: None,
+            limit: None,
+            consistent_read: None,
+            default_consistent_read: false,
+            scan_forward: true,
+            exclusive_start_key: None,
+            retry: None,
+        }
+        .defaults(self.defaults())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortKeyCondition {
+    Eq(AttributeValue),
+    Lt(AttributeValue),
+    Le(AttributeValue),
+    Gt(AttributeValue),
+    Ge(AttributeValue),
+    Between(AttributeValue, AttributeValue),
+    BeginsWith(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Query<'a> {
+    store: &'a DynamoDbStore,
+    table_name: String,
+    index_name: Option<String>,
+    partition_key: Option<(String, AttributeValue)>,
+    sort_key: Option<(String, SortKeyCondition)>,
+    projection: Vec<String>,
+    filter: Option<Filter>,
+    limit: Option<i32>,
+    consistent_read: Option<bool>,
+    default_consistent_read: bool,
+    scan_forward: bool,
+    exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    retry: Option<RetryConfig>,
+}
+
+impl<'a> Query<'a> {
+    pub fn index(mut self, index_name: impl Into<String>) -> Self {
+        self.index_name = Some(index_name.into());
+        self
+    }
+
+    pub fn partition_key(mut self, name: impl Into<String>, value: AttributeValue) -> Self {
+        self.partition_key = Some((name.into(), value));
+        self
+    }
+
+    pub fn sort_key(mut self, name: impl Into<String>, condition: SortKeyCondition) -> Self {
+        self.sort_key = Some((name.into(), condition));
+        self
+    }
+
+    pub fn sort_key_begins_with(self, name: impl Into<String>, prefix: impl Into<String>) -> Self {
+        self.sort_key(name, SortKeyCondition::BeginsWith(prefix.into()))
+    }
+
+    pub fn projection(mut self, attributes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.projection = attributes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn consistent_read(mut self, consistent_read: bool) -> Self {
+        self.consistent_read = Some(consistent_read);
+        self
+    }
+
+    /// Applies the page size, read consistency and retry settings of `defaults`. A
+    /// default consistent read only applies to tables, since GSIs do not support it.
+    pub(crate) fn defaults(mut self, defaults: &RequestOptions) -> Self {
+        self.limit = defaults.limit.or(self.limit);
+        self.retry = defaults.retry.or(self.retry);
+        if let Some(consistent_read) = defaults.consistent_read {
+            self.default_consistent_read = consistent_read;
+        }
+        self
+    }
+
+    /// Returns items in descending sort key order.
+    pub fn reverse(mut self) -> Self {
+        self.scan_forward = false;
+        self
+    }
+
+    pub fn start_after(mut self, key: HashMap<String, AttributeValue>) -> Self {
+        self.exclusive_start_key = Some(key);
+        self
+    }
+
+    /// Fetches a single page starting after the configured start key.
+    pub async fn page(&self) -> Result<Page, Error> {
+        let Some((partition_key, partition_value)) = &self.partition_key else {
+            return Err(Error::Validation(
+                "a query needs a partition key value".to_string(),
+            ));
+        };
+
+        let mut names = HashMap::from([("#k0".to_string(), partition_key.clone())]);
+        let mut values = HashMap::from([(":k0".to_string(), partition_value.clone())]);
+        let mut key_condition = "#k0 = :k0".to_string();
+
+        if let Some((sort_key, condition)) = &self.sort_key {
+            names.insert("#k1".to_string(), sort_key.clone());
+            let clause = match condition {
+                SortKeyCondition::Eq(value) => comparison("=", value, &mut values),
+                SortKeyCondition::Lt(value) => comparison("<", value, &mut values),
+                SortKeyCondition::Le(value) => comparison("<=", value, &mut values),
+                SortKeyCondition::Gt(value) => comparison(">", value, &mut values),
+                SortKeyCondition::Ge(value) => comparison(">=", value, &mut values),
+                SortKeyCondition::Between(low, high) => {
+                    values.insert(":k1".to_string(), low.clone());
+                    values.insert(":k2".to_string(), high.clone());
+                    "#k1 BETWEEN :k1 AND :k2".to_string()
+                }
+                SortKeyCondition::BeginsWith(prefix) => {
+                    values.insert(":k1".to_string(), AttributeValue::S(prefix.clone()));
+                    "begins_with(#k1, :k1)".to_string()
+                }
+            };
+            key_condition = format!("{key_condition} AND {clause}");
+        }
+
+        let projection = projection_expression(&self.projection, &mut names);
+        let filter = self
+            .filter
+            .as_ref()
+            .map(|filter| filter.render(&mut names, &mut values));
+
+        let operation = Operation::new("Query", &self.table_name);
+        let request = self
+            .store
+            .read_client()
+            .query()
+            .table_name(&self.table_name)
+            .set_index_name(self.index_name.clone())
+            .key_condition_expression(key_condition)
+            .set_projection_expression(projection)
+            .set_filter_expression(filter)
+            .set_expression_attribute_names(Some(names))
+            .set_expression_attribute_values(Some(values))
+            .set_limit(self.limit)
+            .consistent_read(
+                self.consistent_read
+                    .unwrap_or(self.default_consistent_read && self.index_name.is_none()),
+            )
+            .scan_index_forward(self.scan_forward)
+            .set_exclusive_start_key(self.exclusive_start_key.clone())
+            .checked()?;
+        let config = RequestOptions {
+            retry: self.retry,
+            ..RequestOptions::default()
+        }
+        .config_override();
+        let request = async {
+            match config {
+                Some(config) => request.customize().config_override(config).send().await,
+                None => request.send().await,
+            }
+        };
+        let output = operation.send(request).await?;
+
+        Ok(Page {
+            items: output.items.unwrap_or_default(),
+            last_evaluated_key: output.last_evaluated_key,
+            scanned_count: output.scanned_count,
+        })
+    }
+
+    /// Streams pages lazily; the next request is only sent when the stream is polled.
+    pub fn pages(self) -> impl Stream<Item = Result<Page, Error>> + 'a {
+        let (fetch, cursor) = self.fetcher();
+        stream_pages(fetch, cursor)
+    }
+
+    /// Streams at most `n` items, sending no further requests once they are read.
+    ///
+    /// Each request asks for no more items than are still missing, so a selective filter
+    /// takes more, smaller requests, but nothing is read past the `n`th item.
+    pub fn take_items(
+        self,
+        n: usize,
+    ) -> impl Stream<Item = Result<HashMap<String, AttributeValue>, Error>> + 'a {
+        let (fetch, cursor) = self.fetcher();
+        stream_items(fetch, cursor, n)
+    }
+
+    /// Reads up to `max` items, like [`take_items`](Self::take_items), and returns them
+    /// as one page. Its `last_evaluated_key` is set when more items may follow, and
+    /// passing it to `start_after` continues right after the last returned item.
+    /// Fails with a validation error for a `max` of 0.
+    pub async fn collect_limited(self, max: usize) -> Result<Page, Error> {
+        let (fetch, cursor) = self.fetcher();
+        collect_items(fetch, cursor, max).await
+    }
+
+    pub async fn all(self) -> Result<Vec<HashMap<String, AttributeValue>>, Error> {
+        Ok(self.all_with_stats().await?.items)
+    }
+
+    /// Like [`all`](Self::all), also reporting the page count, the items per page and
+    /// the scanned count, e.g. to estimate what the reads cost. [`pages`](Self::pages)
+    /// gives the raw pages instead.
+    pub async fn all_with_stats(self) -> Result<AllPages, Error> {
+        let (fetch, cursor) = self.fetcher();
+        read_all_pages(fetch, cursor).await
+    }
+
+    /// Fetches the page a cursor points at, starting from this query's own start key
+    /// and limit.
+    fn fetcher(self) -> (impl Fn(Cursor) -> StoreFuture<'a, Page> + 'a, Cursor) {
+        let cursor = Cursor {
+            start_key: self.exclusive_start_key.clone(),
+            limit: self.limit,
+        };
+        let fetch = move |cursor: Cursor| -> StoreFuture<'a, Page> {
+            let mut query = self.clone();
+            query.exclusive_start_key = cursor.start_key;
+            query.limit = cursor.limit;
+            Box::pin(async move { query.page().await })
+        };
+        (fetch, cursor)
+    }
+}
+
+fn comparison(
+    operator: &str,
+    value: &AttributeValue,
+    values: &mut HashMap<String, AttributeValue>,
+) -> String {
+    values.insert(":k1".to_string(), value.clone());
+    format!("#k1 {operator} :k1")
+}
@@ -0,0 +1,497 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    api::{DynamoStoreApi, Item, QueryRequest},
+    filter::Filter,
+    keys::ulid,
+    schema::{ScalarType, TableSchema},
+    DynamoDbStore, Error,
+};
+
+const QUEUE_ATTRIBUTE: &str = "queue";
+const ID_ATTRIBUTE: &str = "message_id";
+const BODY_ATTRIBUTE: &str = "body";
+const VISIBLE_AT_ATTRIBUTE: &str = "visible_at";
+const ATTEMPTS_ATTRIBUTE: &str = "attempts";
+const RECEIPT_ATTRIBUTE: &str = "receipt";
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// An at-least-once queue stored in a DynamoDB table, for workloads too small to
+/// justify SQS.
+///
+/// Each queue is one partition of the table, ordered by ULID message ids. Receiving a
+/// message leases it for the visibility timeout; a message that is not acknowledged in
+/// time becomes visible again, and one that was received `max_attempts` times moves to
+/// the dead-letter queue instead.
+///
+/// The store is any [`DynamoStoreApi`] that keeps attribute names as given, e.g.
+/// [`DynamoDbStore`] or an in-memory store in tests.
+#[derive(Debug, Clone)]
+pub struct Queue<S = DynamoDbStore> {
+    store: S,
+    table_name: String,
+    name: String,
+    visibility_timeout: Duration,
+    max_attempts: u32,
+    dead_letter_name: String,
+}
+
+/// A received message. Pass it to [`Queue::ack`] once it is processed.
+#[derive(Debug, Clone)]
+pub struct Message<T> {
+    pub id: String,
+    /// How often the message has been received, including this time.
+    pub attempts: u32,
+    pub body: T,
+    receipt: String,
+    /// The message as stored by the receive; unchanged while the receipt is held.
+    item: Item,
+}
+
+impl Queue {
+    /// Creates the queue table if it does not exist yet.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let schema =
+            TableSchema::new(QUEUE_ATTRIBUTE, ScalarType::S).sort_key(ID_ATTRIBUTE, ScalarType::S);
+
+        match self
+            .store
+            .create_table_from_schema(&self.table_name, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        self.store
+            .wait_until_active(&self.table_name, WAIT_TIMEOUT)
+            .await
+    }
+}
+
+impl<S: DynamoStoreApi + Clone> Queue<S> {
+    pub fn new(store: &S, table_name: impl Into<String>, name: impl Into<String>) -> Self {
+        let name = name.into();
+
+        Self {
+            store: store.clone(),
+            table_name: table_name.into(),
+            dead_letter_name: format!("{name}-dead-letter"),
+            name,
+            visibility_timeout: Duration::from_secs(30),
+            max_attempts: 5,
+        }
+    }
+
+    pub fn visibility_timeout(mut self, visibility_timeout: Duration) -> Self {
+        self.visibility_timeout = visibility_timeout;
+        self
+    }
+
+    /// Receives after which a message moves to the dead-letter queue.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Names the dead-letter queue in the same table; defaults to `<name>-dead-letter`.
+    pub fn dead_letter_name(mut self, name: impl Into<String>) -> Self {
+        self.dead_letter_name = name.into();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The dead-letter queue, with the same settings, to inspect or redrive messages.
+    pub fn dead_letters(&self) -> Queue<S> {
+        Queue {
+            name: self.dead_letter_name.clone(),
+            dead_letter_name: format!("{}-dead-letter", self.dead_letter_name),
+            ..self.clone()
+        }
+    }
+
+    /// Adds a message and returns its id.
+    pub async fn enqueue<T: Serialize>(&self, body: &T) -> Result<String, Error> {
+        let id = ulid();
+        let item = HashMap::from([
+            (
+                QUEUE_ATTRIBUTE.to_string(),
+                AttributeValue::S(self.name.clone()),
+            ),
+            (ID_ATTRIBUTE.to_string(), AttributeValue::S(id.clone())),
+            (
+                BODY_ATTRIBUTE.to_string(),
+                serde_dynamo::to_attribute_value(body)?,
+            ),
+            (VISIBLE_AT_ATTRIBUTE.to_string(), number(0)),
+            (ATTEMPTS_ATTRIBUTE.to_string(), number(0)),
+        ]);
+
+        self.store.put_item(&self.table_name, item, None).await?;
+        Ok(id)
+    }
+
+    /// Leases up to `max` visible messages, oldest first. Returns fewer, possibly none,
+    /// when the queue holds fewer visible messages.
+    pub async fn receive<T: DeserializeOwned>(&self, max: usize) -> Result<Vec<Message<T>>, Error> {
+        let now = epoch_millis(SystemTime::now());
+        let mut request = QueryRequest::new(
+            &self.table_name,
+            QUEUE_ATTRIBUTE,
+            AttributeValue::S(self.name.clone()),
+        )
+        .filter(Filter::le(VISIBLE_AT_ATTRIBUTE, number(now)))
+        .consistent_read(true);
+        let mut messages = Vec::new();
+
+        while messages.len() < max {
+            let page = self.store.query_page(&request).await?;
+            for item in page.items {
+                if messages.len() == max {
+                    break;
+                }
+                if let Some(message) = self.lease(item).await? {
+                    messages.push(message);
+                }
+            }
+
+            match page.last_evaluated_key {
+                Some(key) => request = request.start_after(key),
+                None => break,
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Deletes a processed message. Returns `false` if its lease ran out and it was
+    /// received again in the meantime, in which case it stays in the queue.
+    pub async fn ack<T>(&self, message: &Message<T>) -> Result<bool, Error> {
+        let condition = holds_receipt(&message.receipt);
+        match self
+            .store
+            .delete_item(&self.table_name, self.key(&message.id), Some(condition))
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(err) if err.is_conditional_check_failed() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Makes a received message visible again after `delay`, e.g. to retry it later
+    /// or to extend the lease while processing takes longer. Returns `false` if the
+    /// lease already ran out.
+    pub async fn change_visibility<T>(
+        &self,
+        message: &Message<T>,
+        delay: Duration,
+    ) -> Result<bool, Error> {
+        let mut item = message.item.clone();
+        item.insert(
+            VISIBLE_AT_ATTRIBUTE.to_string(),
+            number(epoch_millis(visible_after(delay)?)),
+        );
+
+        let condition = holds_receipt(&message.receipt);
+        match self
+            .store
+            .put_item(&self.table_name, item, Some(condition))
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(err) if err.is_conditional_check_failed() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Leases `item` if nobody else did since it was read, and moves it to the
+    /// dead-letter queue instead of returning it once it has used up its attempts.
+    async fn lease<T: DeserializeOwned>(
+        &self,
+        mut item: Item,
+    ) -> Result<Option<Message<T>>, Error> {
+        let (Some(AttributeValue::S(id)), Some(observed), Some(AttributeValue::N(attempts))) = (
+            item.get(ID_ATTRIBUTE).cloned(),
+            item.get(VISIBLE_AT_ATTRIBUTE).cloned(),
+            item.get(ATTEMPTS_ATTRIBUTE),
+        ) else {
+            return Err(Error::UnexpectedResponse {
+                operation: "Query",
+                reason: format!("queue {} holds a malformed message", self.name),
+            });
+        };
+        let attempts: u32 = attempts.parse().unwrap_or_default();
+
+        let receipt = ulid();
+        let visible_at = epoch_millis(visible_after(self.visibility_timeout)?);
+        item.insert(VISIBLE_AT_ATTRIBUTE.to_string(), number(visible_at));
+        item.insert(
+            RECEIPT_ATTRIBUTE.to_string(),
+            AttributeValue::S(receipt.clone()),
+        );
+        item.insert(ATTEMPTS_ATTRIBUTE.to_string(), number(attempts + 1));
+
+        match self
+            .store
+            .put_item(
+                &self.table_name,
+                item.clone(),
+                Some(unchanged_since(observed)),
+            )
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_conditional_check_failed() => return Ok(None),
+            Err(err) => return Err(err),
+        }
+
+        if attempts >= self.max_attempts {
+            self.dead_letter(&id, &receipt, item).await?;
+            return Ok(None);
+        }
+
+        let body = item
+            .get(BODY_ATTRIBUTE)
+            .cloned()
+            .unwrap_or(AttributeValue::Null(true));
+        Ok(Some(Message {
+            id,
+            attempts: attempts + 1,
+            body: serde_dynamo::from_attribute_value(body)?,
+            receipt,
+            item,
+        }))
+    }
+
+    /// Copies a message leased under `receipt` to the dead-letter queue and then
+    /// deletes it. The lease keeps other receivers away meanwhile; if this stops
+    /// halfway, the message becomes visible again and is moved once more, overwriting
+    /// the same copy.
+    async fn dead_letter(&self, id: &str, receipt: &str, mut item: Item) -> Result<(), Error> {
+        item.insert(
+            QUEUE_ATTRIBUTE.to_string(),
+            AttributeValue::S(self.dead_letter_name.clone()),
+        );
+        item.insert(VISIBLE_AT_ATTRIBUTE.to_string(), number(0));
+        item.insert(ATTEMPTS_ATTRIBUTE.to_string(), number(0));
+        item.remove(RECEIPT_ATTRIBUTE);
+        self.store.put_item(&self.table_name, item, None).await?;
+
+        match self
+            .store
+            .delete_item(&self.table_name, self.key(id), Some(holds_receipt(receipt)))
+            .await
+        {
+            // The lease ran out and another receive took the message over; it moves
+            // the message itself.
+            Err(err) if err.is_conditional_check_failed() => Ok(()),
+            result => result,
+        }
+    }
+
+    fn key(&self, id: &str) -> Item {
+        HashMap::from([
+            (
+                QUEUE_ATTRIBUTE.to_string(),
+                AttributeValue::S(self.name.clone()),
+            ),
+            (ID_ATTRIBUTE.to_string(), AttributeValue::S(id.to_string())),
+        ])
+    }
+}
+
+/// Still leased by the receive that handed out `receipt`.
+fn holds_receipt(receipt: &str) -> Filter {
+    Filter::eq(RECEIPT_ATTRIBUTE, AttributeValue::S(receipt.to_string()))
+}
+
+/// Not leased by anyone since its visibility was read as `observed`.
+fn unchanged_since(observed: AttributeValue) -> Filter {
+    Filter::eq(VISIBLE_AT_ATTRIBUTE, observed)
+}
+
+fn visible_after(delay: Duration) -> Result<SystemTime, Error> {
+    SystemTime::now()
+        .checked_add(delay)
+        .ok_or_else(|| Error::Validation(format!("visibility delay {delay:?} is out of range")))
+}
+
+fn number(value: impl ToString) -> AttributeValue {
+    AttributeValue::N(value.to_string())
+}
+
+fn epoch_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryStore;
+
+    const TABLE: &str = "queues";
+
+    fn queues() -> InMemoryStore {
+        InMemoryStore::new().with_table(
+            TABLE,
+            TableSchema::new(QUEUE_ATTRIBUTE, ScalarType::S).sort_key(ID_ATTRIBUTE, ScalarType::S),
+        )
+    }
+
+    fn key() -> Item {
+        HashMap::from([
+            (
+                QUEUE_ATTRIBUTE.to_string(),
+                AttributeValue::S("jobs".to_string()),
+            ),
+            (
+                ID_ATTRIBUTE.to_string(),
+                AttributeValue::S("01".to_string()),
+            ),
+        ])
+    }
+
+    fn message(visible_at: u128, receipt: Option<&str>) -> Item {
+        let mut item = key();
+        item.insert(VISIBLE_AT_ATTRIBUTE.to_string(), number(visible_at));
+        item.insert(ATTEMPTS_ATTRIBUTE.to_string(), number(1));
+        if let Some(receipt) = receipt {
+            item.insert(
+                RECEIPT_ATTRIBUTE.to_string(),
+                AttributeValue::S(receipt.to_string()),
+            );
+        }
+        item
+    }
+
+    #[tokio::test]
+    async fn a_message_leased_since_it_was_read_is_not_leased_again() {
+        let store = queues();
+        store.put_item(TABLE, message(0, None), None).await.unwrap();
+        store
+            .put_item(
+                TABLE,
+                message(5_000, Some("first")),
+                Some(unchanged_since(number(0))),
+            )
+            .await
+            .unwrap();
+
+        let err = store
+            .put_item(
+                TABLE,
+                message(6_000, Some("second")),
+                Some(unchanged_since(number(0))),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err.is_conditional_check_failed());
+    }
+
+    #[tokio::test]
+    async fn only_the_current_receipt_acknowledges() {
+        let store = queues();
+        store
+            .put_item(TABLE, message(5_000, Some("second")), None)
+            .await
+            .unwrap();
+
+        let err = store
+            .delete_item(TABLE, key(), Some(holds_receipt("first")))
+            .await
+            .unwrap_err();
+        assert!(err.is_conditional_check_failed());
+
+        store
+            .delete_item(TABLE, key(), Some(holds_receipt("second")))
+            .await
+            .unwrap();
+        assert!(store.items(TABLE).is_empty());
+    }
+
+    fn queue(store: &InMemoryStore) -> Queue<InMemoryStore> {
+        Queue::new(store, TABLE, "jobs").max_attempts(2)
+    }
+
+    #[tokio::test]
+    async fn messages_are_received_in_order_and_acknowledged() {
+        let store = queues();
+        let jobs = queue(&store);
+        jobs.enqueue(&"first".to_string()).await.unwrap();
+        jobs.enqueue(&"second".to_string()).await.unwrap();
+
+        let messages = jobs.receive::<String>(10).await.unwrap();
+        let bodies: Vec<&str> = messages
+            .iter()
+            .map(|message| message.body.as_str())
+            .collect();
+        assert_eq!(bodies, ["first", "second"]);
+        assert_eq!(messages[0].attempts, 1);
+
+        // Leased messages stay hidden until their visibility timeout ends.
+        assert!(jobs.receive::<String>(10).await.unwrap().is_empty());
+
+        for message in &messages {
+            assert!(jobs.ack(message).await.unwrap());
+        }
+        assert!(store.items(TABLE).is_empty());
+    }
+
+    #[tokio::test]
+    async fn unacknowledged_messages_come_back_and_stale_receipts_lose_them() {
+        let store = queues();
+        let jobs = queue(&store);
+        jobs.enqueue(&"job".to_string()).await.unwrap();
+
+        let first = jobs.receive::<String>(1).await.unwrap().remove(0);
+        assert!(jobs
+            .change_visibility(&first, Duration::ZERO)
+            .await
+            .unwrap());
+
+        let second = jobs.receive::<String>(1).await.unwrap().remove(0);
+        assert_eq!(second.attempts, 2);
+        assert!(!jobs.ack(&first).await.unwrap());
+        assert!(!jobs
+            .change_visibility(&first, Duration::ZERO)
+            .await
+            .unwrap());
+        assert!(jobs.ack(&second).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn messages_out_of_attempts_move_to_the_dead_letter_queue() {
+        let store = queues();
+        let jobs = queue(&store);
+        let id = jobs.enqueue(&"poison".to_string()).await.unwrap();
+
+        for _ in 0..2 {
+            let message = jobs.receive::<String>(1).await.unwrap().remove(0);
+            jobs.change_visibility(&message, Duration::ZERO)
+                .await
+                .unwrap();
+        }
+        assert!(jobs.receive::<String>(1).await.unwrap().is_empty());
+
+        let dead = jobs.dead_letters().receive::<String>(10).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].id, id);
+        assert_eq!(dead[0].body, "poison");
+        assert_eq!(dead[0].attempts, 1);
+        assert_eq!(store.items(TABLE).len(), 1);
+    }
+}
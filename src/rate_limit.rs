@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use aws_sdk_dynamodb::types::{AttributeValue, ReturnValue};
+
+use crate::{
+    expression::CheckedExpressions,
+    filter::Filter,
+    instrument::Operation,
+    schema::{ScalarType, TableSchema},
+    ttl::{ttl_value, DEFAULT_TTL_ATTRIBUTE},
+    update::with_condition,
+    DynamoDbStore, Error,
+};
+
+const KEY_ATTRIBUTE: &str = "rate_key";
+const COUNT_ATTRIBUTE: &str = "count";
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How requests are counted against the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitWindow {
+    /// Counts requests per aligned window, e.g. per calendar minute. Cheapest, but a
+    /// client can send up to twice the limit across a window boundary.
+    #[default]
+    Fixed,
+    /// Weighs the previous window's count by how much of it still overlaps the last
+    /// `window`, smoothing out bursts at boundaries. Costs one extra read per check.
+    Sliding,
+}
+
+/// The outcome of [`RateLimiter::check_and_increment`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Whether the request is within the limit. Denied requests are not counted.
+    pub allowed: bool,
+    /// Requests left in the current window after this one.
+    pub remaining: u64,
+    /// Time until the current window ends.
+    pub reset_after: Duration,
+}
+
+/// Rate limiting counters in a DynamoDB table, shared by every instance of a service.
+///
+/// Each key and window is one item, counted with an atomic `ADD` and removed by TTL
+/// once it no longer matters.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    store: DynamoDbStore,
+    table_name: String,
+    window: RateLimitWindow,
+}
+
+impl RateLimiter {
+    pub fn new(store: &DynamoDbStore, table_name: impl Into<String>) -> Self {
+        Self {
+            store: store.clone(),
+            table_name: table_name.into(),
+            window: RateLimitWindow::default(),
+        }
+    }
+
+    pub fn window(mut self, window: RateLimitWindow) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Creates the counter table with TTL enabled if it does not exist yet.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let schema = TableSchema::new(KEY_ATTRIBUTE, ScalarType::S);
+
+        match self
+            .store
+            .create_table_from_schema(&self.table_name, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        self.store
+            .wait_until_active(&self.table_name, WAIT_TIMEOUT)
+            .await?;
+        self.store
+            .enable_ttl(&self.table_name, DEFAULT_TTL_ATTRIBUTE)
+            .await
+    }
+
+    /// Counts one request for `key` if fewer than `limit` were counted in the last
+    /// `window`, and reports whether it is allowed.
+    pub async fn check_and_increment(
+        &self,
+        key: &str,
+        limit: u64,
+        window: Duration,
+    ) -> Result<RateLimit, Error> {
+        let window_millis = window.as_millis();
+        if window_millis == 0 {
+            return Err(Error::Validation(
+                "rate limit window must be at least one millisecond".to_string(),
+            ));
+        }
+
+        let now = SystemTime::now();
+        let now_millis = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let (window_start, elapsed) = window_position(now_millis, window_millis);
+        let reset_after = reset_after(elapsed, window_millis)?;
+        let expires_at = counter_expiry(now, reset_after, window)?;
+
+        let threshold = match self.window {
+            RateLimitWindow::Fixed => limit,
+            RateLimitWindow::Sliding => {
+                let previous = self
+                    .count(key, window_start.saturating_sub(window_millis))
+                    .await?;
+                sliding_threshold(limit, previous, elapsed, window_millis)
+            }
+        };
+        let denied = RateLimit {
+            allowed: false,
+            remaining: 0,
+            reset_after,
+        };
+        if threshold == 0 {
+            return Ok(denied);
+        }
+
+        let operation = Operation::new("UpdateItem", &self.table_name);
+        let request = self
+            .store
+            .client()
+            .update_item()
+            .table_name(&self.table_name)
+            .set_key(Some(self.key(key, window_start)))
+            .update_expression("ADD #count :one SET #ttl = :expires")
+            .expression_attribute_names("#count", COUNT_ATTRIBUTE)
+            .expression_attribute_names("#ttl", DEFAULT_TTL_ATTRIBUTE)
+            .expression_attribute_values(":one", AttributeValue::N("1".to_string()))
+            .expression_attribute_values(":expires", ttl_value(expires_at)?)
+            .return_values(ReturnValue::UpdatedNew);
+        let request = with_condition(request, &below(threshold)).checked()?.send();
+
+        let output = match operation.send(request).await.map_err(Error::from) {
+            Ok(output) => output,
+            Err(err) if err.is_conditional_check_failed() => return Ok(denied),
+            Err(err) => return Err(err),
+        };
+        let count = output
+            .attributes
+            .as_ref()
+            .and_then(count)
+            .unwrap_or(threshold);
+
+        Ok(RateLimit {
+            allowed: true,
+            remaining: threshold.saturating_sub(count),
+            reset_after,
+        })
+    }
+
+    async fn count(&self, key: &str, window_start: u128) -> Result<u64, Error> {
+        let item = self
+            .store
+            .get_item(&self.table_name, self.key(key, window_start))
+            .await?;
+        Ok(item.as_ref().and_then(count).unwrap_or_default())
+    }
+
+    fn key(&self, key: &str, window_start: u128) -> HashMap<String, AttributeValue> {
+        HashMap::from([(
+            KEY_ATTRIBUTE.to_string(),
+            AttributeValue::S(format!("{key}#{window_start}")),
+        )])
+    }
+}
+
+/// The start of the aligned window containing `now_millis`, and how far into it
+/// `now_millis` is.
+fn window_position(now_millis: u128, window_millis: u128) -> (u128, u128) {
+    let elapsed = now_millis % window_millis;
+    (now_millis - elapsed, elapsed)
+}
+
+/// How long until the window ends, when `elapsed` of its `window_millis` have passed.
+fn reset_after(elapsed: u128, window_millis: u128) -> Result<Duration, Error> {
+    u64::try_from(window_millis - elapsed)
+        .map(Duration::from_millis)
+        .map_err(|_| {
+            Error::Validation(format!(
+                "rate limit window of {window_millis} ms is too long"
+            ))
+        })
+}
+
+/// When the counter of the current window may expire. Sliding windows read the
+/// previous window, so each one is kept for a window past its end.
+fn counter_expiry(
+    now: SystemTime,
+    reset_after: Duration,
+    window: Duration,
+) -> Result<SystemTime, Error> {
+    now.checked_add(reset_after)
+        .and_then(|end| end.checked_add(window))
+        .ok_or_else(|| Error::Validation(format!("rate limit window {window:?} is too long")))
+}
+
+/// The count the current window may reach when the part of the previous window that
+/// still overlaps the last `window_millis` is counted too.
+fn sliding_threshold(limit: u64, previous: u64, elapsed: u128, window_millis: u128) -> u64 {
+    let overlap = 1.0 - elapsed as f64 / window_millis as f64;
+    (limit as f64 - previous as f64 * overlap).ceil().max(0.0) as u64
+}
+
+/// Counted fewer than `threshold` requests so far, or none at all.
+fn below(threshold: u64) -> Filter {
+    Filter::not_exists(COUNT_ATTRIBUTE).or(Filter::lt(
+        COUNT_ATTRIBUTE,
+        AttributeValue::N(threshold.to_string()),
+    ))
+}
+
+fn count(item: &HashMap<String, AttributeValue>) -> Option<u64> {
+    match item.get(COUNT_ATTRIBUTE) {
+        Some(AttributeValue::N(value)) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::DynamoStoreApi, testing::InMemoryStore};
+
+    #[test]
+    fn windows_are_aligned_to_their_length() {
+        assert_eq!(window_position(125_000, 60_000), (120_000, 5_000));
+        assert_eq!(window_position(120_000, 60_000), (120_000, 0));
+        assert_eq!(window_position(179_999, 60_000), (120_000, 59_999));
+    }
+
+    #[test]
+    fn timing_covers_the_rest_of_the_window_and_one_more() {
+        let now = UNIX_EPOCH + Duration::from_millis(1_250);
+        let reset = reset_after(250, 1_000).unwrap();
+
+        assert_eq!(reset, Duration::from_millis(750));
+        assert_eq!(
+            counter_expiry(now, reset, Duration::from_secs(1)).unwrap(),
+            UNIX_EPOCH + Duration::from_millis(3_000)
+        );
+    }
+
+    #[test]
+    fn windows_too_long_to_represent_are_rejected() {
+        assert!(matches!(
+            reset_after(0, Duration::MAX.as_millis()),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            counter_expiry(SystemTime::now(), Duration::from_secs(1), Duration::MAX),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn the_previous_window_weighs_less_as_the_current_one_goes_on() {
+        assert_eq!(sliding_threshold(10, 8, 0, 1_000), 2);
+        assert_eq!(sliding_threshold(10, 8, 500, 1_000), 6);
+        assert_eq!(sliding_threshold(10, 8, 999, 1_000), 10);
+        assert_eq!(sliding_threshold(10, 0, 0, 1_000), 10);
+    }
+
+    #[test]
+    fn a_busy_previous_window_leaves_nothing() {
+        assert_eq!(sliding_threshold(10, 30, 500, 1_000), 0);
+    }
+
+    #[tokio::test]
+    async fn counting_stops_at_the_threshold() {
+        let store = InMemoryStore::new()
+            .with_table("limits", TableSchema::new(KEY_ATTRIBUTE, ScalarType::S));
+        let counter = |count: u64| {
+            HashMap::from([
+                (
+                    KEY_ATTRIBUTE.to_string(),
+                    AttributeValue::S("client#0".to_string()),
+                ),
+                (
+                    COUNT_ATTRIBUTE.to_string(),
+                    AttributeValue::N(count.to_string()),
+                ),
+            ])
+        };
+
+        for count in 1..=3 {
+            store
+                .put_item("limits", counter(count), Some(below(3)))
+                .await
+                .unwrap();
+        }
+        let err = store
+            .put_item("limits", counter(4), Some(below(3)))
+            .await
+            .unwrap_err();
+
+        assert!(err.is_conditional_check_failed());
+    }
+}
@@ -0,0 +1,31 @@
+use std::env;
+
+use aws_sdk_dynamodb::{config::Region, Config};
+
+/// Which replica of a Global Table reads go to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ReadPreference {
+    /// The region the store writes to.
+    #[default]
+    Primary,
+    /// The region the code runs in, read from `AWS_REGION` or `AWS_DEFAULT_REGION`, e.g.
+    /// a Lambda deployed to every replica region with one primary for writes. Falls
+    /// back to the primary region if neither is set.
+    Nearest,
+    Region(String),
+}
+
+impl ReadPreference {
+    /// The read region, or `None` if it is the primary region of `config`.
+    pub(crate) fn region(&self, config: &Config) -> Option<Region> {
+        let region = match self {
+            Self::Primary => return None,
+            Self::Nearest => env::var("AWS_REGION")
+                .or_else(|_| env::var("AWS_DEFAULT_REGION"))
+                .ok()?,
+            Self::Region(region) => region.clone(),
+        };
+
+        (config.region().map(Region::as_ref) != Some(region.as_str())).then(|| Region::new(region))
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{
+    batch::BatchWriteResult, entity::DynamoEntity, table::TableBoundStore,
+    typed_table::TypedTableStore, DynamoDbStore, Error,
+};
+
+/// Typed access to the table of a [`DynamoEntity`].
+pub struct Repository<T: DynamoEntity> {
+    table: TypedTableStore<T, T::Key>,
+}
+
+impl<T: DynamoEntity> Clone for Repository<T> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+        }
+    }
+}
+
+impl<T: DynamoEntity> fmt::Debug for Repository<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Repository")
+            .field("table_name", &self.table.table().table_name())
+            .finish_non_exhaustive()
+    }
+}
+
+impl DynamoDbStore {
+    pub fn repository<T: DynamoEntity>(&self) -> Repository<T> {
+        Repository {
+            table: self.typed_table(T::TABLE),
+        }
+    }
+}
+
+impl<T: DynamoEntity> Repository<T> {
+    pub fn table(&self) -> &TableBoundStore {
+        self.table.table()
+    }
+
+    pub async fn save(&self, entity: &T) -> Result<(), Error> {
+        self.table.put(entity).await
+    }
+
+    pub async fn find(&self, key: &T::Key) -> Result<Option<T>, Error> {
+        self.table.get(key).await
+    }
+
+    pub async fn delete(&self, key: &T::Key) -> Result<(), Error> {
+        self.table.delete(key).await
+    }
+
+    /// Returns every entity stored under the partition key value `pk`.
+    pub async fn find_all_by_pk(&self, pk: &impl Serialize) -> Result<Vec<T>, Error> {
+        let table = self.table.table();
+        let partition_key = table.key_names().await?[0].clone();
+        let items = table
+            .store()
+            .query(table.table_name())
+            .partition_key(partition_key, serde_dynamo::to_attribute_value(pk)?)
+            .all()
+            .await?;
+
+        items
+            .into_iter()
+            .map(|item| table.read_item(item))
+            .collect()
+    }
+
+    pub async fn save_all(&self, entities: &[T]) -> Result<BatchWriteResult, Error> {
+        self.table.batch_put(entities).await
+    }
+
+    /// Loads the entities at `keys`; missing entities are left out and the order is not
+    /// preserved.
+    pub async fn find_many(&self, keys: &[T::Key]) -> Result<Vec<T>, Error> {
+        self.table.batch_get(keys).await
+    }
+
+    pub async fn delete_all(&self, keys: &[T::Key]) -> Result<BatchWriteResult, Error> {
+        self.table.batch_delete(keys).await
+    }
+}
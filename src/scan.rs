@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::Stream;
+
+use crate::{
+    api::StoreFuture,
+    expression::CheckedExpressions,
+    filter::Filter,
+    instrument::Operation,
+    options::RequestOptions,
+    page::{
+        collect_items, projection_expression, read_all_pages, stream_items, stream_pages, AllPages,
+        Cursor, Page,
+    },
+    retry::RetryConfig,
+    DynamoDbStore, Error,
+};
+
+impl DynamoDbStore {
+    pub fn scan(&self, table_name: impl Into<String>) -> Scan<'_> {
+        Scan {
+            store: self,
+            table_name: table_name.into(),
+            projection: Vec::new(),
+            filter: None,
+            segment: None,
+            limit: None,
+            consistent_read: false,
+            exclusive_start_key: None,
+            retry: None,
+        }
+        .defaults(self.defaults())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Scan<'a> {
+    store: &'a DynamoDbStore,
+    table_name: String,
+    projection: Vec<String>,
+    filter: Option<Filter>,
+    segment: Option<(i32, i32)>,
+    limit: Option<i32>,
+    consistent_read: bool,
+    exclusive_start_key: Option<HashMap<String, AttributeValue>>,
+    retry: Option<RetryConfig>,
+}
+
+impl<'a> Scan<'a> {
+    pub fn projection(mut self, attributes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.projection = attributes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only returns items matching `filter`; filtered items still consume read capacity.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    pub fn segment(mut self, segment: i32, total_segments: i32) -> Self {
+        self.segment = Some((segment, total_segments));
+        self
+    }
+
+    pub fn limit(mut self, limit: i32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn consistent_read(mut self, consistent_read: bool) -> Self {
+        self.consistent_read = consistent_read;
+        self
+    }
+
+    /// Applies the page size, read consistency and retry settings of `defaults`.
+    pub(crate) fn defaults(mut self, defaults: &RequestOptions) -> Self {
+        self.limit = defaults.limit.or(self.limit);
+        self.retry = defaults.retry.or(self.retry);
+        self.consistent_read = defaults.consistent_read.unwrap_or(self.consistent_read);
+        self
+    }
+
+    pub fn start_after(mut self, key: HashMap<String, AttributeValue>) -> Self {
+        self.exclusive_start_key = Some(key);
+        self
+    }
+
+    /// Fetches a single page starting after the configured start key.
+    pub async fn page(&self) -> Result<Page, Error> {
+        let mut names = HashMap::new();
+        let mut values = HashMap::new();
+        let projection = projection_expression(&self.projection, &mut names);
+        let filter = self
+            .filter
+            .as_ref()
+            .map(|filter| filter.render(&mut names, &mut values));
+
+        let operation = Operation::new("Scan", &self.table_name);
+        let request = self
+            .store
+            .read_client()
+            .scan()
+            .table_name(&self.table_name)
+            .set_projection_expression(projection)
+            .set_filter_expression(filter)
+            .set_expression_attribute_names((!names.is_empty()).then_some(names))
+            .set_expression_attribute_values((!values.is_empty()).then_some(values))
+            .set_segment(self.segment.map(|(segment, _)| segment))
+            .set_total_segments(self.segment.map(|(_, total)| total))
+            .set_limit(self.limit)
+            .consistent_read(self.consistent_read)
+            .set_exclusive_start_key(self.exclusive_start_key.clone())
+            .checked()?;
+        let config = RequestOptions {
+            retry: self.retry,
+            ..RequestOptions::default()
+        }
+        .config_override();
+        let request = async {
+            match config {
+                Some(config) => request.customize().config_override(config).send().await,
+                None => request.send().await,
+            }
+        };
+        let output = operation.send(request).await?;
+
+        Ok(Page {
+            items: output.items.unwrap_or_default(),
+            last_evaluated_key: output.last_evaluated_key,
+            scanned_count: output.scanned_count,
+        })
+    }
+
+    /// Streams pages lazily; the next request is only sent when the stream is polled.
+    pub fn pages(self) -> impl Stream<Item = Result<Page, Error>> + 'a {
+        let (fetch, cursor) = self.fetcher();
+        stream_pages(fetch, cursor)
+    }
+
+    /// Streams at most `n` items, sending no further requests once they are read.
+    ///
+    /// Each request asks for no more items than are still missing, so a selective filter
+    /// takes more, smaller requests, but nothing is read past the `n`th item.
+    pub fn take_items(
+        self,
+        n: usize,
+    ) -> impl Stream<Item = Result<HashMap<String, AttributeValue>, Error>> + 'a {
+        let (fetch, cursor) = self.fetcher();
+        stream_items(fetch, cursor, n)
+    }
+
+    /// Reads up to `max` items, like [`take_items`](Self::take_items), and returns them
+    /// as one page. Its `last_evaluated_key` is set when more items may follow, and
+    /// passing it to `start_after` continues right after the last returned item.
+    /// Fails with a validation error for a `max` of 0.
+    pub async fn collect_limited(self, max: usize) -> Result<Page, Error> {
+        let (fetch, cursor) = self.fetcher();
+        collect_items(fetch, cursor, max).await
+    }
+
+    pub async fn all(self) -> Result<Vec<HashMap<String, AttributeValue>>, Error> {
+        Ok(self.all_with_stats().await?.items)
+    }
+
+    /// Like [`all`](Self::all), also reporting the page count, the items per page and
+    /// the scanned count, e.g. to estimate what the reads cost. [`pages`](Self::pages)
+    /// gives the raw pages instead.
+    pub async fn all_with_stats(self) -> Result<AllPages, Error> {
+        let (fetch, cursor) = self.fetcher();
+        read_all_pages(fetch, cursor).await
+    }
+
+    /// Fetches the page a cursor points at, starting from this scan's own start key
+    /// and limit.
+    fn fetcher(self) -> (impl Fn(Cursor) -> StoreFuture<'a, Page> + 'a, Cursor) {
+        let cursor = Cursor {
+            start_key: self.exclusive_start_key.clone(),
+            limit: self.limit,
+        };
+        let fetch = move |cursor: Cursor| -> StoreFuture<'a, Page> {
+            let mut scan = self.clone();
+            scan.exclusive_start_key = cursor.start_key;
+            scan.limit = cursor.limit;
+            Box::pin(async move { scan.page().await })
+        };
+        (fetch, cursor)
+    }
+}
@@ -0,0 +1,195 @@
+use aws_sdk_dynamodb::{
+    error::BuildError,
+    types::{
+        AttributeDefinition, CreateGlobalSecondaryIndexAction, GlobalSecondaryIndex,
+        KeySchemaElement, KeyType, Projection, ProvisionedThroughput,
+    },
+};
+
+pub use aws_sdk_dynamodb::types::{BillingMode, ProjectionType, ScalarAttributeType as ScalarType};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyAttribute {
+    pub name: String,
+    pub scalar_type: ScalarType,
+}
+
+impl KeyAttribute {
+    pub fn new(name: impl Into<String>, scalar_type: ScalarType) -> Self {
+        Self {
+            name: name.into(),
+            scalar_type,
+        }
+    }
+
+    pub(crate) fn definition(&self) -> Result<AttributeDefinition, BuildError> {
+        AttributeDefinition::builder()
+            .attribute_name(&self.name)
+            .attribute_type(self.scalar_type.clone())
+            .build()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Gsi {
+    pub name: String,
+    pub partition_key: KeyAttribute,
+    pub sort_key: Option<KeyAttribute>,
+    pub projection: ProjectionType,
+    pub non_key_attributes: Vec<String>,
+    pub provisioned_throughput: Option<(i64, i64)>,
+}
+
+impl Gsi {
+    pub fn new(
+        name: impl Into<String>,
+        partition_key: impl Into<String>,
+        scalar_type: ScalarType,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            partition_key: KeyAttribute::new(partition_key, scalar_type),
+            sort_key: None,
+            projection: ProjectionType::All,
+            non_key_attributes: Vec::new(),
+            provisioned_throughput: None,
+        }
+    }
+
+    pub fn sort_key(mut self, name: impl Into<String>, scalar_type: ScalarType) -> Self {
+        self.sort_key = Some(KeyAttribute::new(name, scalar_type));
+        self
+    }
+
+    pub fn projection(mut self, projection: ProjectionType) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    pub fn include(mut self, attributes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.projection = ProjectionType::Include;
+        self.non_key_attributes = attributes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn provisioned_throughput(mut self, read_capacity: i64, write_capacity: i64) -> Self {
+        self.provisioned_throughput = Some((read_capacity, write_capacity));
+        self
+    }
+
+    pub(crate) fn key_attributes(&self) -> impl Iterator<Item = &KeyAttribute> {
+        std::iter::once(&self.partition_key).chain(self.sort_key.as_ref())
+    }
+
+    pub(crate) fn to_sdk(&self) -> Result<GlobalSecondaryIndex, BuildError> {
+        GlobalSecondaryIndex::builder()
+            .index_name(&self.name)
+            .set_key_schema(Some(key_schema(
+                &self.partition_key,
+                self.sort_key.as_ref(),
+            )?))
+            .projection(self.sdk_projection())
+            .set_provisioned_throughput(self.sdk_provisioned_throughput()?)
+            .build()
+    }
+
+    pub(crate) fn to_create_action(&self) -> Result<CreateGlobalSecondaryIndexAction, BuildError> {
+        CreateGlobalSecondaryIndexAction::builder()
+            .index_name(&self.name)
+            .set_key_schema(Some(key_schema(
+                &self.partition_key,
+                self.sort_key.as_ref(),
+            )?))
+            .projection(self.sdk_projection())
+            .set_provisioned_throughput(self.sdk_provisioned_throughput()?)
+            .build()
+    }
+
+    fn sdk_projection(&self) -> Projection {
+        let mut projection = Projection::builder().projection_type(self.projection.clone());
+        if !self.non_key_attributes.is_empty() {
+            projection = projection.set_non_key_attributes(Some(self.non_key_attributes.clone()));
+        }
+
+        projection.build()
+    }
+
+    fn sdk_provisioned_throughput(&self) -> Result<Option<ProvisionedThroughput>, BuildError> {
+        self.provisioned_throughput
+            .map(|(read, write)| provisioned_throughput(read, write))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TableSchema {
+    pub partition_key: KeyAttribute,
+    pub sort_key: Option<KeyAttribute>,
+    pub billing_mode: BillingMode,
+    pub provisioned_throughput: Option<(i64, i64)>,
+    pub gsis: Vec<Gsi>,
+}
+
+impl TableSchema {
+    pub fn new(partition_key: impl Into<String>, scalar_type: ScalarType) -> Self {
+        Self {
+            partition_key: KeyAttribute::new(partition_key, scalar_type),
+            sort_key: None,
+            billing_mode: BillingMode::PayPerRequest,
+            provisioned_throughput: None,
+            gsis: Vec::new(),
+        }
+    }
+
+    pub fn sort_key(mut self, name: impl Into<String>, scalar_type: ScalarType) -> Self {
+        self.sort_key = Some(KeyAttribute::new(name, scalar_type));
+        self
+    }
+
+    pub fn billing_mode(mut self, billing_mode: BillingMode) -> Self {
+        self.billing_mode = billing_mode;
+        self
+    }
+
+    pub fn provisioned_throughput(mut self, read_capacity: i64, write_capacity: i64) -> Self {
+        self.billing_mode = BillingMode::Provisioned;
+        self.provisioned_throughput = Some((read_capacity, write_capacity));
+        self
+    }
+
+    pub fn gsi(mut self, gsi: Gsi) -> Self {
+        self.gsis.push(gsi);
+        self
+    }
+}
+
+pub(crate) fn key_schema(
+    partition_key: &KeyAttribute,
+    sort_key: Option<&KeyAttribute>,
+) -> Result<Vec<KeySchemaElement>, BuildError> {
+    let mut schema = vec![KeySchemaElement::builder()
+        .attribute_name(&partition_key.name)
+        .key_type(KeyType::Hash)
+        .build()?];
+
+    if let Some(sort_key) = sort_key {
+        schema.push(
+            KeySchemaElement::builder()
+                .attribute_name(&sort_key.name)
+                .key_type(KeyType::Range)
+                .build()?,
+        );
+    }
+
+    Ok(schema)
+}
+
+pub(crate) fn provisioned_throughput(
+    read_capacity: i64,
+    write_capacity: i64,
+) -> Result<ProvisionedThroughput, BuildError> {
+    ProvisionedThroughput::builder()
+        .read_capacity_units(read_capacity)
+        .write_capacity_units(write_capacity)
+        .build()
+}
@@ -0,0 +1,129 @@
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tower_service::Service;
+
+use crate::{
+    api::{DynamoStoreApi, Item, QueryRequest, StoreFuture},
+    batch::{BatchGetResult, BatchWriteResult},
+    filter::Filter,
+    page::Page,
+    Error,
+};
+
+/// A store operation sent through [`StoreService`].
+#[derive(Debug, Clone)]
+pub enum StoreRequest {
+    PutItem {
+        table_name: String,
+        item: Item,
+        condition: Option<Filter>,
+    },
+    GetItem {
+        table_name: String,
+        key: Item,
+    },
+    DeleteItem {
+        table_name: String,
+        key: Item,
+        condition: Option<Filter>,
+    },
+    Query(Box<QueryRequest>),
+    BatchPutItems {
+        table_name: String,
+        items: Vec<Item>,
+    },
+    BatchDeleteItems {
+        table_name: String,
+        keys: Vec<Item>,
+    },
+    BatchGetItems {
+        table_name: String,
+        keys: Vec<Item>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum StoreResponse {
+    /// A put or delete succeeded.
+    Done,
+    Item(Option<Item>),
+    Page(Page),
+    BatchWrite(BatchWriteResult),
+    BatchGet(BatchGetResult),
+}
+
+/// Exposes a [`DynamoStoreApi`] as a `tower::Service`, so timeouts, rate limits, retries
+/// and load shedding can be layered around store calls with tower middleware.
+#[derive(Debug)]
+pub struct StoreService<S: ?Sized> {
+    store: Arc<S>,
+}
+
+impl<S: ?Sized> Clone for StoreService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+        }
+    }
+}
+
+impl<S: DynamoStoreApi + ?Sized> StoreService<S> {
+    pub fn new(store: Arc<S>) -> Self {
+        Self { store }
+    }
+}
+
+impl<S: DynamoStoreApi + ?Sized + 'static> Service<StoreRequest> for StoreService<S> {
+    type Response = StoreResponse;
+    type Error = Error;
+    type Future = StoreFuture<'static, StoreResponse>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: StoreRequest) -> Self::Future {
+        let store = Arc::clone(&self.store);
+
+        Box::pin(async move {
+            let response = match request {
+                StoreRequest::PutItem {
+                    table_name,
+                    item,
+                    condition,
+                } => {
+                    store.put_item(&table_name, item, condition).await?;
+                    StoreResponse::Done
+                }
+                StoreRequest::GetItem { table_name, key } => {
+                    StoreResponse::Item(store.get_item(&table_name, key).await?)
+                }
+                StoreRequest::DeleteItem {
+                    table_name,
+                    key,
+                    condition,
+                } => {
+                    store.delete_item(&table_name, key, condition).await?;
+                    StoreResponse::Done
+                }
+                StoreRequest::Query(request) => {
+                    StoreResponse::Page(store.query_page(&request).await?)
+                }
+                StoreRequest::BatchPutItems { table_name, items } => {
+                    StoreResponse::BatchWrite(store.batch_put_items(&table_name, items).await?)
+                }
+                StoreRequest::BatchDeleteItems { table_name, keys } => {
+                    StoreResponse::BatchWrite(store.batch_delete_items(&table_name, keys).await?)
+                }
+                StoreRequest::BatchGetItems { table_name, keys } => {
+                    StoreResponse::BatchGet(store.batch_get_items(&table_name, keys).await?)
+                }
+            };
+
+            Ok(response)
+        })
+    }
+}
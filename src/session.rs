@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    time::{Duration, SystemTime},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    expression::CheckedExpressions,
+    instrument::Operation,
+    keys::random_bytes,
+    schema::{ScalarType, TableSchema},
+    ttl::{ttl_value, Expiry, DEFAULT_TTL_ATTRIBUTE},
+    DynamoDbStore, Error,
+};
+
+const ID_ATTRIBUTE: &str = "session_id";
+const DATA_ATTRIBUTE: &str = "data";
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Server-side web sessions in a DynamoDB table, expired by TTL.
+///
+/// Session ids are 256-bit random tokens, safe to hand out as cookie values.
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    store: DynamoDbStore,
+    table_name: String,
+}
+
+impl SessionStore {
+    pub fn new(store: &DynamoDbStore, table_name: impl Into<String>) -> Self {
+        Self {
+            store: store.clone(),
+            table_name: table_name.into(),
+        }
+    }
+
+    /// Creates the session table with TTL enabled if it does not exist yet.
+    pub async fn create_table(&self) -> Result<(), Error> {
+        let schema = TableSchema::new(ID_ATTRIBUTE, ScalarType::S);
+
+        match self
+            .store
+            .create_table_from_schema(&self.table_name, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => return Ok(()),
+            Err(err) => return Err(err),
+        }
+
+        self.store
+            .wait_until_active(&self.table_name, WAIT_TIMEOUT)
+            .await?;
+        self.store
+            .enable_ttl(&self.table_name, DEFAULT_TTL_ATTRIBUTE)
+            .await
+    }
+
+    /// Stores `session_data` under a new session id that expires after `ttl`, and
+    /// returns the id.
+    pub async fn create<T: Serialize>(
+        &self,
+        session_data: &T,
+        ttl: Duration,
+    ) -> Result<String, Error> {
+        let id = session_id();
+        let mut item = self.key(&id);
+        item.insert(
+            DATA_ATTRIBUTE.to_string(),
+            serde_dynamo::to_attribute_value(session_data)?,
+        );
+        item.insert(DEFAULT_TTL_ATTRIBUTE.to_string(), ttl_value(ttl)?);
+
+        self.store.put_item(&self.table_name, item).await?;
+        Ok(id)
+    }
+
+    /// Reads a session. Returns `None` for unknown, revoked and expired sessions,
+    /// including expired ones DynamoDB has not deleted yet.
+    pub async fn get<T: DeserializeOwned>(&self, session_id: &str) -> Result<Option<T>, Error> {
+        let Some(mut item) = self
+            .store
+            .get_item(&self.table_name, self.key(session_id))
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let expires_at = match item.get(DEFAULT_TTL_ATTRIBUTE) {
+            Some(AttributeValue::N(value)) => value.parse::<i64>().ok(),
+            _ => None,
+        };
+        let now = SystemTime::now().epoch_seconds()?;
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Ok(None);
+        }
+
+        let data = item
+            .remove(DATA_ATTRIBUTE)
+            .unwrap_or(AttributeValue::Null(true));
+        Ok(Some(serde_dynamo::from_attribute_value(data)?))
+    }
+
+    /// Extends a session to expire `ttl` from now, e.g. on every authenticated request
+    /// for sliding expiry. Returns `false` if the session no longer exists or has
+    /// already expired.
+    pub async fn touch(&self, session_id: &str, ttl: Duration) -> Result<bool, Error> {
+        let operation = Operation::new("UpdateItem", &self.table_name);
+        let request = self
+            .store
+            .client()
+            .update_item()
+            .table_name(&self.table_name)
+            .set_key(Some(self.key(session_id)))
+            .update_expression("SET #ttl = :expires")
+            .condition_expression("attribute_exists(#id) AND #ttl > :now")
+            .expression_attribute_names("#id", ID_ATTRIBUTE)
+            .expression_attribute_names("#ttl", DEFAULT_TTL_ATTRIBUTE)
+            .expression_attribute_values(":expires", ttl_value(ttl)?)
+            .expression_attribute_values(":now", ttl_value(SystemTime::now())?)
+            .checked()?
+            .send();
+
+        match operation.send(request).await.map_err(Error::from) {
+            Ok(_) => Ok(true),
+            Err(err) if err.is_conditional_check_failed() => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Deletes a session, e.g. on logout. Revoking an unknown session is not an error.
+    pub async fn revoke(&self, session_id: &str) -> Result<(), Error> {
+        self.store
+            .delete_item(&self.table_name, self.key(session_id))
+            .await?;
+        Ok(())
+    }
+
+    fn key(&self, session_id: &str) -> HashMap<String, AttributeValue> {
+        HashMap::from([(
+            ID_ATTRIBUTE.to_string(),
+            AttributeValue::S(session_id.to_string()),
+        )])
+    }
+}
+
+fn session_id() -> String {
+    random_bytes::<32>()
+        .iter()
+        .fold(String::with_capacity(64), |mut id, byte| {
+            let _ = write!(id, "{byte:02x}");
+            id
+        })
+}
@@ -0,0 +1,89 @@
+use std::collections::{HashMap, HashSet};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde_dynamo::{number_set::NumberSet, string_set::StringSet};
+
+use crate::{expression::CheckedExpressions, instrument::Operation, DynamoDbStore, Error};
+
+/// Values that map to a DynamoDB string set (`SS`) or number set (`NS`).
+pub trait SetValues {
+    fn to_set_value(&self) -> Result<AttributeValue, Error>;
+    fn is_empty(&self) -> bool;
+}
+
+impl SetValues for HashSet<String> {
+    fn to_set_value(&self) -> Result<AttributeValue, Error> {
+        Ok(serde_dynamo::to_attribute_value(StringSet(self))?)
+    }
+
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+impl SetValues for HashSet<i64> {
+    fn to_set_value(&self) -> Result<AttributeValue, Error> {
+        Ok(serde_dynamo::to_attribute_value(NumberSet(self))?)
+    }
+
+    fn is_empty(&self) -> bool {
+        HashSet::is_empty(self)
+    }
+}
+
+impl DynamoDbStore {
+    /// Adds `values` to the set stored in `attribute`, creating the set if needed.
+    pub async fn add_to_set(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        attribute: &str,
+        values: &impl SetValues,
+    ) -> Result<(), Error> {
+        self.update_set("ADD", table_name, key, attribute, values)
+            .await
+    }
+
+    /// Removes `values` from the set stored in `attribute`; DynamoDB drops the attribute
+    /// once the set is empty.
+    pub async fn remove_from_set(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        attribute: &str,
+        values: &impl SetValues,
+    ) -> Result<(), Error> {
+        self.update_set("DELETE", table_name, key, attribute, values)
+            .await
+    }
+
+    async fn update_set(
+        &self,
+        action: &str,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        attribute: &str,
+        values: &impl SetValues,
+    ) -> Result<(), Error> {
+        if values.is_empty() {
+            return Err(Error::Validation(format!(
+                "cannot {action} an empty set on {attribute}"
+            )));
+        }
+
+        let operation = Operation::new("UpdateItem", table_name).attributes(&key);
+        let request = self
+            .client()
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(key))
+            .update_expression(format!("{action} #set :values"))
+            .expression_attribute_names("#set", attribute)
+            .expression_attribute_values(":values", values.to_set_value()?)
+            .checked()?
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use futures_util::future::try_join_all;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    keys::{random_bytes, raw_prefixed},
+    query::SortKeyCondition,
+    DynamoDbStore, Error,
+};
+
+/// Spreads the items of a hot logical partition key over `shards` physical keys
+/// (`"{key}#0"` … `"{key}#{shards - 1}"`) and fans reads out over all of them.
+#[derive(Debug, Clone)]
+pub struct ShardedTable {
+    store: DynamoDbStore,
+    table_name: String,
+    partition_key: String,
+    shards: u32,
+}
+
+impl DynamoDbStore {
+    pub fn sharded(&self, table_name: impl Into<String>, shards: u32) -> ShardedTable {
+        ShardedTable {
+            store: self.clone(),
+            table_name: table_name.into(),
+            partition_key: "PK".to_string(),
+            shards: shards.max(1),
+        }
+    }
+}
+
+impl ShardedTable {
+    pub fn with_partition_key(mut self, attribute_name: impl Into<String>) -> Self {
+        self.partition_key = attribute_name.into();
+        self
+    }
+
+    pub fn shards(&self) -> u32 {
+        self.shards
+    }
+
+    pub fn shard_key(&self, logical_key: &str, shard: u32) -> String {
+        raw_prefixed(logical_key, &shard.to_string())
+    }
+
+    /// The shard `discriminator` always maps to, so items written with
+    /// [`Self::put_for`] can be read back with a single `GetItem`.
+    pub fn shard_for(&self, logical_key: &str, discriminator: &str) -> String {
+        self.shard_key(logical_key, fnv1a(discriminator.as_bytes()) % self.shards)
+    }
+
+    /// Writes `item` to a random shard of `logical_key`, overwriting its partition key.
+    pub async fn put<T: Serialize>(&self, logical_key: &str, item: &T) -> Result<(), Error> {
+        let shard = u32::from_be_bytes(random_bytes::<4>()) % self.shards;
+        self.put_to(self.shard_key(logical_key, shard), item).await
+    }
+
+    /// Writes `item` to the shard chosen by [`Self::shard_for`].
+    pub async fn put_for<T: Serialize>(
+        &self,
+        logical_key: &str,
+        discriminator: &str,
+        item: &T,
+    ) -> Result<(), Error> {
+        self.put_to(self.shard_for(logical_key, discriminator), item)
+            .await
+    }
+
+    /// Queries every shard of `logical_key` concurrently and concatenates the results in
+    /// shard order.
+    pub async fn query_all<T: DeserializeOwned>(&self, logical_key: &str) -> Result<Vec<T>, Error> {
+        self.fan_out(logical_key, None).await
+    }
+
+    /// Like [`Self::query_all`], restricted by a sort key condition on every shard.
+    pub async fn query_all_where<T: DeserializeOwned>(
+        &self,
+        logical_key: &str,
+        sort_key: &str,
+        condition: SortKeyCondition,
+    ) -> Result<Vec<T>, Error> {
+        self.fan_out(logical_key, Some((sort_key, condition))).await
+    }
+
+    async fn put_to<T: Serialize>(&self, shard_key: String, item: &T) -> Result<(), Error> {
+        let mut item: HashMap<String, AttributeValue> = serde_dynamo::to_item(item)?;
+        item.insert(self.partition_key.clone(), AttributeValue::S(shard_key));
+
+        self.store.put_item(&self.table_name, item).await?;
+        Ok(())
+    }
+
+    async fn fan_out<T: DeserializeOwned>(
+        &self,
+        logical_key: &str,
+        sort_key: Option<(&str, SortKeyCondition)>,
+    ) -> Result<Vec<T>, Error> {
+        let queries = (0..self.shards).map(|shard| {
+            let mut query = self.store.query(&self.table_name).partition_key(
+                &self.partition_key,
+                AttributeValue::S(self.shard_key(logical_key, shard)),
+            );
+            if let Some((name, condition)) = &sort_key {
+                query = query.sort_key(*name, condition.clone());
+            }
+            query.all()
+        });
+
+        let items: Vec<_> = try_join_all(queries).await?.into_iter().flatten().collect();
+        Ok(serde_dynamo::from_items(items)?)
+    }
+}
+
+/// 32-bit FNV-1a, used because it is stable across processes and Rust versions.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::offline_store;
+
+    #[test]
+    fn fnv1a_matches_the_reference_values() {
+        assert_eq!(fnv1a(b""), 0x811c_9dc5);
+        assert_eq!(fnv1a(b"a"), 0xe40c_292c);
+        assert_eq!(fnv1a(b"foobar"), 0xbf9c_f968);
+    }
+
+    #[test]
+    fn discriminators_map_to_fixed_shards() {
+        let table = offline_store().sharded("events", 10);
+
+        assert_eq!(table.shard_for("tenant", ""), "tenant#1");
+        assert_eq!(table.shard_for("tenant", "user-42"), "tenant#9");
+        assert_eq!(table.shard_for("tenant", "user-43"), "tenant#0");
+        assert_eq!(
+            offline_store()
+                .sharded("events", 4)
+                .shard_for("tenant", "user-42"),
+            "tenant#3"
+        );
+    }
+
+    #[test]
+    fn zero_shards_fall_back_to_one() {
+        let table = offline_store().sharded("events", 0);
+
+        assert_eq!(table.shards(), 1);
+        assert_eq!(table.shard_for("tenant", "user-42"), "tenant#0");
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{keys::raw_prefixed, DynamoDbStore, Error};
+
+/// An entity stored in a single-table design, identified by its key prefix (`"ORDER"`
+/// for `ORDER#<id>`).
+pub trait EntityType {
+    const PREFIX: &'static str;
+
+    /// Formats `id` with this entity's prefix.
+    fn key(id: impl AsRef<str>) -> String {
+        raw_prefixed(Self::PREFIX, id.as_ref())
+    }
+}
+
+/// Access to a table that stores several entity types under generic `PK`/`SK` keys.
+#[derive(Debug, Clone)]
+pub struct SingleTable {
+    store: DynamoDbStore,
+    table_name: String,
+    partition_key: String,
+    sort_key: String,
+}
+
+impl DynamoDbStore {
+    pub fn single_table(&self, table_name: impl Into<String>) -> SingleTable {
+        SingleTable {
+            store: self.clone(),
+            table_name: table_name.into(),
+            partition_key: "PK".to_string(),
+            sort_key: "SK".to_string(),
+        }
+    }
+}
+
+impl SingleTable {
+    pub fn with_key_attributes(
+        mut self,
+        partition_key: impl Into<String>,
+        sort_key: impl Into<String>,
+    ) -> Self {
+        self.partition_key = partition_key.into();
+        self.sort_key = sort_key.into();
+        self
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Stores `item` under the partition `pk` with the sort key `E::key(id)`.
+    pub async fn put<E: EntityType + Serialize>(
+        &self,
+        pk: &str,
+        id: &str,
+        item: &E,
+    ) -> Result<(), Error> {
+        let mut attributes: HashMap<String, AttributeValue> = serde_dynamo::to_item(item)?;
+        attributes.extend(self.key::<E>(pk, id));
+
+        self.store.put_item(&self.table_name, attributes).await?;
+        Ok(())
+    }
+
+    pub async fn get<E: EntityType + DeserializeOwned>(
+        &self,
+        pk: &str,
+        id: &str,
+    ) -> Result<Option<E>, Error> {
+        match self
+            .store
+            .get_item(&self.table_name, self.key::<E>(pk, id))
+            .await?
+        {
+            Some(item) => Ok(Some(serde_dynamo::from_item(item)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn delete<E: EntityType>(&self, pk: &str, id: &str) -> Result<(), Error> {
+        self.store
+            .delete_item(&self.table_name, self.key::<E>(pk, id))
+            .await?;
+        Ok(())
+    }
+
+    /// Returns every `E` stored under `pk`, i.e. the items whose sort key begins with
+    /// `E::PREFIX`, in sort key order.
+    pub async fn items_of_type_under<E: EntityType + DeserializeOwned>(
+        &self,
+        pk: &str,
+    ) -> Result<Vec<E>, Error> {
+        let items = self
+            .store
+            .query(&self.table_name)
+            .partition_key(&self.partition_key, AttributeValue::S(pk.to_string()))
+            .sort_key_begins_with(&self.sort_key, raw_prefixed(E::PREFIX, ""))
+            .all()
+            .await?;
+
+        Ok(serde_dynamo::from_items(items)?)
+    }
+
+    fn key<E: EntityType>(&self, pk: &str, id: &str) -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            (
+                self.partition_key.clone(),
+                AttributeValue::S(pk.to_string()),
+            ),
+            (self.sort_key.clone(), AttributeValue::S(E::key(id))),
+        ])
+    }
+}
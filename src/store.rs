@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use aws_config::{BehaviorVersion, SdkConfig};
+use aws_sdk_dynamodb::Client;
+
+use crate::{
+    naming::NamingConvention, options::RequestOptions, read_preference::ReadPreference, Error,
+    TableDescription,
+};
+
+#[derive(Debug, Clone)]
+pub struct DynamoDbStore {
+    client: Client,
+    read_client: Option<Client>,
+    naming: NamingConvention,
+    defaults: RequestOptions,
+    descriptions: Arc<Mutex<HashMap<String, TableDescription>>>,
+}
+
+impl DynamoDbStore {
+    pub async fn new() -> Self {
+        let config = aws_config::load_from_env().await;
+
+        Self::from_config(&config)
+    }
+
+    /// Loads credentials and region from the named profile of the shared config files
+    /// (`~/.aws/config` and `~/.aws/credentials`), e.g. one profile per account in local
+    /// development.
+    pub async fn from_profile(profile_name: &str) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .profile_name(profile_name)
+            .load()
+            .await;
+
+        Self::from_config(&config)
+    }
+
+    pub fn from_config(config: &SdkConfig) -> Self {
+        Self::from_client(Client::new(config))
+    }
+
+    pub fn from_client(client: Client) -> Self {
+        Self {
+            client,
+            read_client: None,
+            naming: NamingConvention::default(),
+            defaults: RequestOptions::default(),
+            descriptions: Arc::default(),
+        }
+    }
+
+    /// Sets the naming convention that tables bound from this store start with.
+    pub fn with_naming(mut self, naming: NamingConvention) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Sets request defaults, e.g. always consistent reads, for every call of this
+    /// store and the tables bound from it. Settings passed to a single call win.
+    pub fn with_defaults(mut self, defaults: RequestOptions) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Sends gets, queries and scans to the replica `preference` picks, for Global
+    /// Tables; every write still goes to the region of [`client`](Self::client).
+    ///
+    /// Consistent reads from a replica are only consistent with writes made in that
+    /// replica's region.
+    pub fn with_read_preference(mut self, preference: ReadPreference) -> Self {
+        self.read_client = preference.region(self.client.config()).map(|region| {
+            Client::from_conf(self.client.config().to_builder().region(region).build())
+        });
+        self
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The client gets, queries and scans are sent with; see
+    /// [`with_read_preference`](Self::with_read_preference).
+    pub fn read_client(&self) -> &Client {
+        self.read_client.as_ref().unwrap_or(&self.client)
+    }
+
+    pub fn naming(&self) -> NamingConvention {
+        self.naming
+    }
+
+    pub fn defaults(&self) -> &RequestOptions {
+        &self.defaults
+    }
+
+    /// Describes `table_name` once per store and then returns the cached description.
+    ///
+    /// Key schema, billing mode and throughput rarely change; use
+    /// [`describe_table`](Self::describe_table) for current statuses and item counts.
+    pub async fn cached_description(&self, table_name: &str) -> Result<TableDescription, Error> {
+        if let Some(description) = self.cached_descriptions().get(table_name) {
+            return Ok(description.clone());
+        }
+
+        let description = self.describe_table(table_name).await?;
+        self.cached_descriptions()
+            .insert(table_name.to_string(), description.clone());
+        Ok(description)
+    }
+
+    /// Names of the partition and sort key of `table_name`.
+    pub(crate) async fn table_key_names(&self, table_name: &str) -> Result<Vec<String>, Error> {
+        let description = self.cached_description(table_name).await?;
+        let mut names = vec![description.partition_key.name];
+        names.extend(description.sort_key.map(|sort_key| sort_key.name));
+        Ok(names)
+    }
+
+    fn cached_descriptions(&self) -> MutexGuard<'_, HashMap<String, TableDescription>> {
+        self.descriptions
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
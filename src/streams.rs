@@ -0,0 +1,645 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodbstreams::types::{Record, Shard, ShardIteratorType};
+use futures_util::future::try_join_all;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    change_event::ChangeEvent,
+    expression::CheckedExpressions,
+    instrument::Operation,
+    schema::{ScalarType, TableSchema},
+    DynamoDbStore, Error,
+};
+
+const CONSUMER_ATTRIBUTE: &str = "consumer";
+const SHARD_ATTRIBUTE: &str = "shard_id";
+const SEQUENCE_NUMBER_ATTRIBUTE: &str = "sequence_number";
+const FINISHED_ATTRIBUTE: &str = "finished";
+const WAIT_TIMEOUT: Duration = Duration::from_secs(600);
+/// How long a listed set of shards is reused before the stream is described again.
+const SHARD_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Checkpoint {
+    sequence_number: Option<String>,
+    finished: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ShardList {
+    stream_arn: String,
+    shards: Vec<Shard>,
+    listed_at: Instant,
+}
+
+/// Records read by one `GetRecords` call and the iterator to continue with, which is
+/// `None` once a closed shard is drained.
+#[derive(Debug, Default)]
+struct Batch {
+    records: Vec<Record>,
+    next_iterator: Option<String>,
+}
+
+/// Reads a table's stream shard by shard and persists progress in a checkpoint table,
+/// so a restarted consumer resumes where the previous one stopped.
+#[derive(Debug, Clone)]
+pub struct StreamConsumer {
+    store: DynamoDbStore,
+    streams: aws_sdk_dynamodbstreams::Client,
+    table_name: String,
+    checkpoint_table: String,
+    consumer_name: String,
+    poll_interval: Duration,
+    batch_size: Option<i32>,
+    shard_list: Arc<Mutex<Option<ShardList>>>,
+}
+
+impl StreamConsumer {
+    pub fn new(
+        store: &DynamoDbStore,
+        streams: aws_sdk_dynamodbstreams::Client,
+        table_name: impl Into<String>,
+        checkpoint_table: impl Into<String>,
+    ) -> Self {
+        Self {
+            store: store.clone(),
+            streams,
+            table_name: table_name.into(),
+            checkpoint_table: checkpoint_table.into(),
+            consumer_name: "default".to_string(),
+            poll_interval: Duration::from_secs(1),
+            batch_size: None,
+            shard_list: Arc::default(),
+        }
+    }
+
+    /// Keeps the checkpoints of independent consumers sharing one checkpoint table apart.
+    pub fn consumer_name(mut self, consumer_name: impl Into<String>) -> Self {
+        self.consumer_name = consumer_name.into();
+        self
+    }
+
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: i32) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Polls the stream until the handler or a request fails.
+    ///
+    /// Delivery is at least once: a batch of records is checkpointed only after the
+    /// handler accepted every event in it.
+    pub async fn run<T, F, Fut>(&self, mut handler: F) -> Result<(), Error>
+    where
+        T: DeserializeOwned,
+        F: FnMut(ChangeEvent<T>) -> Fut,
+        Fut: Future<Output = Result<(), Error>>,
+    {
+        self.ensure_checkpoint_table().await?;
+
+        loop {
+            self.poll_once(&mut handler).await?;
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Reads every shard that is ready once and returns the number of handled events.
+    ///
+    /// The first batch of every ready shard is requested concurrently; the handler still
+    /// sees the events one at a time. The shard list is cached between polls and listed
+    /// again after a minute or once a shard is drained, when its children appear.
+    ///
+    /// Unlike [`run`](Self::run), this expects the checkpoint table to exist already.
+    pub async fn poll_once<T, F, Fut>(&self, handler: &mut F) -> Result<usize, Error>
+    where
+        T: DeserializeOwned,
+        F: FnMut(ChangeEvent<T>) -> Fut,
+        Fut: Future<Output = Result<(), Error>>,
+    {
+        let ShardList {
+            stream_arn, shards, ..
+        } = self.shard_list().await?;
+        let checkpoints = self.checkpoints(&stream_arn).await?;
+        let ready = ready_shards(&shards, &checkpoints);
+
+        let first_batches =
+            try_join_all(ready.iter().map(|(shard_id, _, checkpoint)| {
+                self.open_shard(&stream_arn, shard_id, checkpoint)
+            }))
+            .await?;
+
+        let mut handled = 0;
+        let mut drained = false;
+        for ((shard_id, closed, checkpoint), batch) in ready.into_iter().zip(first_batches) {
+            let (count, checkpoint) = self
+                .read_shard(&stream_arn, shard_id, closed, checkpoint, batch, handler)
+                .await?;
+            handled += count;
+            drained |= checkpoint.finished;
+        }
+
+        if drained {
+            *lock(&self.shard_list) = None;
+        }
+
+        Ok(handled)
+    }
+
+    /// Positions an iterator after the checkpoint and reads the first batch with it.
+    ///
+    /// A checkpoint whose records were already trimmed from the stream, after a consumer
+    /// was stopped for longer than the 24 hour retention, resumes at the oldest record
+    /// still available; the records in between are lost.
+    async fn open_shard(
+        &self,
+        stream_arn: &str,
+        shard_id: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<Batch, Error> {
+        let after = checkpoint.sequence_number.as_deref();
+        match self.read_from(stream_arn, shard_id, after).await {
+            Err(err) if restart_at_trim_horizon(&err, after) => {
+                self.read_from(stream_arn, shard_id, None).await
+            }
+            result => result,
+        }
+    }
+
+    /// Reads the first batch after `after`, or from the oldest record without it.
+    async fn read_from(
+        &self,
+        stream_arn: &str,
+        shard_id: &str,
+        after: Option<&str>,
+    ) -> Result<Batch, Error> {
+        let request = self
+            .streams
+            .get_shard_iterator()
+            .stream_arn(stream_arn)
+            .shard_id(shard_id);
+        let request = match after {
+            Some(sequence_number) => request
+                .shard_iterator_type(ShardIteratorType::AfterSequenceNumber)
+                .sequence_number(sequence_number),
+            None => request.shard_iterator_type(ShardIteratorType::TrimHorizon),
+        };
+        let output = Operation::new("GetShardIterator", &self.table_name)
+            .send(request.send())
+            .await
+            .map_err(aws_sdk_dynamodbstreams::Error::from)?;
+
+        match output.shard_iterator {
+            Some(iterator) => self.get_records(iterator).await,
+            None => Ok(Batch::default()),
+        }
+    }
+
+    async fn get_records(&self, iterator: String) -> Result<Batch, Error> {
+        let request = self
+            .streams
+            .get_records()
+            .shard_iterator(iterator)
+            .set_limit(self.batch_size)
+            .send();
+        let output = Operation::new("GetRecords", &self.table_name)
+            .send(request)
+            .await
+            .map_err(aws_sdk_dynamodbstreams::Error::from)?;
+
+        Ok(Batch {
+            records: output.records.unwrap_or_default(),
+            next_iterator: output.next_shard_iterator,
+        })
+    }
+
+    async fn read_shard<T, F, Fut>(
+        &self,
+        stream_arn: &str,
+        shard_id: &str,
+        closed: bool,
+        mut checkpoint: Checkpoint,
+        mut batch: Batch,
+        handler: &mut F,
+    ) -> Result<(usize, Checkpoint), Error>
+    where
+        T: DeserializeOwned,
+        F: FnMut(ChangeEvent<T>) -> Fut,
+        Fut: Future<Output = Result<(), Error>>,
+    {
+        let mut handled = 0;
+        loop {
+            let had_records = !batch.records.is_empty();
+            for record in batch.records {
+                let sequence_number = record
+                    .dynamodb
+                    .as_ref()
+                    .and_then(|change| change.sequence_number.clone());
+                handler(ChangeEvent::from_stream_record(record)?).await?;
+                handled += 1;
+                if sequence_number.is_some() {
+                    checkpoint.sequence_number = sequence_number;
+                }
+            }
+
+            let Some(iterator) = batch.next_iterator else {
+                checkpoint.finished = true;
+                self.save_checkpoint(stream_arn, shard_id, &checkpoint)
+                    .await?;
+                break;
+            };
+            if had_records {
+                self.save_checkpoint(stream_arn, shard_id, &checkpoint)
+                    .await?;
+            }
+
+            // An open shard is caught up once a batch comes back empty. A closed one can
+            // return empty batches before the end, so it is followed until the iterator
+            // runs out.
+            if !had_records && !closed {
+                break;
+            }
+
+            // Iterators expire after 15 minutes, e.g. while a slow handler runs; a new
+            // one continues from the checkpoint.
+            batch = match self.get_records(iterator).await {
+                Err(err) if err.is_expired_iterator() => {
+                    self.open_shard(stream_arn, shard_id, &checkpoint).await?
+                }
+                result => result?,
+            };
+        }
+
+        Ok((handled, checkpoint))
+    }
+
+    /// The stream and its shards, listed again once the cached list is too old or gone.
+    async fn shard_list(&self) -> Result<ShardList, Error> {
+        if let Some(list) = lock(&self.shard_list)
+            .as_ref()
+            .filter(|list| list.listed_at.elapsed() < SHARD_REFRESH_INTERVAL)
+        {
+            return Ok(list.clone());
+        }
+
+        let stream_arn = self.stream_arn().await?;
+        let shards = self.shards(&stream_arn).await?;
+        let list = ShardList {
+            stream_arn,
+            shards,
+            listed_at: Instant::now(),
+        };
+        *lock(&self.shard_list) = Some(list.clone());
+
+        Ok(list)
+    }
+
+    async fn stream_arn(&self) -> Result<String, Error> {
+        let operation = Operation::new("DescribeTable", &self.table_name);
+        let request = self
+            .store
+            .client()
+            .describe_table()
+            .table_name(&self.table_name)
+            .send();
+        let output = operation.send(request).await?;
+
+        output
+            .table
+            .and_then(|table| table.latest_stream_arn)
+            .ok_or_else(|| {
+                Error::Validation(format!("table {} has no stream enabled", self.table_name))
+            })
+    }
+
+    async fn shards(&self, stream_arn: &str) -> Result<Vec<Shard>, Error> {
+        let mut shards = Vec::new();
+        let mut start_after = None;
+
+        loop {
+            let request = self
+                .streams
+                .describe_stream()
+                .stream_arn(stream_arn)
+                .set_exclusive_start_shard_id(start_after)
+                .send();
+            let output = Operation::new("DescribeStream", &self.table_name)
+                .send(request)
+                .await
+                .map_err(aws_sdk_dynamodbstreams::Error::from)?;
+
+            let Some(description) = output.stream_description else {
+                break;
+            };
+            shards.extend(description.shards.unwrap_or_default());
+            start_after = description.last_evaluated_shard_id;
+            if start_after.is_none() {
+                break;
+            }
+        }
+
+        Ok(shards)
+    }
+
+    fn consumer_key(&self, stream_arn: &str) -> AttributeValue {
+        AttributeValue::S(format!("{}#{stream_arn}", self.consumer_name))
+    }
+
+    async fn checkpoints(&self, stream_arn: &str) -> Result<HashMap<String, Checkpoint>, Error> {
+        let mut checkpoints = HashMap::new();
+        let mut start_after = None;
+
+        loop {
+            let operation = Operation::new("Query", &self.checkpoint_table);
+            let request = self
+                .store
+                .client()
+                .query()
+                .table_name(&self.checkpoint_table)
+                .key_condition_expression("#consumer = :consumer")
+                .expression_attribute_names("#consumer", CONSUMER_ATTRIBUTE)
+                .expression_attribute_values(":consumer", self.consumer_key(stream_arn))
+                .consistent_read(true)
+                .set_exclusive_start_key(start_after)
+                .checked()?
+                .send();
+            let output = operation.send(request).await?;
+
+            checkpoints.extend(
+                output
+                    .items
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(read_checkpoint),
+            );
+
+            start_after = output.last_evaluated_key;
+            if start_after.is_none() {
+                break;
+            }
+        }
+
+        Ok(checkpoints)
+    }
+
+    async fn save_checkpoint(
+        &self,
+        stream_arn: &str,
+        shard_id: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<(), Error> {
+        let item = checkpoint_item(self.consumer_key(stream_arn), shard_id, checkpoint);
+        let operation = Operation::new("PutItem", &self.checkpoint_table).attributes(&item);
+        let request = self
+            .store
+            .client()
+            .put_item()
+            .table_name(&self.checkpoint_table)
+            .set_item(Some(item))
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+
+    async fn ensure_checkpoint_table(&self) -> Result<(), Error> {
+        let schema = TableSchema::new(CONSUMER_ATTRIBUTE, ScalarType::S)
+            .sort_key(SHARD_ATTRIBUTE, ScalarType::S);
+
+        match self
+            .store
+            .create_table_from_schema(&self.checkpoint_table, &schema)
+            .await
+        {
+            Ok(_) => {}
+            Err(err) if err.is_resource_in_use() => {}
+            Err(err) => return Err(err),
+        }
+
+        self.store
+            .wait_until_active(&self.checkpoint_table, WAIT_TIMEOUT)
+            .await
+    }
+}
+
+/// The shards to read now with where each resumes: unfinished shards whose parent is
+/// drained or no longer listed, each with whether it is closed and its checkpoint.
+fn ready_shards<'a>(
+    shards: &'a [Shard],
+    checkpoints: &HashMap<String, Checkpoint>,
+) -> Vec<(&'a str, bool, Checkpoint)> {
+    let known: HashSet<&str> = shards.iter().filter_map(Shard::shard_id).collect();
+
+    let mut ready = Vec::new();
+    for shard in shards {
+        let Some(shard_id) = shard.shard_id() else {
+            continue;
+        };
+        let checkpoint = checkpoints.get(shard_id).cloned().unwrap_or_default();
+        if checkpoint.finished {
+            continue;
+        }
+
+        // A child shard is only read once its parent is drained, which keeps the
+        // changes of a single item in order.
+        let parent_pending = shard.parent_shard_id().is_some_and(|parent| {
+            known.contains(parent)
+                && !checkpoints
+                    .get(parent)
+                    .is_some_and(|checkpoint| checkpoint.finished)
+        });
+        if parent_pending {
+            continue;
+        }
+
+        let closed = shard
+            .sequence_number_range()
+            .and_then(|range| range.ending_sequence_number())
+            .is_some();
+        ready.push((shard_id, closed, checkpoint));
+    }
+    ready
+}
+
+/// Whether reading after the checkpoint `after` failed because its records were
+/// trimmed, so the shard has to be read from its oldest record instead.
+fn restart_at_trim_horizon(err: &Error, after: Option<&str>) -> bool {
+    after.is_some() && err.is_trimmed_data_access()
+}
+
+fn checkpoint_item(
+    consumer_key: AttributeValue,
+    shard_id: &str,
+    checkpoint: &Checkpoint,
+) -> HashMap<String, AttributeValue> {
+    let mut item = HashMap::from([
+        (CONSUMER_ATTRIBUTE.to_string(), consumer_key),
+        (
+            SHARD_ATTRIBUTE.to_string(),
+            AttributeValue::S(shard_id.to_string()),
+        ),
+        (
+            FINISHED_ATTRIBUTE.to_string(),
+            AttributeValue::Bool(checkpoint.finished),
+        ),
+    ]);
+    if let Some(sequence_number) = &checkpoint.sequence_number {
+        item.insert(
+            SEQUENCE_NUMBER_ATTRIBUTE.to_string(),
+            AttributeValue::S(sequence_number.clone()),
+        );
+    }
+    item
+}
+
+/// The shard id and checkpoint stored in `item`, or `None` for an item without a
+/// shard id.
+fn read_checkpoint(item: &HashMap<String, AttributeValue>) -> Option<(String, Checkpoint)> {
+    let Some(AttributeValue::S(shard_id)) = item.get(SHARD_ATTRIBUTE) else {
+        return None;
+    };
+    let sequence_number = match item.get(SEQUENCE_NUMBER_ATTRIBUTE) {
+        Some(AttributeValue::S(sequence_number)) => Some(sequence_number.clone()),
+        _ => None,
+    };
+    let finished = matches!(
+        item.get(FINISHED_ATTRIBUTE),
+        Some(AttributeValue::Bool(true))
+    );
+
+    Some((
+        shard_id.clone(),
+        Checkpoint {
+            sequence_number,
+            finished,
+        },
+    ))
+}
+
+fn lock(shard_list: &Mutex<Option<ShardList>>) -> MutexGuard<'_, Option<ShardList>> {
+    shard_list
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use aws_sdk_dynamodbstreams::types::{
+        error::{ExpiredIteratorException, TrimmedDataAccessException},
+        SequenceNumberRange,
+    };
+
+    use super::*;
+
+    fn shard(shard_id: &str, parent: Option<&str>, closed: bool) -> Shard {
+        let mut range = SequenceNumberRange::builder().starting_sequence_number("100");
+        if closed {
+            range = range.ending_sequence_number("900");
+        }
+        Shard::builder()
+            .shard_id(shard_id)
+            .set_parent_shard_id(parent.map(str::to_string))
+            .sequence_number_range(range.build())
+            .build()
+    }
+
+    fn checkpoint(sequence_number: Option<&str>, finished: bool) -> Checkpoint {
+        Checkpoint {
+            sequence_number: sequence_number.map(str::to_string),
+            finished,
+        }
+    }
+
+    fn ready_ids<'a>(
+        shards: &'a [Shard],
+        checkpoints: &HashMap<String, Checkpoint>,
+    ) -> Vec<&'a str> {
+        ready_shards(shards, checkpoints)
+            .into_iter()
+            .map(|(shard_id, _, _)| shard_id)
+            .collect()
+    }
+
+    #[test]
+    fn children_of_a_split_wait_until_their_parent_is_drained() {
+        let shards = [
+            shard("parent", None, true),
+            shard("left", Some("parent"), false),
+            shard("right", Some("parent"), false),
+        ];
+
+        let mut checkpoints = HashMap::new();
+        assert_eq!(ready_ids(&shards, &checkpoints), ["parent"]);
+
+        checkpoints.insert("parent".to_string(), checkpoint(Some("150"), false));
+        assert_eq!(ready_ids(&shards, &checkpoints), ["parent"]);
+
+        checkpoints.insert("parent".to_string(), checkpoint(Some("900"), true));
+        assert_eq!(ready_ids(&shards, &checkpoints), ["left", "right"]);
+    }
+
+    #[test]
+    fn children_of_a_parent_no_longer_listed_are_ready() {
+        let shards = [shard("child", Some("expired-parent"), false)];
+
+        assert_eq!(ready_ids(&shards, &HashMap::new()), ["child"]);
+    }
+
+    #[test]
+    fn shards_resume_from_their_checkpoint() {
+        let shards = [shard("open", None, false), shard("closed", None, true)];
+        let checkpoints = HashMap::from([
+            ("open".to_string(), checkpoint(Some("120"), false)),
+            ("closed".to_string(), checkpoint(Some("130"), false)),
+        ]);
+
+        assert_eq!(
+            ready_shards(&shards, &checkpoints),
+            [
+                ("open", false, checkpoint(Some("120"), false)),
+                ("closed", true, checkpoint(Some("130"), false)),
+            ]
+        );
+    }
+
+    #[test]
+    fn checkpoints_read_back_as_saved() {
+        let consumer_key = AttributeValue::S("default#arn".to_string());
+
+        for saved in [
+            checkpoint(None, false),
+            checkpoint(Some("120"), false),
+            checkpoint(Some("900"), true),
+        ] {
+            let item = checkpoint_item(consumer_key.clone(), "shard-1", &saved);
+            assert_eq!(read_checkpoint(&item), Some(("shard-1".to_string(), saved)));
+        }
+    }
+
+    #[test]
+    fn trimmed_checkpoints_restart_at_the_trim_horizon() {
+        let trimmed = Error::from(aws_sdk_dynamodbstreams::Error::TrimmedDataAccessException(
+            TrimmedDataAccessException::builder().build(),
+        ));
+        let expired = Error::from(aws_sdk_dynamodbstreams::Error::ExpiredIteratorException(
+            ExpiredIteratorException::builder().build(),
+        ));
+
+        assert!(restart_at_trim_horizon(&trimmed, Some("120")));
+        // Reading from the trim horizon already failed; there is nothing older.
+        assert!(!restart_at_trim_horizon(&trimmed, None));
+        assert!(!restart_at_trim_horizon(&expired, Some("120")));
+        assert!(expired.is_expired_iterator());
+        assert!(!trimmed.is_expired_iterator());
+    }
+}
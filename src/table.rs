@@ -0,0 +1,246 @@
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::OnceCell;
+
+#[cfg(feature = "dev-auto-create")]
+use crate::schema::TableSchema;
+use crate::{
+    empty_values::EmptyValuePolicy, naming::NamingConvention, options::RequestOptions,
+    query::Query, retry::RetryConfig, scan::Scan, ttl::DEFAULT_TTL_ATTRIBUTE,
+    versioned::DEFAULT_VERSION_ATTRIBUTE, DynamoDbStore, Error,
+};
+
+#[derive(Debug, Clone)]
+pub struct TableBoundStore {
+    store: DynamoDbStore,
+    table_name: String,
+    ttl_attribute: String,
+    version_attribute: String,
+    empty_values: EmptyValuePolicy,
+    naming: NamingConvention,
+    defaults: RequestOptions,
+    key_names: Arc<OnceCell<Vec<String>>>,
+    #[cfg(feature = "dev-auto-create")]
+    auto_create: Option<TableSchema>,
+}
+
+impl DynamoDbStore {
+    pub fn for_table(&self, table_name: impl Into<String>) -> TableBoundStore {
+        TableBoundStore {
+            store: self.clone(),
+            table_name: table_name.into(),
+            ttl_attribute: DEFAULT_TTL_ATTRIBUTE.to_string(),
+            version_attribute: DEFAULT_VERSION_ATTRIBUTE.to_string(),
+            empty_values: EmptyValuePolicy::default(),
+            naming: self.naming(),
+            defaults: self.defaults().clone(),
+            key_names: Arc::default(),
+            #[cfg(feature = "dev-auto-create")]
+            auto_create: None,
+        }
+    }
+}
+
+impl TableBoundStore {
+    pub fn with_ttl_attribute(mut self, attribute_name: impl Into<String>) -> Self {
+        self.ttl_attribute = attribute_name.into();
+        self
+    }
+
+    pub fn with_version_attribute(mut self, attribute_name: impl Into<String>) -> Self {
+        self.version_attribute = attribute_name.into();
+        self
+    }
+
+    /// Applies `policy` to every item this table serializes for writing.
+    pub fn with_empty_values(mut self, policy: EmptyValuePolicy) -> Self {
+        self.empty_values = policy;
+        self
+    }
+
+    /// Spells top-level attribute names of items and keys in `naming`, overriding the
+    /// store's convention.
+    pub fn with_naming(mut self, naming: NamingConvention) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Creates the table from `schema` the first time an operation finds it missing.
+    ///
+    /// Only available with the `dev-auto-create` feature, which is meant for
+    /// `[dev-dependencies]` and DynamoDB Local, never for production builds.
+    #[cfg(feature = "dev-auto-create")]
+    pub fn auto_create(mut self, schema: TableSchema) -> Self {
+        self.auto_create = Some(schema);
+        self
+    }
+
+    /// Replaces the request defaults inherited from the store for calls on this table.
+    pub fn with_defaults(mut self, defaults: RequestOptions) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Retries this table's calls with `retry` instead of the store's settings, e.g.
+    /// patient backoff for a high-volume table and failing fast for a latency-sensitive
+    /// one. Also bounds the retries of unprocessed batch items.
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.defaults.retry = Some(retry);
+        self
+    }
+
+    pub fn store(&self) -> &DynamoDbStore {
+        &self.store
+    }
+
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn ttl_attribute(&self) -> &str {
+        &self.ttl_attribute
+    }
+
+    pub fn version_attribute(&self) -> &str {
+        &self.version_attribute
+    }
+
+    pub fn naming(&self) -> NamingConvention {
+        self.naming
+    }
+
+    pub fn defaults(&self) -> &RequestOptions {
+        &self.defaults
+    }
+
+    /// Retry settings for the unprocessed items of batch calls on this table.
+    pub(crate) fn batch_retry(&self) -> RetryConfig {
+        self.defaults.retry.unwrap_or_default()
+    }
+
+    /// Starts a query on this table with the table's defaults applied.
+    pub fn query(&self) -> Query<'_> {
+        self.store.query(&self.table_name).defaults(&self.defaults)
+    }
+
+    /// Starts a scan of this table with the table's defaults applied.
+    pub fn scan(&self) -> Scan<'_> {
+        self.store.scan(&self.table_name).defaults(&self.defaults)
+    }
+
+    pub async fn put<T: Serialize>(&self, item: &T) -> Result<(), Error> {
+        self.run(|| async {
+            let item = self.to_item(item)?;
+            self.store
+                .put_item_with_options(&self.table_name, item, &self.defaults)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get<K: Serialize, T: DeserializeOwned>(
+        &self,
+        key: &K,
+    ) -> Result<Option<T>, Error> {
+        self.run(|| async {
+            let key = self.to_key(key)?;
+
+            let output = self
+                .store
+                .get_item_with_options(&self.table_name, key, &self.defaults)
+                .await?;
+            match output.item {
+                Some(item) => Ok(Some(self.read_item(item)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    pub async fn delete<K: Serialize>(&self, key: &K) -> Result<(), Error> {
+        self.run(|| async {
+            let key = self.to_key(key)?;
+            self.store
+                .delete_item_with_options(&self.table_name, key, &self.defaults)
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Serializes an item for writing, applying the table's naming convention and empty
+    /// value policy.
+    pub(crate) fn to_item<T: Serialize>(
+        &self,
+        item: &T,
+    ) -> Result<HashMap<String, AttributeValue>, Error> {
+        self.prepare_item(serde_dynamo::to_item(item)?)
+    }
+
+    /// Applies the table's naming convention and empty value policy to a raw item.
+    pub(crate) fn prepare_item(
+        &self,
+        item: HashMap<String, AttributeValue>,
+    ) -> Result<HashMap<String, AttributeValue>, Error> {
+        self.empty_values.apply(self.naming.rename_item(item))
+    }
+
+    pub(crate) fn to_key<K: Serialize>(
+        &self,
+        key: &K,
+    ) -> Result<HashMap<String, AttributeValue>, Error> {
+        Ok(self.naming.rename_item(serde_dynamo::to_item(key)?))
+    }
+
+    pub(crate) fn read_item<T: DeserializeOwned>(
+        &self,
+        item: HashMap<String, AttributeValue>,
+    ) -> Result<T, Error> {
+        Ok(serde_dynamo::from_item(self.naming.restore_item(item))?)
+    }
+
+    /// Names of the partition and sort key, described once and then cached.
+    pub(crate) async fn key_names(&self) -> Result<&[String], Error> {
+        let names = self
+            .key_names
+            .get_or_try_init(|| async {
+                let description = self.store.describe_table(&self.table_name).await?;
+                let mut names = vec![description.partition_key.name];
+                names.extend(description.sort_key.map(|sort_key| sort_key.name));
+                Ok::<_, Error>(names)
+            })
+            .await?;
+
+        Ok(names)
+    }
+
+    pub(crate) async fn run<T, F, Fut>(&self, call: F) -> Result<T, Error>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        #[cfg(feature = "dev-auto-create")]
+        if let Some(schema) = &self.auto_create {
+            return match call().await {
+                Err(err) if err.is_resource_not_found() => {
+                    self.store
+                        .create_table_from_schema(&self.table_name, schema)
+                        .await?;
+                    self.store
+                        .wait_until_active(&self.table_name, AUTO_CREATE_TIMEOUT)
+                        .await?;
+                    call().await
+                }
+                result => result,
+            };
+        }
+
+        call().await
+    }
+}
+
+#[cfg(feature = "dev-auto-create")]
+const AUTO_CREATE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    filter::Filter,
+    keys::{raw_prefixed, KEY_SEPARATOR},
+    DynamoDbStore, Error,
+};
+
+/// Access scoped to one tenant of a shared table.
+///
+/// Partition key values are stored as `"{tenant}#{value}"` and the prefix is removed
+/// again from every item read, so callers work with unprefixed keys.
+#[derive(Debug, Clone)]
+pub struct TenantStore {
+    store: DynamoDbStore,
+    tenant: String,
+    partition_key: String,
+    tenant_attribute: Option<String>,
+}
+
+impl DynamoDbStore {
+    pub fn for_tenant(&self, tenant: impl Into<String>) -> TenantStore {
+        TenantStore {
+            store: self.clone(),
+            tenant: tenant.into(),
+            partition_key: "PK".to_string(),
+            tenant_attribute: None,
+        }
+    }
+}
+
+impl TenantStore {
+    pub fn with_partition_key(mut self, attribute_name: impl Into<String>) -> Self {
+        self.partition_key = attribute_name.into();
+        self
+    }
+
+    /// Also writes the tenant id to `attribute_name` and only returns query results
+    /// whose attribute matches, which guards indexes that do not use the prefixed key.
+    pub fn with_tenant_attribute(mut self, attribute_name: impl Into<String>) -> Self {
+        self.tenant_attribute = Some(attribute_name.into());
+        self
+    }
+
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    /// The stored form of the partition key value `value`.
+    pub fn scoped_key(&self, value: &str) -> Result<String, Error> {
+        // `acme#x` could otherwise read the keys of tenant `acme` whose values start with `x#`.
+        if self.tenant.is_empty() || self.tenant.contains(KEY_SEPARATOR) {
+            return Err(Error::Validation(format!(
+                "tenant id `{}` must be non-empty and must not contain `{KEY_SEPARATOR}`",
+                self.tenant
+            )));
+        }
+
+        Ok(raw_prefixed(&self.tenant, value))
+    }
+
+    pub async fn put<T: Serialize>(&self, table_name: &str, item: &T) -> Result<(), Error> {
+        let mut item: HashMap<String, AttributeValue> = serde_dynamo::to_item(item)?;
+        self.scope(&mut item)?;
+        if let Some(attribute) = &self.tenant_attribute {
+            item.insert(attribute.clone(), AttributeValue::S(self.tenant.clone()));
+        }
+
+        self.store.put_item(table_name, item).await?;
+        Ok(())
+    }
+
+    pub async fn get<K: Serialize, T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        key: &K,
+    ) -> Result<Option<T>, Error> {
+        let mut key: HashMap<String, AttributeValue> = serde_dynamo::to_item(key)?;
+        self.scope(&mut key)?;
+
+        match self.store.get_item(table_name, key).await? {
+            Some(item) => Ok(Some(self.unscope(item)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn delete<K: Serialize>(&self, table_name: &str, key: &K) -> Result<(), Error> {
+        let mut key: HashMap<String, AttributeValue> = serde_dynamo::to_item(key)?;
+        self.scope(&mut key)?;
+
+        self.store.delete_item(table_name, key).await?;
+        Ok(())
+    }
+
+    /// Returns every item of this tenant stored under the partition key value `pk`.
+    pub async fn query<T: DeserializeOwned>(
+        &self,
+        table_name: &str,
+        pk: &str,
+    ) -> Result<Vec<T>, Error> {
+        let mut query = self
+            .store
+            .query(table_name)
+            .partition_key(&self.partition_key, AttributeValue::S(self.scoped_key(pk)?));
+        if let Some(attribute) = &self.tenant_attribute {
+            query = query.filter(Filter::eq(
+                attribute,
+                AttributeValue::S(self.tenant.clone()),
+            ));
+        }
+
+        query
+            .all()
+            .await?
+            .into_iter()
+            .map(|item| self.unscope(item))
+            .collect()
+    }
+
+    fn scope(&self, item: &mut HashMap<String, AttributeValue>) -> Result<(), Error> {
+        match item.get_mut(&self.partition_key) {
+            Some(AttributeValue::S(value)) => {
+                *value = self.scoped_key(value)?;
+                Ok(())
+            }
+            Some(_) => Err(Error::Validation(format!(
+                "tenant-scoped partition key `{}` must be a string",
+                self.partition_key
+            ))),
+            None => Err(Error::Validation(format!(
+                "item has no partition key attribute `{}`",
+                self.partition_key
+            ))),
+        }
+    }
+
+    fn unscope<T: DeserializeOwned>(
+        &self,
+        mut item: HashMap<String, AttributeValue>,
+    ) -> Result<T, Error> {
+        if let Some(AttributeValue::S(value)) = item.get_mut(&self.partition_key) {
+            let unscoped = value
+                .strip_prefix(self.tenant.as_str())
+                .and_then(|rest| rest.strip_prefix(KEY_SEPARATOR))
+                .ok_or_else(|| {
+                    Error::Validation(format!(
+                        "item key `{value}` does not belong to tenant `{}`",
+                        self.tenant
+                    ))
+                })?;
+            *value = unscoped.to_string();
+        }
+
+        Ok(serde_dynamo::from_item(item)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::offline_store;
+
+    type Record = HashMap<String, String>;
+
+    fn tenant(id: &str) -> TenantStore {
+        offline_store().for_tenant(id)
+    }
+
+    fn keyed(pk: &str) -> HashMap<String, AttributeValue> {
+        HashMap::from([("PK".to_string(), AttributeValue::S(pk.to_string()))])
+    }
+
+    #[test]
+    fn keys_are_prefixed_with_the_tenant() {
+        let acme = tenant("acme");
+
+        let mut item = keyed("order#1");
+        acme.scope(&mut item).unwrap();
+        assert_eq!(item["PK"], AttributeValue::S("acme#order#1".to_string()));
+
+        let record: Record = acme.unscope(item).unwrap();
+        assert_eq!(record["PK"], "order#1");
+    }
+
+    #[test]
+    fn tenant_ids_that_could_overlap_are_rejected_everywhere() {
+        for id in ["", "acme#x"] {
+            let store = tenant(id);
+            assert!(matches!(store.scoped_key("1"), Err(Error::Validation(_))));
+            assert!(matches!(
+                store.scope(&mut keyed("1")),
+                Err(Error::Validation(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn queries_reject_invalid_tenants_before_any_call() {
+        let result = tenant("acme#x").query::<Record>("Orders", "1").await;
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn items_of_other_tenants_are_not_returned() {
+        let acme = tenant("acme");
+
+        for pk in ["globex#1", "acmex#1", "acme", "1"] {
+            assert!(matches!(
+                acme.unscope::<Record>(keyed(pk)),
+                Err(Error::Validation(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use aws_sdk_dynamodb::{
+    config::{BehaviorVersion, Credentials, Region},
+    Client, Config,
+};
+use testcontainers_modules::{
+    dynamodb_local::DynamoDb,
+    testcontainers::{core::IntoContainerPort, runners::AsyncRunner, ContainerAsync},
+};
+
+use crate::{entity::DynamoEntity, schema::TableSchema, DynamoDbStore, Error};
+
+const DYNAMODB_LOCAL_PORT: u16 = 8000;
+const TABLE_ACTIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A DynamoDB Local container for integration tests, stopped when dropped.
+pub struct LocalDynamoDb {
+    container: ContainerAsync<DynamoDb>,
+    endpoint_url: String,
+    store: DynamoDbStore,
+}
+
+impl LocalDynamoDb {
+    /// Starts DynamoDB Local in Docker and connects a store to it with dummy credentials.
+    pub async fn start() -> Result<Self, Error> {
+        let container = DynamoDb::default().start().await?;
+        let host = container.get_host().await?;
+        let port = container
+            .get_host_port_ipv4(DYNAMODB_LOCAL_PORT.tcp())
+            .await?;
+        let endpoint_url = format!("http://{host}:{port}");
+
+        let config = Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .endpoint_url(&endpoint_url)
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("local", "local", None, None, "test"))
+            .build();
+
+        Ok(Self {
+            container,
+            endpoint_url,
+            store: DynamoDbStore::from_client(Client::from_conf(config)),
+        })
+    }
+
+    pub fn store(&self) -> &DynamoDbStore {
+        &self.store
+    }
+
+    pub fn endpoint_url(&self) -> &str {
+        &self.endpoint_url
+    }
+
+    pub fn container(&self) -> &ContainerAsync<DynamoDb> {
+        &self.container
+    }
+
+    /// Creates `table_name` from `schema` and waits until it is active.
+    pub async fn create_table(&self, table_name: &str, schema: &TableSchema) -> Result<(), Error> {
+        self.store
+            .create_table_from_schema(table_name, schema)
+            .await?;
+        self.store
+            .wait_until_active(table_name, TABLE_ACTIVE_TIMEOUT)
+            .await
+    }
+
+    /// Creates the table of the entity `T` from `schema`.
+    pub async fn create_table_for<T: DynamoEntity>(
+        &self,
+        schema: &TableSchema,
+    ) -> Result<(), Error> {
+        self.create_table(T::TABLE, schema).await
+    }
+}
@@ -0,0 +1,689 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use aws_sdk_dynamodb::types::{
+    error::{ConditionalCheckFailedException, ResourceNotFoundException},
+    AttributeValue,
+};
+
+use crate::{
+    api::{DynamoStoreApi, Item, QueryRequest, StoreFuture},
+    filter::Filter,
+    limits::validate_item,
+    page::Page,
+    query::SortKeyCondition,
+    schema::{KeyAttribute, ScalarType, TableSchema},
+    Error,
+};
+
+/// An in-process [`DynamoStoreApi`] for unit tests.
+///
+/// Tables have to be created with their schema first. Keys are validated against it,
+/// conditions and filters are evaluated, and queries return items in sort key order in
+/// pages of at most [`InMemoryStore::with_page_size`] evaluated items.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryStore {
+    tables: Arc<Mutex<HashMap<String, MemoryTable>>>,
+    page_size: Option<usize>,
+}
+
+#[derive(Debug)]
+struct MemoryTable {
+    schema: TableSchema,
+    items: Vec<Item>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ends query pages after `page_size` evaluated items, like DynamoDB does at 1 MB.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size.max(1));
+        self
+    }
+
+    pub fn with_table(self, table_name: impl Into<String>, schema: TableSchema) -> Self {
+        self.create_table(table_name, schema);
+        self
+    }
+
+    /// Creates or replaces `table_name`, dropping its items.
+    pub fn create_table(&self, table_name: impl Into<String>, schema: TableSchema) {
+        self.tables().insert(
+            table_name.into(),
+            MemoryTable {
+                schema,
+                items: Vec::new(),
+            },
+        );
+    }
+
+    /// A snapshot of the items currently stored in `table_name`.
+    pub fn items(&self, table_name: &str) -> Vec<Item> {
+        self.tables()
+            .get(table_name)
+            .map(|table| table.items.clone())
+            .unwrap_or_default()
+    }
+
+    fn tables(&self) -> MutexGuard<'_, HashMap<String, MemoryTable>> {
+        self.tables
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn with_table_mut<T>(
+        &self,
+        table_name: &str,
+        f: impl FnOnce(&mut MemoryTable) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut tables = self.tables();
+        let table = tables.get_mut(table_name).ok_or_else(|| {
+            Error::from(aws_sdk_dynamodb::Error::ResourceNotFoundException(
+                ResourceNotFoundException::builder()
+                    .message(format!("Requested resource not found: {table_name}"))
+                    .build(),
+            ))
+        })?;
+        f(table)
+    }
+
+    fn put(&self, table_name: &str, item: Item, condition: Option<Filter>) -> Result<(), Error> {
+        validate_item(&item)?;
+        self.with_table_mut(table_name, |table| {
+            let key = table.key_of(&item, false)?;
+            let position = table.position(&key);
+            check_condition(
+                condition.as_ref(),
+                position.map(|index| &table.items[index]),
+            )?;
+
+            match position {
+                Some(index) => table.items[index] = item,
+                None => table.items.push(item),
+            }
+            Ok(())
+        })
+    }
+
+    fn get(&self, table_name: &str, key: Item) -> Result<Option<Item>, Error> {
+        self.with_table_mut(table_name, |table| {
+            let key = table.key_of(&key, true)?;
+            Ok(table.position(&key).map(|index| table.items[index].clone()))
+        })
+    }
+
+    fn delete(&self, table_name: &str, key: Item, condition: Option<Filter>) -> Result<(), Error> {
+        self.with_table_mut(table_name, |table| {
+            let key = table.key_of(&key, true)?;
+            let position = table.position(&key);
+            check_condition(
+                condition.as_ref(),
+                position.map(|index| &table.items[index]),
+            )?;
+
+            if let Some(index) = position {
+                table.items.remove(index);
+            }
+            Ok(())
+        })
+    }
+
+    fn query(&self, request: &QueryRequest) -> Result<Page, Error> {
+        self.with_table_mut(&request.table_name, |table| {
+            let (partition_key, sort_key) = table.query_keys(request.index_name.as_deref())?;
+            let (partition_name, partition_value) = &request.partition_key;
+            if *partition_name != partition_key.name {
+                return Err(Error::Validation(format!(
+                    "query partition key `{partition_name}` is not the key `{}`",
+                    partition_key.name
+                )));
+            }
+
+            let mut matching: Vec<&Item> = table
+                .items
+                .iter()
+                .filter(|item| item.get(partition_name) == Some(partition_value))
+                .filter(|item| match (&sort_key, &request.sort_key) {
+                    (Some(sort_key), _) if !item.contains_key(&sort_key.name) => false,
+                    (_, Some((name, condition))) => item
+                        .get(name)
+                        .is_some_and(|value| sort_key_matches(value, condition)),
+                    _ => true,
+                })
+                .collect();
+
+            if let Some(sort_key) = &sort_key {
+                matching.sort_by(|a, b| {
+                    compare(&a[&sort_key.name], &b[&sort_key.name]).unwrap_or(Ordering::Equal)
+                });
+            }
+            if !request.scan_forward {
+                matching.reverse();
+            }
+
+            let key_names = table.page_key_names(&partition_key, sort_key.as_ref());
+            if let Some(start) = &request.exclusive_start_key {
+                // The start item may have been deleted since; resume by sort key then.
+                let position = matching
+                    .iter()
+                    .position(|item| same_key(item, start, &key_names))
+                    .or_else(|| {
+                        let sort_key = &sort_key.as_ref()?.name;
+                        let start = start.get(sort_key)?;
+                        let resume = if request.scan_forward {
+                            Ordering::Greater
+                        } else {
+                            Ordering::Less
+                        };
+                        let next = matching
+                            .iter()
+                            .position(|item| compare(&item[sort_key], start) == Some(resume))
+                            .unwrap_or(matching.len());
+                        next.checked_sub(1)
+                    });
+                if let Some(position) = position {
+                    matching.drain(..=position);
+                }
+            }
+
+            let page_size = match (request.limit, self.page_size) {
+                (Some(limit), Some(page_size)) => (limit.max(0) as usize).min(page_size),
+                (Some(limit), None) => limit.max(0) as usize,
+                (None, Some(page_size)) => page_size,
+                (None, None) => usize::MAX,
+            };
+            let evaluated = &matching[..matching.len().min(page_size)];
+            let last_evaluated_key = (evaluated.len() < matching.len())
+                .then(|| evaluated.last())
+                .flatten()
+                .map(|item| project(item, &key_names));
+
+            Ok(Page {
+                items: evaluated
+                    .iter()
+                    .filter(|item| {
+                        request
+                            .filter
+                            .as_ref()
+                            .is_none_or(|filter| evaluate(filter, item))
+                    })
+                    .map(|item| (*item).clone())
+                    .collect(),
+                last_evaluated_key,
+                scanned_count: evaluated.len() as i32,
+            })
+        })
+    }
+}
+
+impl MemoryTable {
+    /// Extracts the primary key of `item`; with `exact`, `item` has to be just the key.
+    fn key_of(&self, item: &Item, exact: bool) -> Result<Item, Error> {
+        let attributes: Vec<&KeyAttribute> = std::iter::once(&self.schema.partition_key)
+            .chain(self.schema.sort_key.as_ref())
+            .collect();
+
+        let mut key = Item::new();
+        for attribute in &attributes {
+            let value = item.get(&attribute.name).ok_or_else(|| {
+                Error::Validation(format!("missing key attribute `{}`", attribute.name))
+            })?;
+            if !has_type(value, &attribute.scalar_type) {
+                return Err(Error::Validation(format!(
+                    "key attribute `{}` has the wrong type",
+                    attribute.name
+                )));
+            }
+            key.insert(attribute.name.clone(), value.clone());
+        }
+
+        if exact && item.len() != attributes.len() {
+            return Err(Error::Validation(
+                "the provided key does not match the table's key schema".to_string(),
+            ));
+        }
+        Ok(key)
+    }
+
+    fn position(&self, key: &Item) -> Option<usize> {
+        self.items.iter().position(|item| {
+            key.iter()
+                .all(|(name, value)| item.get(name) == Some(value))
+        })
+    }
+
+    fn query_keys(
+        &self,
+        index_name: Option<&str>,
+    ) -> Result<(KeyAttribute, Option<KeyAttribute>), Error> {
+        match index_name {
+            None => Ok((
+                self.schema.partition_key.clone(),
+                self.schema.sort_key.clone(),
+            )),
+            Some(index_name) => self
+                .schema
+                .gsis
+                .iter()
+                .find(|gsi| gsi.name == index_name)
+                .map(|gsi| (gsi.partition_key.clone(), gsi.sort_key.clone()))
+                .ok_or_else(|| Error::Validation(format!("no index named `{index_name}`"))),
+        }
+    }
+
+    /// Table key attributes plus the queried index's key attributes, as DynamoDB returns
+    /// in `LastEvaluatedKey`.
+    fn page_key_names(
+        &self,
+        partition_key: &KeyAttribute,
+        sort_key: Option<&KeyAttribute>,
+    ) -> Vec<String> {
+        let mut names: Vec<String> = std::iter::once(&self.schema.partition_key)
+            .chain(self.schema.sort_key.as_ref())
+            .chain(std::iter::once(partition_key))
+            .chain(sort_key)
+            .map(|attribute| attribute.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+fn check_condition(condition: Option<&Filter>, existing: Option<&Item>) -> Result<(), Error> {
+    let empty = Item::new();
+    match condition {
+        Some(condition) if !evaluate(condition, existing.unwrap_or(&empty)) => {
+            Err(aws_sdk_dynamodb::Error::ConditionalCheckFailedException(
+                ConditionalCheckFailedException::builder()
+                    .message("The conditional request failed")
+                    .build(),
+            )
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn evaluate(filter: &Filter, item: &Item) -> bool {
+    match filter {
+        Filter::Compare {
+            attribute,
+            operator,
+            value,
+        } => item.get(attribute).is_some_and(|stored| {
+            let ordering = compare(stored, value);
+            match *operator {
+                "=" => stored == value,
+                "<>" => stored != value,
+                "<" => ordering == Some(Ordering::Less),
+                "<=" => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                ">" => ordering == Some(Ordering::Greater),
+                ">=" => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                _ => false,
+            }
+        }),
+        Filter::Between {
+            attribute,
+            low,
+            high,
+        } => item.get(attribute).is_some_and(|stored| {
+            matches!(
+                compare(stored, low),
+                Some(Ordering::Greater | Ordering::Equal)
+            ) && matches!(
+                compare(stored, high),
+                Some(Ordering::Less | Ordering::Equal)
+            )
+        }),
+        Filter::BeginsWith { attribute, prefix } => {
+            matches!(item.get(attribute), Some(AttributeValue::S(value)) if value.starts_with(prefix.as_str()))
+        }
+        Filter::Contains { attribute, value } => match (item.get(attribute), value) {
+            (Some(AttributeValue::S(stored)), AttributeValue::S(part)) => {
+                stored.contains(part.as_str())
+            }
+            (Some(AttributeValue::Ss(set)), AttributeValue::S(member)) => set.contains(member),
+            (Some(AttributeValue::Ns(set)), AttributeValue::N(member)) => set.contains(member),
+            (Some(AttributeValue::L(list)), member) => list.contains(member),
+            _ => false,
+        },
+        Filter::Exists(attribute) => item.contains_key(attribute),
+        Filter::NotExists(attribute) => !item.contains_key(attribute),
+        Filter::And(filters) => filters.iter().all(|filter| evaluate(filter, item)),
+        Filter::Or(filters) => filters.iter().any(|filter| evaluate(filter, item)),
+        Filter::Not(filter) => !evaluate(filter, item),
+    }
+}
+
+fn sort_key_matches(value: &AttributeValue, condition: &SortKeyCondition) -> bool {
+    let ordering = |other| compare(value, other);
+    match condition {
+        SortKeyCondition::Eq(other) => value == other,
+        SortKeyCondition::Lt(other) => ordering(other) == Some(Ordering::Less),
+        SortKeyCondition::Le(other) => {
+            matches!(ordering(other), Some(Ordering::Less | Ordering::Equal))
+        }
+        SortKeyCondition::Gt(other) => ordering(other) == Some(Ordering::Greater),
+        SortKeyCondition::Ge(other) => {
+            matches!(ordering(other), Some(Ordering::Greater | Ordering::Equal))
+        }
+        SortKeyCondition::Between(low, high) => {
+            matches!(ordering(low), Some(Ordering::Greater | Ordering::Equal))
+                && matches!(ordering(high), Some(Ordering::Less | Ordering::Equal))
+        }
+        SortKeyCondition::BeginsWith(prefix) => {
+            matches!(value, AttributeValue::S(value) if value.starts_with(prefix.as_str()))
+        }
+    }
+}
+
+/// Orders scalar values the way DynamoDB compares them; other types are unordered.
+fn compare(a: &AttributeValue, b: &AttributeValue) -> Option<Ordering> {
+    match (a, b) {
+        (AttributeValue::S(a), AttributeValue::S(b)) => Some(a.cmp(b)),
+        (AttributeValue::N(a), AttributeValue::N(b)) => {
+            a.parse::<f64>().ok()?.partial_cmp(&b.parse::<f64>().ok()?)
+        }
+        (AttributeValue::B(a), AttributeValue::B(b)) => Some(a.as_ref().cmp(b.as_ref())),
+        _ => None,
+    }
+}
+
+fn has_type(value: &AttributeValue, scalar_type: &ScalarType) -> bool {
+    matches!(
+        (value, scalar_type),
+        (AttributeValue::S(_), ScalarType::S)
+            | (AttributeValue::N(_), ScalarType::N)
+            | (AttributeValue::B(_), ScalarType::B)
+    )
+}
+
+fn same_key(item: &Item, key: &Item, names: &[String]) -> bool {
+    names.iter().all(|name| item.get(name) == key.get(name))
+}
+
+fn project(item: &Item, names: &[String]) -> Item {
+    names
+        .iter()
+        .filter_map(|name| Some((name.clone(), item.get(name)?.clone())))
+        .collect()
+}
+
+impl DynamoStoreApi for InMemoryStore {
+    fn put_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        item: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move { self.put(table_name, item, condition) })
+    }
+
+    fn get_item<'a>(&'a self, table_name: &'a str, key: Item) -> StoreFuture<'a, Option<Item>> {
+        Box::pin(async move { self.get(table_name, key) })
+    }
+
+    fn delete_item<'a>(
+        &'a self,
+        table_name: &'a str,
+        key: Item,
+        condition: Option<Filter>,
+    ) -> StoreFuture<'a, ()> {
+        Box::pin(async move { self.delete(table_name, key, condition) })
+    }
+
+    fn query_page<'a>(&'a self, request: &'a QueryRequest) -> StoreFuture<'a, Page> {
+        Box::pin(async move { self.query(request) })
+    }
+}
+
+/// A store whose client points at a closed local port, so every call fails at once;
+/// for testing code paths that must not reach DynamoDB.
+#[cfg(test)]
+pub(crate) fn offline_store() -> crate::DynamoDbStore {
+    use aws_sdk_dynamodb::{
+        config::{retry::RetryConfig, BehaviorVersion, Credentials, Region},
+        Client, Config,
+    };
+
+    let config = Config::builder()
+        .behavior_version(BehaviorVersion::latest())
+        .endpoint_url("http://127.0.0.1:1")
+        .region(Region::new("us-east-1"))
+        .credentials_provider(Credentials::new("test", "test", None, None, "test"))
+        .retry_config(RetryConfig::disabled())
+        .build();
+    crate::DynamoDbStore::from_client(Client::from_conf(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api::DynamoStoreApiExt, schema::Gsi};
+
+    const TABLE: &str = "orders";
+
+    fn s(value: &str) -> AttributeValue {
+        AttributeValue::S(value.to_string())
+    }
+
+    fn n(value: i64) -> AttributeValue {
+        AttributeValue::N(value.to_string())
+    }
+
+    fn order(customer: &str, number: i64, status: &str) -> Item {
+        HashMap::from([
+            ("customer".to_string(), s(customer)),
+            ("number".to_string(), n(number)),
+            ("status".to_string(), s(status)),
+        ])
+    }
+
+    fn key(customer: &str, number: i64) -> Item {
+        HashMap::from([
+            ("customer".to_string(), s(customer)),
+            ("number".to_string(), n(number)),
+        ])
+    }
+
+    fn orders() -> InMemoryStore {
+        InMemoryStore::new().with_table(
+            TABLE,
+            TableSchema::new("customer", ScalarType::S)
+                .sort_key("number", ScalarType::N)
+                .gsi(
+                    Gsi::new("by_status", "status", ScalarType::S)
+                        .sort_key("number", ScalarType::N),
+                ),
+        )
+    }
+
+    async fn stored(numbers: &[i64]) -> InMemoryStore {
+        let store = orders();
+        for number in numbers {
+            let status = if number % 2 == 0 { "OPEN" } else { "SHIPPED" };
+            store
+                .put_item(TABLE, order("alice", *number, status), None)
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    fn numbers(items: &[Item]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| match &item["number"] {
+                AttributeValue::N(number) => number.clone(),
+                other => panic!("unexpected number {other:?}"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn items_are_replaced_read_and_deleted_by_key() {
+        let store = orders();
+        store
+            .put_item(TABLE, order("alice", 1, "OPEN"), None)
+            .await
+            .unwrap();
+        store
+            .put_item(TABLE, order("alice", 1, "SHIPPED"), None)
+            .await
+            .unwrap();
+
+        let item = store.get_item(TABLE, key("alice", 1)).await.unwrap();
+        assert_eq!(item, Some(order("alice", 1, "SHIPPED")));
+
+        store
+            .delete_item(TABLE, key("alice", 1), None)
+            .await
+            .unwrap();
+        assert_eq!(store.get_item(TABLE, key("alice", 1)).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn keys_are_checked_against_the_schema() {
+        let store = orders();
+
+        let missing = store
+            .put_item(
+                TABLE,
+                HashMap::from([("customer".to_string(), s("alice"))]),
+                None,
+            )
+            .await;
+        let wrong_type = store
+            .get_item(
+                TABLE,
+                HashMap::from([
+                    ("customer".to_string(), s("alice")),
+                    ("number".to_string(), s("1")),
+                ]),
+            )
+            .await;
+        let not_just_the_key = store.get_item(TABLE, order("alice", 1, "OPEN")).await;
+
+        assert!(matches!(missing, Err(Error::Validation(_))));
+        assert!(matches!(wrong_type, Err(Error::Validation(_))));
+        assert!(matches!(not_just_the_key, Err(Error::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn a_missing_table_is_not_found() {
+        let err = orders()
+            .get_item("missing", key("alice", 1))
+            .await
+            .unwrap_err();
+
+        assert!(err.is_resource_not_found());
+    }
+
+    #[tokio::test]
+    async fn conditions_are_checked_against_the_stored_item() {
+        let store = orders();
+        let create = || Some(Filter::not_exists("customer"));
+        store
+            .put_item(TABLE, order("alice", 1, "OPEN"), create())
+            .await
+            .unwrap();
+
+        let again = store
+            .put_item(TABLE, order("alice", 1, "OPEN"), create())
+            .await
+            .unwrap_err();
+        let shipped = store
+            .delete_item(
+                TABLE,
+                key("alice", 1),
+                Some(Filter::eq("status", s("SHIPPED"))),
+            )
+            .await
+            .unwrap_err();
+
+        assert!(again.is_conditional_check_failed());
+        assert!(shipped.is_conditional_check_failed());
+        assert_eq!(store.items(TABLE).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn queries_return_items_in_sort_key_order() {
+        let store = stored(&[3, 10, 1, 2]).await;
+        let request = QueryRequest::new(TABLE, "customer", s("alice"));
+
+        let forward = store.query_all(request.clone()).await.unwrap();
+        let reverse = store.query_all(request.reverse()).await.unwrap();
+
+        // Numbers compare numerically, not as strings.
+        assert_eq!(numbers(&forward), ["1", "2", "3", "10"]);
+        assert_eq!(numbers(&reverse), ["10", "3", "2", "1"]);
+    }
+
+    #[tokio::test]
+    async fn sort_key_conditions_and_filters_narrow_the_results() {
+        let store = stored(&[1, 2, 3, 4, 5]).await;
+        let request = QueryRequest::new(TABLE, "customer", s("alice"))
+            .sort_key("number", SortKeyCondition::Between(n(2), n(4)))
+            .filter(Filter::eq("status", s("OPEN")));
+
+        let page = store.query_page(&request).await.unwrap();
+
+        assert_eq!(numbers(&page.items), ["2", "4"]);
+        assert_eq!(page.scanned_count, 3);
+    }
+
+    #[tokio::test]
+    async fn pages_end_after_the_page_size_and_resume_after_the_last_key() {
+        let store = stored(&[1, 2, 3, 4, 5]).await.with_page_size(2);
+        let request = QueryRequest::new(TABLE, "customer", s("alice"));
+
+        let first = store.query_page(&request).await.unwrap();
+        let second = store
+            .query_page(
+                &request
+                    .clone()
+                    .start_after(first.last_evaluated_key.clone().unwrap()),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(numbers(&first.items), ["1", "2"]);
+        assert_eq!(first.last_evaluated_key, Some(key("alice", 2)));
+        assert_eq!(numbers(&second.items), ["3", "4"]);
+        assert_eq!(numbers(&store.query_all(request).await.unwrap()).len(), 5);
+    }
+
+    #[tokio::test]
+    async fn a_deleted_start_item_resumes_by_sort_key() {
+        let store = stored(&[1, 2, 3]).await;
+        store
+            .delete_item(TABLE, key("alice", 2), None)
+            .await
+            .unwrap();
+        let request = QueryRequest::new(TABLE, "customer", s("alice")).start_after(key("alice", 2));
+
+        let page = store.query_page(&request).await.unwrap();
+
+        assert_eq!(numbers(&page.items), ["3"]);
+    }
+
+    #[tokio::test]
+    async fn index_queries_return_table_and_index_keys() {
+        let store = stored(&[1, 2, 4]).await.with_page_size(1);
+        let request = QueryRequest::new(TABLE, "status", s("OPEN")).index("by_status");
+
+        let page = store.query_page(&request).await.unwrap();
+
+        assert_eq!(numbers(&page.items), ["2"]);
+        let mut last_key = key("alice", 2);
+        last_key.insert("status".to_string(), s("OPEN"));
+        assert_eq!(page.last_evaluated_key, Some(last_key));
+    }
+}
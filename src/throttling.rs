@@ -0,0 +1,99 @@
+use std::fmt;
+
+use crate::{metrics, DynamoDbStore, Error};
+
+/// Read units one worker consumes at least per second: a full 1 MB page of an
+/// eventually consistent scan, assuming a page takes no longer than a second.
+const READ_UNITS_PER_WORKER: i64 = 128;
+/// Write units one worker consumes at least per second: one batch of 25 small items.
+const WRITE_UNITS_PER_WORKER: i64 = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapacityKind {
+    Read,
+    Write,
+}
+
+/// A batch job configured with more concurrency than a provisioned table can serve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThrottlingHint {
+    pub table_name: String,
+    pub kind: CapacityKind,
+    pub concurrency: usize,
+    /// Capacity units per second the workers need at the least.
+    pub required_units: i64,
+    pub provisioned_units: i64,
+}
+
+impl fmt::Display for ThrottlingHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            CapacityKind::Read => "read",
+            CapacityKind::Write => "write",
+        };
+        write!(
+            f,
+            "{} concurrent workers need about {} {kind} units per second, but table {} \
+             is provisioned with {}; expect throttling or lower the concurrency",
+            self.concurrency, self.required_units, self.table_name, self.provisioned_units
+        )
+    }
+}
+
+impl DynamoDbStore {
+    /// Checks whether `concurrency` workers scanning and batch-writing `table_name` will
+    /// exceed its provisioned throughput. On-demand tables never produce hints.
+    ///
+    /// Uses the [cached description](Self::cached_description) of the table.
+    pub async fn throttling_hints(
+        &self,
+        table_name: &str,
+        concurrency: usize,
+        kinds: &[CapacityKind],
+    ) -> Result<Vec<ThrottlingHint>, Error> {
+        let description = self.cached_description(table_name).await?;
+        let Some(throughput) = description.throughput else {
+            return Ok(Vec::new());
+        };
+
+        Ok(kinds
+            .iter()
+            .filter_map(|kind| {
+                let (per_worker, provisioned_units) = match kind {
+                    CapacityKind::Read => (READ_UNITS_PER_WORKER, throughput.read_units),
+                    CapacityKind::Write => (WRITE_UNITS_PER_WORKER, throughput.write_units),
+                };
+                let required_units = per_worker * concurrency as i64;
+
+                (required_units > provisioned_units).then(|| ThrottlingHint {
+                    table_name: table_name.to_string(),
+                    kind: *kind,
+                    concurrency,
+                    required_units,
+                    provisioned_units,
+                })
+            })
+            .collect())
+    }
+
+    /// Reports throttling hints for a batch job through the metrics and tracing hooks.
+    /// Failing to describe the table never fails the job itself.
+    #[cfg_attr(not(feature = "wire-logging"), allow(unused_variables))]
+    pub(crate) async fn warn_on_throttling(
+        &self,
+        job: &'static str,
+        table_name: &str,
+        concurrency: usize,
+        kinds: &[CapacityKind],
+    ) {
+        let Ok(hints) = self.throttling_hints(table_name, concurrency, kinds).await else {
+            return;
+        };
+
+        for hint in hints {
+            metrics::record_throttling_hint(job, table_name);
+            #[cfg(feature = "wire-logging")]
+            tracing::warn!(job, "{hint}");
+        }
+    }
+}
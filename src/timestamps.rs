@@ -0,0 +1,175 @@
+/// Formats a UTC timestamp as `2024-05-01T09:30:00.000Z`: always UTC and always the same width, so string order
+/// is time order.
+fn sortable(
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    millis: u16,
+) -> String {
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{millis:03}Z")
+}
+
+#[cfg(feature = "chrono")]
+pub mod chrono {
+    use ::chrono::{DateTime, Datelike, Timelike, Utc};
+
+    /// Formats `value` like the [`rfc3339`] adapter, e.g. to build a sort key.
+    pub fn sortable(value: &DateTime<Utc>) -> String {
+        super::sortable(
+            value.year(),
+            value.month() as u8,
+            value.day() as u8,
+            value.hour() as u8,
+            value.minute() as u8,
+            value.second() as u8,
+            (value.nanosecond() / 1_000_000).min(999) as u16,
+        )
+    }
+
+    /// Stores the timestamp as whole seconds since the epoch, the format TTL attributes use.
+    pub mod epoch_seconds {
+        use ::chrono::{DateTime, Utc};
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &DateTime<Utc>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_i64(value.timestamp())
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<DateTime<Utc>, D::Error> {
+            let seconds = i64::deserialize(deserializer)?;
+            DateTime::from_timestamp(seconds, 0)
+                .ok_or_else(|| de::Error::custom(format!("timestamp {seconds} is out of range")))
+        }
+    }
+
+    /// Stores the timestamp as milliseconds since the epoch.
+    pub mod epoch_millis {
+        use ::chrono::{DateTime, Utc};
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &DateTime<Utc>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_i64(value.timestamp_millis())
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<DateTime<Utc>, D::Error> {
+            let millis = i64::deserialize(deserializer)?;
+            DateTime::from_timestamp_millis(millis)
+                .ok_or_else(|| de::Error::custom(format!("timestamp {millis} is out of range")))
+        }
+    }
+
+    /// Stores the timestamp as a fixed-width UTC RFC 3339 string and reads back any offset.
+    pub mod rfc3339 {
+        use ::chrono::{DateTime, Utc};
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &DateTime<Utc>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&super::sortable(value))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<DateTime<Utc>, D::Error> {
+            let value = String::deserialize(deserializer)?;
+            DateTime::parse_from_rfc3339(&value)
+                .map(|value| value.with_timezone(&Utc))
+                .map_err(de::Error::custom)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+pub mod time {
+    use ::time::{OffsetDateTime, UtcOffset};
+
+    /// Formats `value` like the [`rfc3339`] adapter, e.g. to build a sort key.
+    pub fn sortable(value: &OffsetDateTime) -> String {
+        let value = value.to_offset(UtcOffset::UTC);
+        super::sortable(
+            value.year(),
+            value.month().into(),
+            value.day(),
+            value.hour(),
+            value.minute(),
+            value.second(),
+            value.millisecond(),
+        )
+    }
+
+    /// Stores the timestamp as whole seconds since the epoch, the format TTL attributes use.
+    pub mod epoch_seconds {
+        use ::time::OffsetDateTime;
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &OffsetDateTime,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_i64(value.unix_timestamp())
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<OffsetDateTime, D::Error> {
+            let seconds = i64::deserialize(deserializer)?;
+            OffsetDateTime::from_unix_timestamp(seconds).map_err(de::Error::custom)
+        }
+    }
+
+    /// Stores the timestamp as milliseconds since the epoch.
+    pub mod epoch_millis {
+        use ::time::OffsetDateTime;
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &OffsetDateTime,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_i64((value.unix_timestamp_nanos() / 1_000_000) as i64)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<OffsetDateTime, D::Error> {
+            let millis = i64::deserialize(deserializer)?;
+            OffsetDateTime::from_unix_timestamp_nanos(i128::from(millis) * 1_000_000)
+                .map_err(de::Error::custom)
+        }
+    }
+
+    /// Stores the timestamp as a fixed-width UTC RFC 3339 string and reads back any offset.
+    pub mod rfc3339 {
+        use ::time::{format_description::well_known::Rfc3339, OffsetDateTime};
+        use serde::{de, Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(
+            value: &OffsetDateTime,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(&super::sortable(value))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<OffsetDateTime, D::Error> {
+            let value = String::deserialize(deserializer)?;
+            OffsetDateTime::parse(&value, &Rfc3339).map_err(de::Error::custom)
+        }
+    }
+}
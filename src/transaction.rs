@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::{AttributeValue, ConditionCheck, Delete, Put, TransactWriteItem};
+
+use crate::{
+    api::render_condition,
+    filter::Filter,
+    instrument::Operation,
+    limits::{item_size, validate_item},
+    DynamoDbStore, Error,
+};
+
+/// Actions one transaction can hold.
+pub const MAX_TRANSACTION_ACTIONS: usize = 100;
+/// Aggregate size of the items and keys in one transaction.
+pub const MAX_TRANSACTION_SIZE: usize = 4 * 1024 * 1024;
+
+/// Writes and condition checks that succeed or fail together, sent with
+/// [`DynamoDbStore::transact_write`].
+///
+/// A [`condition_check`](Self::condition_check) writes nothing; it only makes the whole
+/// transaction fail unless an item elsewhere is in the expected state, e.g. an order
+/// that has to exist and still be `OPEN` while a payment for it is written.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transaction {
+    actions: Vec<TransactionAction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TransactionAction {
+    Put {
+        table_name: String,
+        item: HashMap<String, AttributeValue>,
+        condition: Option<Filter>,
+    },
+    Delete {
+        table_name: String,
+        key: HashMap<String, AttributeValue>,
+        condition: Option<Filter>,
+    },
+    ConditionCheck {
+        table_name: String,
+        key: HashMap<String, AttributeValue>,
+        condition: Filter,
+    },
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put(
+        mut self,
+        table_name: impl Into<String>,
+        item: HashMap<String, AttributeValue>,
+    ) -> Self {
+        self.actions.push(TransactionAction::Put {
+            table_name: table_name.into(),
+            item,
+            condition: None,
+        });
+        self
+    }
+
+    /// Puts `item` only if `condition` holds for the item it replaces.
+    pub fn put_if(
+        mut self,
+        table_name: impl Into<String>,
+        item: HashMap<String, AttributeValue>,
+        condition: Filter,
+    ) -> Self {
+        self.actions.push(TransactionAction::Put {
+            table_name: table_name.into(),
+            item,
+            condition: Some(condition),
+        });
+        self
+    }
+
+    pub fn delete(
+        mut self,
+        table_name: impl Into<String>,
+        key: HashMap<String, AttributeValue>,
+    ) -> Self {
+        self.actions.push(TransactionAction::Delete {
+            table_name: table_name.into(),
+            key,
+            condition: None,
+        });
+        self
+    }
+
+    pub fn delete_if(
+        mut self,
+        table_name: impl Into<String>,
+        key: HashMap<String, AttributeValue>,
+        condition: Filter,
+    ) -> Self {
+        self.actions.push(TransactionAction::Delete {
+            table_name: table_name.into(),
+            key,
+            condition: Some(condition),
+        });
+        self
+    }
+
+    /// Requires `condition` to hold for the item at `key` without writing it. Use
+    /// [`Filter::exists`] on a key attribute to require that the item exists.
+    pub fn condition_check(
+        mut self,
+        table_name: impl Into<String>,
+        key: HashMap<String, AttributeValue>,
+        condition: Filter,
+    ) -> Self {
+        self.actions.push(TransactionAction::ConditionCheck {
+            table_name: table_name.into(),
+            key,
+            condition,
+        });
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Checks the transaction against DynamoDB's limits of 100 actions and 4 MB of items
+    /// and keys, which [`DynamoDbStore::transact_write`] also does before sending.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.actions.len() > MAX_TRANSACTION_ACTIONS {
+            return Err(Error::Validation(format!(
+                "transaction has {} actions, DynamoDB allows at most {MAX_TRANSACTION_ACTIONS}",
+                self.actions.len()
+            )));
+        }
+
+        let size: usize = self
+            .actions
+            .iter()
+            .map(|action| match action {
+                TransactionAction::Put { item, .. } => item_size(item),
+                TransactionAction::Delete { key, .. }
+                | TransactionAction::ConditionCheck { key, .. } => item_size(key),
+            })
+            .sum();
+        if size > MAX_TRANSACTION_SIZE {
+            return Err(Error::Validation(format!(
+                "transaction items and keys are about {size} bytes across {} actions, \
+                 DynamoDB allows at most {MAX_TRANSACTION_SIZE}",
+                self.actions.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn into_items(self) -> Result<Vec<TransactWriteItem>, Error> {
+        self.actions
+            .into_iter()
+            .map(|action| {
+                let item = match action {
+                    TransactionAction::Put {
+                        table_name,
+                        item,
+                        condition,
+                    } => {
+                        validate_item(&item)?;
+                        let (expression, names, values) = render_condition(condition);
+                        let put = Put::builder()
+                            .table_name(table_name)
+                            .set_item(Some(item))
+                            .set_condition_expression(expression)
+                            .set_expression_attribute_names(names)
+                            .set_expression_attribute_values(values)
+                            .build()?;
+                        TransactWriteItem::builder().put(put).build()
+                    }
+                    TransactionAction::Delete {
+                        table_name,
+                        key,
+                        condition,
+                    } => {
+                        let (expression, names, values) = render_condition(condition);
+                        let delete = Delete::builder()
+                            .table_name(table_name)
+                            .set_key(Some(key))
+                            .set_condition_expression(expression)
+                            .set_expression_attribute_names(names)
+                            .set_expression_attribute_values(values)
+                            .build()?;
+                        TransactWriteItem::builder().delete(delete).build()
+                    }
+                    TransactionAction::ConditionCheck {
+                        table_name,
+                        key,
+                        condition,
+                    } => {
+                        let (expression, names, values) = render_condition(Some(condition));
+                        let check = ConditionCheck::builder()
+                            .table_name(table_name)
+                            .set_key(Some(key))
+                            .set_condition_expression(expression)
+                            .set_expression_attribute_names(names)
+                            .set_expression_attribute_values(values)
+                            .build()?;
+                        TransactWriteItem::builder().condition_check(check).build()
+                    }
+                };
+                Ok(item)
+            })
+            .collect()
+    }
+
+    fn table_name(&self) -> &str {
+        match self.actions.first() {
+            Some(
+                TransactionAction::Put { table_name, .. }
+                | TransactionAction::Delete { table_name, .. }
+                | TransactionAction::ConditionCheck { table_name, .. },
+            ) => table_name,
+            None => "",
+        }
+    }
+}
+
+impl DynamoDbStore {
+    /// Sends `transaction` as one TransactWriteItems call. If any condition fails,
+    /// nothing is written and [`Error::is_transaction_canceled`] returns `true`.
+    pub async fn transact_write(&self, transaction: Transaction) -> Result<(), Error> {
+        if transaction.is_empty() {
+            return Ok(());
+        }
+
+        transaction.validate()?;
+        let table_name = transaction.table_name().to_string();
+        let items = transaction.into_items()?;
+
+        let operation = Operation::new("TransactWriteItems", &table_name);
+        let request = self
+            .client()
+            .transact_write_items()
+            .set_transact_items(Some(items))
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{DynamoDbStore, Error};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TruncateProgress {
+    pub scanned: usize,
+    pub deleted: usize,
+    pub failed: usize,
+}
+
+impl DynamoDbStore {
+    /// Deletes every item in the table by scanning its keys and batch-deleting them.
+    pub async fn truncate(&self, table_name: &str) -> Result<TruncateProgress, Error> {
+        self.truncate_with_progress(table_name, |_| {}).await
+    }
+
+    pub async fn truncate_with_progress(
+        &self,
+        table_name: &str,
+        mut on_progress: impl FnMut(&TruncateProgress),
+    ) -> Result<TruncateProgress, Error> {
+        let description = self.describe_table(table_name).await?;
+        let key_attributes = std::iter::once(&description.partition_key)
+            .chain(description.sort_key.as_ref())
+            .map(|attribute| attribute.name.clone());
+
+        let mut scan = self.scan(table_name).projection(key_attributes);
+        let mut progress = TruncateProgress::default();
+
+        loop {
+            let page = scan.page().await?;
+            progress.scanned += page.items.len();
+
+            let result = self.batch_delete_items(table_name, page.items).await?;
+            progress.deleted += result.processed;
+            progress.failed += result.unprocessed.len();
+            on_progress(&progress);
+
+            match page.last_evaluated_key {
+                Some(key) => scan = scan.start_after(key),
+                None => return Ok(progress),
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use aws_sdk_dynamodb::types::{AttributeValue, TimeToLiveSpecification};
+use serde::Serialize;
+
+use crate::{instrument::Operation, DynamoDbStore, Error, TableBoundStore};
+
+pub use aws_sdk_dynamodb::types::TimeToLiveStatus as TtlStatus;
+
+pub const DEFAULT_TTL_ATTRIBUTE: &str = "ttl";
+
+/// A point in time DynamoDB can expire an item at, stored as epoch seconds.
+pub trait Expiry {
+    /// Fails with a validation error for a time the clock cannot represent.
+    fn epoch_seconds(&self) -> Result<i64, Error>;
+}
+
+impl Expiry for Duration {
+    fn epoch_seconds(&self) -> Result<i64, Error> {
+        SystemTime::now()
+            .checked_add(*self)
+            .ok_or_else(|| Error::Validation(format!("expiry {self:?} from now is out of range")))?
+            .epoch_seconds()
+    }
+}
+
+impl Expiry for SystemTime {
+    fn epoch_seconds(&self) -> Result<i64, Error> {
+        Ok(match self.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64),
+        })
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Expiry for chrono::DateTime<Tz> {
+    fn epoch_seconds(&self) -> Result<i64, Error> {
+        Ok(self.timestamp())
+    }
+}
+
+pub fn ttl_value(expiry: impl Expiry) -> Result<AttributeValue, Error> {
+    Ok(AttributeValue::N(expiry.epoch_seconds()?.to_string()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TtlDescription {
+    pub status: TtlStatus,
+    pub attribute_name: Option<String>,
+}
+
+impl DynamoDbStore {
+    pub async fn describe_ttl(&self, table_name: &str) -> Result<TtlDescription, Error> {
+        let operation = Operation::new("DescribeTimeToLive", table_name);
+        let request = self
+            .client()
+            .describe_time_to_live()
+            .table_name(table_name)
+            .send();
+        let output = operation.send(request).await?;
+
+        let ttl = output.time_to_live_description;
+        Ok(TtlDescription {
+            status: ttl
+                .as_ref()
+                .and_then(|ttl| ttl.time_to_live_status())
+                .cloned()
+                .unwrap_or(TtlStatus::Disabled),
+            attribute_name: ttl.and_then(|ttl| ttl.attribute_name),
+        })
+    }
+
+    pub async fn enable_ttl(&self, table_name: &str, attribute_name: &str) -> Result<(), Error> {
+        self.update_ttl(table_name, attribute_name, true).await
+    }
+
+    pub async fn disable_ttl(&self, table_name: &str, attribute_name: &str) -> Result<(), Error> {
+        self.update_ttl(table_name, attribute_name, false).await
+    }
+
+    async fn update_ttl(
+        &self,
+        table_name: &str,
+        attribute_name: &str,
+        enabled: bool,
+    ) -> Result<(), Error> {
+        let specification = TimeToLiveSpecification::builder()
+            .attribute_name(attribute_name)
+            .enabled(enabled)
+            .build()?;
+
+        let operation = Operation::new("UpdateTimeToLive", table_name);
+        let request = self
+            .client()
+            .update_time_to_live()
+            .table_name(table_name)
+            .time_to_live_specification(specification)
+            .send();
+        operation.send(request).await?;
+
+        Ok(())
+    }
+}
+
+impl TableBoundStore {
+    /// Puts the item with the table's TTL attribute set to `expiry`.
+    pub async fn put_with_ttl<T: Serialize>(
+        &self,
+        item: &T,
+        expiry: impl Expiry,
+    ) -> Result<(), Error> {
+        let expires_at = ttl_value(expiry)?;
+
+        self.run(|| async {
+            let mut item = self.to_item(item)?;
+            item.insert(self.ttl_attribute().to_string(), expires_at.clone());
+
+            self.store()
+                .put_item_with_options(self.table_name(), item, self.defaults())
+                .await?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn times_before_the_epoch_are_negative() {
+        let before = UNIX_EPOCH - Duration::from_secs(90);
+
+        assert_eq!(before.epoch_seconds().unwrap(), -90);
+        assert_eq!(
+            ttl_value(UNIX_EPOCH + Duration::from_secs(60)).unwrap(),
+            AttributeValue::N("60".to_string())
+        );
+    }
+
+    #[test]
+    fn durations_past_the_end_of_time_are_rejected() {
+        assert!(matches!(
+            Duration::MAX.epoch_seconds(),
+            Err(Error::Validation(_))
+        ));
+        assert!(Duration::from_secs(60).epoch_seconds().unwrap() > 0);
+    }
+}
@@ -0,0 +1,139 @@
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    batch::{BatchGetResult, BatchWriteResult},
+    table::TableBoundStore,
+    DynamoDbStore, Error,
+};
+
+/// A [`TableBoundStore`] fixed to one item type `T` and key type `K`.
+pub struct TypedTableStore<T, K> {
+    table: TableBoundStore,
+    types: PhantomData<fn() -> (T, K)>,
+}
+
+impl<T, K> Clone for TypedTableStore<T, K> {
+    fn clone(&self) -> Self {
+        Self {
+            table: self.table.clone(),
+            types: PhantomData,
+        }
+    }
+}
+
+impl<T, K> fmt::Debug for TypedTableStore<T, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedTableStore")
+            .field("table", &self.table)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DynamoDbStore {
+    pub fn typed_table<T, K>(&self, table_name: impl Into<String>) -> TypedTableStore<T, K> {
+        self.for_table(table_name).typed()
+    }
+}
+
+impl TableBoundStore {
+    pub fn typed<T, K>(self) -> TypedTableStore<T, K> {
+        TypedTableStore {
+            table: self,
+            types: PhantomData,
+        }
+    }
+}
+
+impl<T, K> TypedTableStore<T, K>
+where
+    T: Serialize + DeserializeOwned,
+    K: Serialize,
+{
+    pub fn table(&self) -> &TableBoundStore {
+        &self.table
+    }
+
+    pub fn into_inner(self) -> TableBoundStore {
+        self.table
+    }
+
+    pub async fn put(&self, item: &T) -> Result<(), Error> {
+        self.table.put(item).await
+    }
+
+    pub async fn get(&self, key: &K) -> Result<Option<T>, Error> {
+        self.table.get(key).await
+    }
+
+    pub async fn delete(&self, key: &K) -> Result<(), Error> {
+        self.table.delete(key).await
+    }
+
+    /// See [`TableBoundStore::get_or_put`].
+    pub async fn get_or_put(&self, key: &K, default_fn: impl FnOnce() -> T) -> Result<T, Error> {
+        self.table.get_or_put(key, default_fn).await
+    }
+
+    pub async fn batch_put(&self, items: &[T]) -> Result<BatchWriteResult, Error> {
+        self.table
+            .store()
+            .batch_put_items_with_retry(
+                self.table.table_name(),
+                items
+                    .iter()
+                    .map(|item| self.table.to_item(item))
+                    .collect::<Result<_, _>>()?,
+                &self.table.batch_retry(),
+            )
+            .await
+    }
+
+    pub async fn batch_delete(&self, keys: &[K]) -> Result<BatchWriteResult, Error> {
+        self.table
+            .store()
+            .batch_delete_items_with_retry(
+                self.table.table_name(),
+                self.to_keys(keys)?,
+                &self.table.batch_retry(),
+            )
+            .await
+    }
+
+    /// Loads the items at `keys`; missing items are left out and the order is not
+    /// preserved.
+    pub async fn batch_get(&self, keys: &[K]) -> Result<Vec<T>, Error> {
+        let BatchGetResult {
+            items, unprocessed, ..
+        } = self
+            .table
+            .store()
+            .batch_get_items_with_retry(
+                self.table.table_name(),
+                self.to_keys(keys)?,
+                &self.table.batch_retry(),
+            )
+            .await?;
+
+        if !unprocessed.is_empty() {
+            return Err(Error::UnexpectedResponse {
+                operation: "BatchGetItem",
+                reason: format!(
+                    "{} keys stayed unprocessed after retries",
+                    unprocessed.len()
+                ),
+            });
+        }
+
+        items
+            .into_iter()
+            .map(|item| self.table.read_item(item))
+            .collect()
+    }
+
+    fn to_keys(&self, keys: &[K]) -> Result<Vec<HashMap<String, AttributeValue>>, Error> {
+        keys.iter().map(|key| self.table.to_key(key)).collect()
+    }
+}
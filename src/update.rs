@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::{
+    operation::update_item::builders::UpdateItemFluentBuilder, types::AttributeValue,
+};
+use serde::Serialize;
+
+use crate::{
+    expression::CheckedExpressions, filter::Filter, instrument::Operation, table::TableBoundStore,
+    DynamoDbStore, Error,
+};
+
+/// `SET`/`REMOVE` clauses with generated `#u{i}`/`:u{i}` placeholders.
+#[derive(Debug, Default)]
+pub(crate) struct UpdateExpression {
+    set: Vec<String>,
+    remove: Vec<String>,
+    names: HashMap<String, String>,
+    values: HashMap<String, AttributeValue>,
+}
+
+impl UpdateExpression {
+    pub(crate) fn set(&mut self, attribute: &str, value: AttributeValue) {
+        let index = self.names.len();
+        self.names
+            .insert(format!("#u{index}"), attribute.to_string());
+        self.values.insert(format!(":u{index}"), value);
+        self.set.push(format!("#u{index} = :u{index}"));
+    }
+
+    pub(crate) fn remove(&mut self, attribute: &str) {
+        let index = self.names.len();
+        self.names
+            .insert(format!("#u{index}"), attribute.to_string());
+        self.remove.push(format!("#u{index}"));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.set.is_empty() && self.remove.is_empty()
+    }
+
+    fn expression(&self) -> String {
+        let mut clauses = Vec::new();
+        if !self.set.is_empty() {
+            clauses.push(format!("SET {}", self.set.join(", ")));
+        }
+        if !self.remove.is_empty() {
+            clauses.push(format!("REMOVE {}", self.remove.join(", ")));
+        }
+        clauses.join(" ")
+    }
+
+    pub(crate) fn apply(self, request: UpdateItemFluentBuilder) -> UpdateItemFluentBuilder {
+        let request = request.update_expression(self.expression());
+        let request = self
+            .names
+            .into_iter()
+            .fold(request, |request, (placeholder, name)| {
+                request.expression_attribute_names(placeholder, name)
+            });
+        self.values
+            .into_iter()
+            .fold(request, |request, (placeholder, value)| {
+                request.expression_attribute_values(placeholder, value)
+            })
+    }
+}
+
+/// Sets `condition` as the condition expression of `request`, with its `#f{i}`/`:f{i}`
+/// placeholders.
+pub(crate) fn with_condition(
+    request: UpdateItemFluentBuilder,
+    condition: &Filter,
+) -> UpdateItemFluentBuilder {
+    let mut names = HashMap::new();
+    let mut values = HashMap::new();
+    let expression = condition.render(&mut names, &mut values);
+
+    let request = names.into_iter().fold(
+        request.condition_expression(expression),
+        |request, (placeholder, name)| request.expression_attribute_names(placeholder, name),
+    );
+    values
+        .into_iter()
+        .fold(request, |request, (placeholder, value)| {
+            request.expression_attribute_values(placeholder, value)
+        })
+}
+
+impl DynamoDbStore {
+    /// Sends `update` for the item at `key`; an empty update sends nothing.
+    pub(crate) async fn send_update(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        update: UpdateExpression,
+    ) -> Result<(), Error> {
+        if update.is_empty() {
+            return Ok(());
+        }
+
+        self.send_update_if(table_name, key, update, None).await
+    }
+
+    /// Sends `update` for the item at `key`, only applying it if `condition` holds.
+    pub(crate) async fn send_update_if(
+        &self,
+        table_name: &str,
+        key: HashMap<String, AttributeValue>,
+        update: UpdateExpression,
+        condition: Option<Filter>,
+    ) -> Result<(), Error> {
+        let operation = Operation::new("UpdateItem", table_name).attributes(&key);
+        let request = self
+            .client()
+            .update_item()
+            .table_name(table_name)
+            .set_key(Some(key));
+        let request = match &condition {
+            Some(condition) => with_condition(request, condition),
+            None => request,
+        };
+        operation
+            .send(update.apply(request).checked()?.send())
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl TableBoundStore {
+    /// Sets every attribute of `changes` on the item at `key`, but only if its stored
+    /// `attribute` equals `expected`, e.g. to move an order out of `"OPEN"` exactly once.
+    ///
+    /// `attribute` is the stored attribute name, after the table's naming convention.
+    /// Returns `false` without changing anything if the item is missing or `attribute`
+    /// holds another value. Null attributes and key attributes of `changes` are skipped.
+    pub async fn update_if_eq<K: Serialize, U: Serialize>(
+        &self,
+        key: &K,
+        attribute: &str,
+        expected: AttributeValue,
+        changes: &U,
+    ) -> Result<bool, Error> {
+        self.run(|| async {
+            let key = self.to_key(key)?;
+
+            let mut update = UpdateExpression::default();
+            for (name, value) in self.to_item(changes)? {
+                if !key.contains_key(&name) && !matches!(value, AttributeValue::Null(_)) {
+                    update.set(&name, value);
+                }
+            }
+            if update.is_empty() {
+                return Err(Error::Validation(
+                    "update_if_eq needs at least one attribute to change".to_string(),
+                ));
+            }
+
+            let condition = Filter::eq(attribute, expected.clone());
+            match self
+                .store()
+                .send_update_if(self.table_name(), key, update, Some(condition))
+                .await
+            {
+                Ok(()) => Ok(true),
+                Err(err) if err.is_conditional_check_failed() => Ok(false),
+                Err(err) => Err(err),
+            }
+        })
+        .await
+    }
+}
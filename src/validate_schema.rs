@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::Serialize;
+
+use crate::{schema::KeyAttribute, DynamoDbStore, Error, ScalarType};
+
+impl DynamoDbStore {
+    /// Checks that `T` serializes the table's key attributes with the right types.
+    ///
+    /// `T::default()` is serialized and compared against the key schema from
+    /// `describe_table`, so run this once at startup rather than per request.
+    pub async fn validate_schema<T: Serialize + Default>(
+        &self,
+        table_name: &str,
+    ) -> Result<(), Error> {
+        let description = self.describe_table(table_name).await?;
+        let item: HashMap<String, AttributeValue> = serde_dynamo::to_item(T::default())?;
+
+        let problems: Vec<String> = std::iter::once(&description.partition_key)
+            .chain(description.sort_key.as_ref())
+            .filter_map(|key| key_problem(key, &item))
+            .collect();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::SchemaMismatch {
+                table_name: table_name.to_string(),
+                problems,
+            })
+        }
+    }
+}
+
+fn key_problem(key: &KeyAttribute, item: &HashMap<String, AttributeValue>) -> Option<String> {
+    let Some(value) = item.get(&key.name) else {
+        return Some(format!("key attribute `{}` is not serialized", key.name));
+    };
+
+    let matches = matches!(
+        (&key.scalar_type, value),
+        (ScalarType::S, AttributeValue::S(_))
+            | (ScalarType::N, AttributeValue::N(_))
+            | (ScalarType::B, AttributeValue::B(_))
+    );
+
+    (!matches).then(|| {
+        format!(
+            "key attribute `{}` must be {} but serializes as {}",
+            key.name,
+            key.scalar_type.as_str(),
+            attribute_type(value)
+        )
+    })
+}
+
+fn attribute_type(value: &AttributeValue) -> &'static str {
+    match value {
+        AttributeValue::S(_) => "S",
+        AttributeValue::N(_) => "N",
+        AttributeValue::B(_) => "B",
+        AttributeValue::Bool(_) => "BOOL",
+        AttributeValue::Null(_) => "NULL",
+        AttributeValue::M(_) => "M",
+        AttributeValue::L(_) => "L",
+        AttributeValue::Ss(_) => "SS",
+        AttributeValue::Ns(_) => "NS",
+        AttributeValue::Bs(_) => "BS",
+        _ => "an unknown type",
+    }
+}
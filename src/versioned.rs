@@ -0,0 +1,145 @@
+use aws_sdk_dynamodb::types::AttributeValue;
+use serde::Serialize;
+
+use crate::{
+    expression::CheckedExpressions, instrument::Operation, limits::validate_item,
+    table::TableBoundStore, Error,
+};
+
+pub(crate) const DEFAULT_VERSION_ATTRIBUTE: &str = "version";
+
+/// An item carrying a version number used for optimistic locking.
+///
+/// Version `0` means the item has never been stored.
+pub trait Versioned {
+    fn version(&self) -> u64;
+    fn set_version(&mut self, version: u64);
+}
+
+impl TableBoundStore {
+    /// Stores `item` only if the stored version still matches `item.version()`, then
+    /// bumps the version on both sides.
+    pub async fn put_versioned<T: Serialize + Versioned>(&self, item: &mut T) -> Result<(), Error> {
+        let expected = item.version();
+        let next = next_version(expected)?;
+
+        self.run(|| async {
+            let mut attributes = self.to_item(&*item)?;
+            attributes.insert(
+                self.version_attribute().to_string(),
+                AttributeValue::N(next.to_string()),
+            );
+            validate_item(&attributes)?;
+
+            let operation = Operation::new("PutItem", self.table_name()).attributes(&attributes);
+            let request = self
+                .store()
+                .client()
+                .put_item()
+                .table_name(self.table_name())
+                .set_item(Some(attributes))
+                .expression_attribute_names("#version", self.version_attribute());
+            let request = if expected == 0 {
+                request.condition_expression("attribute_not_exists(#version)")
+            } else {
+                request
+                    .condition_expression("#version = :expected")
+                    .expression_attribute_values(
+                        ":expected",
+                        AttributeValue::N(expected.to_string()),
+                    )
+            };
+
+            operation
+                .send(request.checked()?.send())
+                .await
+                .map_err(|err| self.version_conflict(err.into(), expected))?;
+            Ok(())
+        })
+        .await?;
+
+        item.set_version(next);
+        Ok(())
+    }
+
+    /// Sets every attribute of `changes` on the item at `key` if its stored version is
+    /// `expected_version`, and returns the incremented version.
+    pub async fn update_versioned<K: Serialize, U: Serialize>(
+        &self,
+        key: &K,
+        expected_version: u64,
+        changes: &U,
+    ) -> Result<u64, Error> {
+        let next = next_version(expected_version)?;
+
+        self.run(|| async {
+            let key = self.to_key(key)?;
+            let changes = self.to_item(changes)?;
+
+            let mut request = self
+                .store()
+                .client()
+                .update_item()
+                .table_name(self.table_name())
+                .set_key(Some(key.clone()))
+                .condition_expression("#version = :expected")
+                .expression_attribute_names("#version", self.version_attribute())
+                .expression_attribute_values(
+                    ":expected",
+                    AttributeValue::N(expected_version.to_string()),
+                )
+                .expression_attribute_values(":next", AttributeValue::N(next.to_string()));
+
+            let mut assignments = vec!["#version = :next".to_string()];
+            let changes = changes
+                .into_iter()
+                .filter(|(name, _)| !key.contains_key(name) && name != self.version_attribute());
+            for (index, (name, value)) in changes.enumerate() {
+                assignments.push(format!("#a{index} = :a{index}"));
+                request = request
+                    .expression_attribute_names(format!("#a{index}"), name)
+                    .expression_attribute_values(format!(":a{index}"), value);
+            }
+
+            let operation = Operation::new("UpdateItem", self.table_name()).attributes(&key);
+            let request = request
+                .update_expression(format!("SET {}", assignments.join(", ")))
+                .checked()?
+                .send();
+            operation
+                .send(request)
+                .await
+                .map_err(|err| self.version_conflict(err.into(), expected_version))?;
+            Ok(next)
+        })
+        .await
+    }
+
+    fn version_conflict(&self, err: Error, expected: u64) -> Error {
+        if err.is_conditional_check_failed() {
+            Error::VersionConflict {
+                table_name: self.table_name().to_string(),
+                expected,
+            }
+        } else {
+            err
+        }
+    }
+}
+
+fn next_version(version: u64) -> Result<u64, Error> {
+    version
+        .checked_add(1)
+        .ok_or_else(|| Error::Validation(format!("version {version} cannot be incremented")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_last_version_cannot_be_incremented() {
+        assert_eq!(next_version(41).unwrap(), 42);
+        assert!(matches!(next_version(u64::MAX), Err(Error::Validation(_))));
+    }
+}
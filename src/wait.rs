@@ -0,0 +1,67 @@
+use std::{future::Future, time::Duration};
+
+use aws_sdk_dynamodb::types::{IndexStatus, TableStatus};
+
+use crate::{instrument::Operation, retry::RetryConfig, DynamoDbStore, Error};
+
+const POLL_BACKOFF: RetryConfig = RetryConfig {
+    max_attempts: u32::MAX,
+    initial_backoff: Duration::from_millis(250),
+    max_backoff: Duration::from_secs(5),
+};
+
+impl DynamoDbStore {
+    /// Waits until the table and all of its global secondary indexes are `ACTIVE`.
+    pub async fn wait_until_active(
+        &self,
+        table_name: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        poll_until(
+            format!("table {table_name} to become active"),
+            timeout,
+            || async {
+                let operation = Operation::new("DescribeTable", table_name);
+                let request = self.client().describe_table().table_name(table_name).send();
+                let output = operation.send(request).await?;
+
+                let Some(table) = output.table else {
+                    return Ok(false);
+                };
+                let indexes_active = table
+                    .global_secondary_indexes()
+                    .iter()
+                    .all(|index| index.index_status() == Some(&IndexStatus::Active));
+
+                Ok(table.table_status() == Some(&TableStatus::Active) && indexes_active)
+            },
+        )
+        .await
+    }
+}
+
+pub(crate) async fn poll_until<F, Fut>(
+    waiting_for: String,
+    timeout: Duration,
+    mut check: F,
+) -> Result<(), Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<bool, Error>>,
+{
+    let poll = async {
+        let mut attempt = 0;
+        while !check().await? {
+            tokio::time::sleep(POLL_BACKOFF.backoff(attempt)).await;
+            attempt += 1;
+        }
+        Ok(())
+    };
+
+    tokio::time::timeout(timeout, poll)
+        .await
+        .map_err(|_| Error::Timeout {
+            waiting_for,
+            waited: timeout,
+        })?
+}
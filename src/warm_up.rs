@@ -0,0 +1,23 @@
+use aws_sdk_dynamodb::error::SdkError;
+
+use crate::{instrument::Operation, DynamoDbStore, Error};
+
+impl DynamoDbStore {
+    /// Resolves credentials and opens a connection to DynamoDB ahead of the first real
+    /// request, e.g. during a Lambda's init phase, which is not billed against the first
+    /// invocation's latency.
+    ///
+    /// Sends a `ListTables` request for one table name. An error response, such as a
+    /// missing `ListTables` permission, still counts as warmed up, since credentials were
+    /// resolved and the connection opened to get it. Fails if credentials cannot be
+    /// resolved or DynamoDB cannot be reached.
+    pub async fn warm_up(&self) -> Result<(), Error> {
+        let operation = Operation::new("ListTables", "");
+        let request = self.client().list_tables().limit(1).send();
+
+        match operation.send(request).await {
+            Ok(_) | Err(SdkError::ServiceError(_)) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
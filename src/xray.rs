@@ -0,0 +1,115 @@
+use std::{
+    net::UdpSocket,
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::json;
+
+const TRACE_HEADER_ENV: &str = "_X_AMZN_TRACE_ID";
+const DAEMON_ADDRESS_ENV: &str = "AWS_XRAY_DAEMON_ADDRESS";
+const DEFAULT_DAEMON_ADDRESS: &str = "127.0.0.1:2000";
+
+static SOCKET: OnceLock<Option<UdpSocket>> = OnceLock::new();
+
+pub(crate) enum Outcome {
+    Success,
+    Error { throttled: bool },
+}
+
+struct TraceHeader {
+    root: String,
+    parent: String,
+}
+
+impl TraceHeader {
+    fn from_env() -> Option<Self> {
+        let header = std::env::var(TRACE_HEADER_ENV).ok()?;
+
+        let mut root = None;
+        let mut parent = None;
+        let mut sampled = false;
+        for part in header.split(';') {
+            match part.trim().split_once('=') {
+                Some(("Root", value)) => root = Some(value.to_string()),
+                Some(("Parent", value)) => parent = Some(value.to_string()),
+                Some(("Sampled", value)) => sampled = value == "1",
+                _ => {}
+            }
+        }
+
+        if !sampled {
+            return None;
+        }
+
+        Some(Self {
+            root: root?,
+            parent: parent?,
+        })
+    }
+}
+
+pub(crate) fn record_subsegment(
+    operation: &str,
+    table: &str,
+    started: SystemTime,
+    ended: SystemTime,
+    outcome: Outcome,
+) {
+    let Some(trace) = TraceHeader::from_env() else {
+        return;
+    };
+    let Some(socket) = SOCKET
+        .get_or_init(|| UdpSocket::bind("0.0.0.0:0").ok())
+        .as_ref()
+    else {
+        return;
+    };
+
+    let (error, throttle) = match outcome {
+        Outcome::Success => (false, false),
+        Outcome::Error { throttled } => (true, throttled),
+    };
+
+    let segment = json!({
+        "name": "DynamoDB",
+        "id": format!("{:016x}", fastrand::u64(..)),
+        "trace_id": trace.root,
+        "parent_id": trace.parent,
+        "type": "subsegment",
+        "namespace": "aws",
+        "start_time": epoch_seconds(started),
+        "end_time": epoch_seconds(ended),
+        "error": error,
+        "throttle": throttle,
+        "aws": {
+            "operation": operation,
+            "table_name": table,
+        },
+        "annotations": {
+            "operation": operation,
+            "table_name": table,
+        },
+    });
+
+    let packet = format!("{{\"format\": \"json\", \"version\": 1}}\n{segment}");
+    let _ = socket.send_to(packet.as_bytes(), daemon_address());
+}
+
+fn daemon_address() -> String {
+    let configured = std::env::var(DAEMON_ADDRESS_ENV).unwrap_or_default();
+
+    // The daemon address is either "host:port" or "tcp:host:port udp:host:port".
+    let udp = configured
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix("udp:"))
+        .or_else(|| (!configured.is_empty()).then_some(configured.as_str()));
+
+    udp.unwrap_or(DEFAULT_DAEMON_ADDRESS).to_string()
+}
+
+fn epoch_seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use clean_dynamodb_store::{DynamoEntity, DynamoKey};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, DynamoEntity)]
+#[dynamo(table = "users", pk = "id", sk = "sk")]
+struct User {
+    id: String,
+    sk: String,
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DynamoEntity)]
+#[serde(rename_all = "camelCase")]
+#[dynamo(table = "orders", key = "OrderId")]
+struct Order {
+    #[dynamo(pk)]
+    customer_id: String,
+    #[dynamo(sk)]
+    #[serde(rename = "order")]
+    order_number: u64,
+    total_cents: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, DynamoKey)]
+struct Session {
+    #[dynamo(pk)]
+    user_id: String,
+    token: String,
+}
+
+fn attribute_names<T: Serialize>(value: &T) -> Vec<String> {
+    let item: HashMap<String, AttributeValue> = serde_dynamo::to_item(value).unwrap();
+    let mut names: Vec<String> = item.into_keys().collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn the_entity_is_bound_to_its_table() {
+    assert_eq!(User::TABLE, "users");
+    assert_eq!(Order::TABLE, "orders");
+}
+
+#[test]
+fn the_key_holds_only_the_key_fields() {
+    let user = User {
+        id: "1".to_string(),
+        sk: "PROFILE".to_string(),
+        name: "Ada".to_string(),
+    };
+
+    assert_eq!(
+        user.key(),
+        UserKey {
+            id: "1".to_string(),
+            sk: "PROFILE".to_string(),
+        }
+    );
+    assert_eq!(attribute_names(&user.key()), ["id", "sk"]);
+}
+
+#[test]
+fn field_markers_and_serde_renames_carry_over_to_the_key() {
+    let order = Order {
+        customer_id: "c-1".to_string(),
+        order_number: 7,
+        total_cents: 1_250,
+    };
+
+    let key: OrderId = order.key();
+
+    assert_eq!(key.order_number, 7);
+    assert_eq!(attribute_names(&key), ["customerId", "order"]);
+    assert!(attribute_names(&order).contains(&"totalCents".to_string()));
+}
+
+#[test]
+fn dynamo_key_generates_the_key_without_a_table() {
+    let session = Session {
+        user_id: "u-1".to_string(),
+        token: "secret".to_string(),
+    };
+
+    let key: SessionKey = session.key();
+
+    assert_eq!(key.user_id, "u-1");
+    assert_eq!(attribute_names(&key), ["user_id"]);
+}